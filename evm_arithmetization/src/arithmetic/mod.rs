@@ -7,6 +7,7 @@ use self::columns::{
 use self::utils::u256_to_array;
 use crate::arithmetic::columns::IS_RANGE_CHECK;
 use crate::extension_tower::BN_BASE;
+use crate::generation::prover_input::div_mod_hint;
 use crate::util::{addmod, mulmod, submod};
 
 mod addcy;
@@ -55,13 +56,7 @@ impl BinaryOperator {
                 }
             }
             BinaryOperator::Sub => input0.overflowing_sub(input1).0,
-            BinaryOperator::Div => {
-                if input1.is_zero() {
-                    U256::zero()
-                } else {
-                    input0 / input1
-                }
-            }
+            BinaryOperator::Div => div_mod_hint(input0, input1).0,
             BinaryOperator::Shr => {
                 if input0 < U256::from(256usize) {
                     input1 >> input0
@@ -69,13 +64,7 @@ impl BinaryOperator {
                     U256::zero()
                 }
             }
-            BinaryOperator::Mod => {
-                if input1.is_zero() {
-                    U256::zero()
-                } else {
-                    input0 % input1
-                }
-            }
+            BinaryOperator::Mod => div_mod_hint(input0, input1).1,
             BinaryOperator::Lt => U256::from((input0 < input1) as u8),
             BinaryOperator::Gt => U256::from((input0 > input1) as u8),
             BinaryOperator::AddFp254 => addmod(input0, input1, BN_BASE),
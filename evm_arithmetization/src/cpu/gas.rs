@@ -18,6 +18,13 @@ const G_LOW: Option<u32> = Some(5);
 const G_MID: Option<u32> = Some(8);
 const G_HIGH: Option<u32> = Some(10);
 
+#[cfg(test)]
+const G_MEMORY: u128 = 3;
+#[cfg(test)]
+const G_QUADCOEFFDIV: u128 = 512;
+#[cfg(test)]
+const G_CALLSTIPEND: u64 = 2300;
+
 const SIMPLE_OPCODES: OpsColumnsView<Option<u32>> = OpsColumnsView {
     binary_op: None,  // This is handled manually below
     ternary_op: None, // This is handled manually below
@@ -39,6 +46,45 @@ const SIMPLE_OPCODES: OpsColumnsView<Option<u32>> = OpsColumnsView {
     exception: None,
 };
 
+/// Returns the gas charged for expanding the active memory from
+/// `current_words` to `new_words` 32-byte words, as computed by the kernel's
+/// `%update_mem_words`. Shrinking the memory is free, and a cost which doesn't
+/// fit in a `u64` saturates to `u64::MAX`, which is out of gas for any
+/// transaction. The kernel tests check the gas charged against it.
+#[cfg(test)]
+pub(crate) fn memory_expansion_cost(current_words: u64, new_words: u64) -> u64 {
+    if new_words <= current_words {
+        return 0;
+    }
+    (memory_cost(new_words) - memory_cost(current_words))
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+/// The total cost of `num_words` words of memory, i.e.
+/// `G_MEMORY * num_words + num_words^2 / 512`. This can't overflow a `u128`.
+#[cfg(test)]
+fn memory_cost(num_words: u64) -> u128 {
+    let num_words = num_words as u128;
+    G_MEMORY * num_words + num_words * num_words / G_QUADCOEFFDIV
+}
+
+/// Returns the gas limit of the sub-context created by a `*CALL` requesting
+/// `requested_gas`, when `available_gas` is left once the other costs of the
+/// call have been charged, as computed by the kernel's `call_charge_gas`. At
+/// most all but one 64th of the available gas is forwarded, and value-bearing
+/// calls add the stipend on top of it. The kernel tests check the gas
+/// forwarded against it.
+#[cfg(test)]
+pub(crate) fn call_gas(requested_gas: u64, available_gas: u64, transfers_value: bool) -> u64 {
+    let forwarded = requested_gas.min(available_gas - available_gas / 64);
+    if transfers_value {
+        forwarded + G_CALLSTIPEND
+    } else {
+        forwarded
+    }
+}
+
 fn eval_packed_accumulate<P: PackedField>(
     lv: &CpuColumnsView<P>,
     nv: &CpuColumnsView<P>,
@@ -326,3 +372,47 @@ pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
     // Evaluates the initial gas constraints.
     eval_ext_circuit_init(builder, lv, nv, yield_constr);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{call_gas, memory_expansion_cost};
+    use crate::cpu::kernel::constants::MIN_MAX_MEMORY_BYTES;
+
+    #[test]
+    fn test_memory_expansion_cost() {
+        assert_eq!(memory_expansion_cost(0, 0), 0);
+        assert_eq!(memory_expansion_cost(0, 1), 3);
+        assert_eq!(memory_expansion_cost(0, 32), 98);
+        assert_eq!(memory_expansion_cost(32, 1), 0);
+
+        // Around the 512-word threshold, where the quadratic term reaches the
+        // cost of a single word.
+        assert_eq!(memory_expansion_cost(0, 512), 2048);
+        assert_eq!(memory_expansion_cost(511, 512), 5);
+        assert_eq!(memory_expansion_cost(512, 513), 5);
+
+        // Beyond 1536 words, the quadratic term dominates.
+        assert_eq!(memory_expansion_cost(0, 1536), 9216);
+        assert_eq!(memory_expansion_cost(1536, 10_000), 216_096);
+
+        // The cost of a full 64-bit memory doesn't fit in a `u64`.
+        assert_eq!(memory_expansion_cost(0, u64::MAX), u64::MAX);
+        assert_eq!(memory_expansion_cost(1 << 40, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_max_memory_bytes_is_out_of_gas() {
+        // The kernel rejects gas amounts which don't fit in a `u32`.
+        let max_words = MIN_MAX_MEMORY_BYTES.1 / 32;
+        assert!(memory_expansion_cost(0, max_words) > u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_call_gas() {
+        assert_eq!(call_gas(1000, 6400, false), 1000);
+        assert_eq!(call_gas(6400, 6400, false), 6300);
+        assert_eq!(call_gas(u64::MAX, 63, false), 63);
+        assert_eq!(call_gas(0, 6400, true), 2300);
+        assert_eq!(call_gas(u64::MAX, 6400, true), 8600);
+    }
+}
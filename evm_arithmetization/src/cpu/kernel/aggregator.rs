@@ -6,9 +6,15 @@ use once_cell::sync::Lazy;
 use super::assembler::{assemble, Kernel};
 use crate::cpu::kernel::constants::evm_constants;
 use crate::cpu::kernel::parser::parse;
+use crate::generation::state::KernelHooks;
 
 pub static KERNEL: Lazy<Kernel> = Lazy::new(combined_kernel);
 
+/// The offsets of the labels of `KERNEL` which the CPU simulation hooks into,
+/// so that they are looked up once rather than for every state.
+pub(crate) static KERNEL_HOOKS: Lazy<KernelHooks> =
+    Lazy::new(|| KernelHooks::new(&KERNEL).expect("The kernel defines `main` and `halt`"));
+
 pub(crate) fn combined_kernel() -> Kernel {
     let files = vec![
         "global jumped_to_0: PANIC",
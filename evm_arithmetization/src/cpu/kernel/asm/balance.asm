@@ -5,10 +5,7 @@ global sys_balance:
     SWAP1
     DUP2 %insert_accessed_addresses
     // stack: cold_access, kexit_info, address
-    PUSH @GAS_COLDACCOUNTACCESS_MINUS_WARMACCESS
-    MUL
-    PUSH @GAS_WARMACCESS
-    ADD
+    %account_access_gas
     %charge_gas
     // stack: kexit_info, address
 
@@ -15,6 +15,71 @@ global sys_gas:
     %mload_context_metadata(@CTX_METADATA_GAS_LIMIT)
 %endmacro
 
+// Replaces the Shanghai gas value on top of the stack by `istanbul` if the
+// block's hardfork is Istanbul. This relies on `@HARDFORK_SHANGHAI` being 0
+// and `@HARDFORK_ISTANBUL` being 1.
+%macro select_by_hardfork(istanbul)
+    // stack: shanghai_value
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_HARDFORK)
+    // stack: is_istanbul, shanghai_value
+    DUP1 ISZERO SWAP1
+    // stack: is_istanbul, is_shanghai, shanghai_value
+    %mul_const($istanbul)
+    SWAP2 MUL ADD
+    // stack: value
+%endmacro
+
+// Gas charged for accessing an account, as in `BALANCE` or `EXTCODESIZE`.
+%macro account_access_gas
+    // stack: cold_access
+    %mul_const(@GAS_COLDACCOUNTACCESS_MINUS_WARMACCESS)
+    %add_const(@GAS_WARMACCESS)
+    %select_by_hardfork(@GAS_ACCOUNTACCESS_ISTANBUL)
+    // stack: gas
+%endmacro
+
+// Gas charged by `SLOAD`.
+%macro sload_gas
+    // stack: cold_access
+    %mul_const(@GAS_COLDSLOAD_MINUS_WARMACCESS)
+    %add_const(@GAS_WARMACCESS)
+    %select_by_hardfork(@GAS_SLOAD_ISTANBUL)
+    // stack: gas
+%endmacro
+
+// Extra gas charged by `SSTORE` on a cold storage slot.
+%macro sstore_cold_gas
+    // stack: cold_access
+    %mul_const(@GAS_COLDSLOAD)
+    %select_by_hardfork(@GAS_ZERO)
+    // stack: gas
+%endmacro
+
+// Gas charged by a no-op or dirty `SSTORE`, excluding the cold access cost.
+%macro sstore_warm_gas
+    PUSH @GAS_WARMACCESS
+    %select_by_hardfork(@GAS_SLOAD_ISTANBUL)
+%endmacro
+
+// Gas charged by an `SSTORE` modifying a clean, non-zero storage slot,
+// excluding the cold access cost.
+%macro sstore_reset_gas
+    PUSH @GAS_SRESET
+    %select_by_hardfork(@GAS_SRESET_ISTANBUL)
+%endmacro
+
+// Gas refunded for clearing a storage slot.
+%macro sstore_clear_refund
+    PUSH @REFUND_SCLEAR
+    %select_by_hardfork(@REFUND_SCLEAR_ISTANBUL)
+%endmacro
+
+// Maximum refund, as a divisor of the gas used by the transaction.
+%macro max_refund_quotient
+    PUSH @MAX_REFUND_QUOTIENT
+    %select_by_hardfork(@MAX_REFUND_QUOTIENT_ISTANBUL)
+%endmacro
+
 
 // TODO: `%refund_gas` and `refund_gas_hook` are hooks used for debugging. They should be removed at some point and `refund_gas_original` renamed to `refund_gas`.
 %macro refund_gas
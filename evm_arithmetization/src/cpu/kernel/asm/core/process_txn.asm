@@ -37,7 +37,7 @@ global process_normalized_txn:
     %assert_eq(invalid_txn_1)
     // stack: sender, retdest
 
-    // Assert sender has no code.
+    // Assert sender has no code (EIP-3607).
     DUP1 %ext_code_empty %assert_nonzero(invalid_txn_1)
     // stack: sender, retdest
 
@@ -334,7 +334,7 @@ process_message_txn_fail:
     // stack: used_gas, leftover_gas
     %mload_global_metadata(@GLOBAL_METADATA_REFUND_COUNTER)
     // stack: refund, used_gas, leftover_gas
-    DUP2 %div_const(@MAX_REFUND_QUOTIENT) // max_refund = used_gas/5
+    %max_refund_quotient DUP3 DIV // max_refund = used_gas/quotient
     // stack: max_refund, refund, used_gas, leftover_gas
     %min
     %stack (refund, used_gas, leftover_gas) -> (leftover_gas, refund, refund, used_gas)
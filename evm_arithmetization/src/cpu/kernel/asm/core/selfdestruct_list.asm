@@ -13,6 +13,37 @@
     %mstore_global_metadata(@GLOBAL_METADATA_SELFDESTRUCT_LIST_LEN) // Store new length.
 %endmacro
 
+/// Returns 1 if the address is in the list, 0 otherwise.
+global selfdestruct_list_contains:
+    // stack: addr, retdest
+    %mload_global_metadata(@GLOBAL_METADATA_SELFDESTRUCT_LIST_LEN)
+    // stack: len, addr, retdest
+    PUSH @SEGMENT_SELFDESTRUCT_LIST ADD
+    PUSH @SEGMENT_SELFDESTRUCT_LIST
+selfdestruct_list_contains_loop:
+    // `i` and `len` are both scaled by SEGMENT_SELFDESTRUCT_LIST
+    // stack: i, len, addr, retdest
+    DUP2 DUP2 EQ %jumpi(selfdestruct_list_contains_not_found)
+    // stack: i, len, addr, retdest
+    DUP1 MLOAD_GENERAL
+    // stack: loaded_addr, i, len, addr, retdest
+    DUP4 EQ %jumpi(selfdestruct_list_contains_found)
+    // stack: i, len, addr, retdest
+    %increment %jump(selfdestruct_list_contains_loop)
+selfdestruct_list_contains_found:
+    %stack (i, len, addr, retdest) -> (retdest, 1)
+    JUMP
+selfdestruct_list_contains_not_found:
+    %stack (i, len, addr, retdest) -> (retdest, 0)
+    JUMP
+
+%macro selfdestruct_list_contains
+    %stack (addr) -> (addr, %%after)
+    %jump(selfdestruct_list_contains)
+%%after:
+    // stack: is_in_list
+%endmacro
+
 /// Remove one occurrence of the address from the list.
 /// Panics if the address is not in the list.
 global remove_selfdestruct_list:
@@ -74,6 +74,7 @@ global sys_selfdestruct:
     // Compute gas.
     // stack: cold_access, balance, address, recipient, kexit_info
     %mul_const(@GAS_COLDACCOUNTACCESS)
+    %select_by_hardfork(@GAS_ZERO)
     DUP2
     // stack: balance, gas_coldaccess, balance, address, recipient, kexit_info
     ISZERO %not_bit
@@ -85,6 +86,18 @@ global sys_selfdestruct:
     %charge_gas
     %stack (kexit_info, balance, address, recipient) -> (balance, address, recipient, kexit_info)
 
+    // Before EIP-3529, the first selfdestruct of an address is refunded.
+    // stack: balance, address, recipient, kexit_info
+    DUP2 %selfdestruct_list_contains ISZERO
+    // stack: first_selfdestruct, balance, address, recipient, kexit_info
+    PUSH 0 %select_by_hardfork(@REFUND_SELFDESTRUCT_ISTANBUL)
+    MUL
+    // stack: refund, balance, address, recipient, kexit_info
+    DUP1 ISZERO %jumpi(sys_selfdestruct_no_refund)
+    DUP1 %refund_gas
+sys_selfdestruct_no_refund:
+    POP
+
     // Insert address into the selfdestruct set.
     // stack: balance, address, recipient, kexit_info
     DUP2 %insert_selfdestruct_list
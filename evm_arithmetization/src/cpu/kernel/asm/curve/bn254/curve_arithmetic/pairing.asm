@@ -13,7 +13,7 @@
 /// def bn254_pairing(pairs: List((Curve, TwistedCurve))) -> Bool:
 ///     
 ///     for P, Q in pairs:
-///         if not (P.is_valid and Q.is_valid):
+///         if not (P.is_valid and Q.is_valid and Q.in_subgroup):
 ///             return @U256_MAX
 ///     
 ///     out = 1
@@ -73,8 +73,28 @@ bn254_input_check:
     ISZERO
     %jumpi(bn_pairing_invalid_input)
     // stack:         inp_j, j, k, inp
+    DUP1
+    %add_const(2)
+    // stack: inp_j', inp_j, j, k, inp
+    DUP1
+    %load_fp254_4
+    %bn_check_twisted_ident
+    // stack: ident?, inp_j', inp_j, j, k, inp
+    %jumpi(bn254_input_check_ident)
+    // stack:         inp_j', inp_j, j, k, inp
+    %stack (ptr) -> (ptr, bn254_input_check_subgroup)
+    %jump(bn_twisted_check_subgroup)
+bn254_input_check_subgroup:
+    // stack:  valid?, inp_j, j, k, inp
+    ISZERO
+    %jumpi(bn_pairing_invalid_input)
+    // stack:          inp_j, j, k, inp
     POP
     %jump(bn254_input_check)
+bn254_input_check_ident:
+    // stack:  inp_j', inp_j, j, k, inp
+    %pop2
+    %jump(bn254_input_check)
 
 bn_pairing_invalid_input:
     // stack:  inp_j, j, k, inp, out, retdest
@@ -92,3 +92,221 @@
     MUL // Cheaper than AND
     MUL // Cheaper than AND
 %endmacro
+
+// Check if a valid point Q of the twisted curve, different from the identity,
+// is in the subgroup of order BN_SCALAR, by computing [BN_SCALAR] * Q with a
+// double-and-add in affine coordinates.
+// Returns 1 if [BN_SCALAR] * Q is the identity, and 0 otherwise.
+//
+// Q is read from the 4 words starting at `ptr` in SEGMENT_BN_PAIRING.
+// The computation uses offsets 0-12 of SEGMENT_BN_PAIRING as scratch space:
+//     0-1: Q.x,  2-3: Q.y,  4-5: A.x,  6-7: A.y,  8-9: lambda,
+//   10-11: the new A.x,  12: whether A is the identity
+// where A is the accumulator.
+global bn_twisted_check_subgroup:
+    // stack:            ptr, retdest
+    DUP1
+    %load_fp254_2
+    // stack:       Q.x, ptr, retdest
+    DUP2  DUP2
+    %store_fp254_2(0)
+    %store_fp254_2(4)
+    // stack:            ptr, retdest
+    %add_const(2)
+    %load_fp254_2
+    // stack:            Q.y, retdest
+    DUP2  DUP2
+    %store_fp254_2(2)
+    %store_fp254_2(6)
+    // stack:                 retdest
+    PUSH 0
+    %mstore_bn254_pairing(12)
+    // The most significant bit of BN_SCALAR is bit 253, which is processed
+    // by setting A = Q.
+    PUSH 253
+    // stack:              i, retdest
+
+bn_twisted_subgroup_loop:
+    // stack:              i, retdest
+    DUP1
+    ISZERO
+    %jumpi(bn_twisted_subgroup_end)
+    %decrement
+    // stack:          i=i-1, retdest
+    PUSH bn_twisted_subgroup_double_done
+    %jump(bn_twisted_subgroup_double)
+
+bn_twisted_subgroup_double_done:
+    // stack:              i, retdest
+    PUSH @BN_SCALAR
+    DUP2
+    SHR
+    PUSH 1
+    AND
+    // stack:         bit_i, i, retdest
+    %jumpi(bn_twisted_subgroup_add)
+    %jump(bn_twisted_subgroup_loop)
+
+bn_twisted_subgroup_end:
+    // stack:              0, retdest
+    POP
+    %mload_bn254_pairing(12)
+    // stack:       is_ident, retdest
+    SWAP1
+    JUMP
+
+// A = 2 * A, then jump to `ret`.
+bn_twisted_subgroup_double:
+    // stack:         ret, i, retdest
+    %mload_bn254_pairing(12)
+    ISZERO
+    %jumpi(bn_twisted_subgroup_double_not_ident)
+    // The identity is unchanged.
+    JUMP
+bn_twisted_subgroup_double_not_ident:
+    PUSH 6
+    %load_fp254_2
+    // stack:        A.y, ret, i, retdest
+    OR
+    ISZERO
+    %jumpi(bn_twisted_subgroup_double_order_2)
+    // stack:         ret, i, retdest
+    PUSH 6
+    %load_fp254_2
+    DUP2  DUP2
+    %add_fp254_2
+    %inv_fp254_2
+    // stack: 1 / (2A.y), ret, i, retdest
+    PUSH 4
+    %load_fp254_2
+    DUP2  DUP2
+    %mul_fp254_2
+    PUSH 3
+    %scale_fp254_2
+    // stack: 3A.x^2, 1 / (2A.y), ret, i, retdest
+    %mul_fp254_2
+    // stack:     lambda, ret, i, retdest
+    %store_fp254_2(8)
+    PUSH 4
+    %load_fp254_2
+    DUP2  DUP2
+    %add_fp254_2
+    // stack:       2A.x, ret, i, retdest
+    PUSH 8
+    %load_fp254_2
+    DUP2  DUP2
+    %mul_fp254_2
+    // stack: lambda^2, 2A.x, ret, i, retdest
+    %sub_fp254_2
+    // stack:    new A.x, ret, i, retdest
+    %store_fp254_2(10)
+    %jump(bn_twisted_subgroup_update_y)
+
+bn_twisted_subgroup_double_order_2:
+    // stack:         ret, i, retdest
+    // A has order 2, so 2 * A is the identity.
+    PUSH 1
+    %mstore_bn254_pairing(12)
+    JUMP
+
+// A = A + Q
+bn_twisted_subgroup_add:
+    // stack:              i, retdest
+    %mload_bn254_pairing(12)
+    %jumpi(bn_twisted_subgroup_add_to_ident)
+    PUSH 4
+    %load_fp254_2
+    PUSH 0
+    %load_fp254_2
+    %sub_fp254_2
+    // stack:      Q.x - A.x, i, retdest
+    DUP2  DUP2
+    OR
+    ISZERO
+    %jumpi(bn_twisted_subgroup_add_same_x)
+    %inv_fp254_2
+    // stack: 1 / (Q.x - A.x), i, retdest
+    PUSH 6
+    %load_fp254_2
+    PUSH 2
+    %load_fp254_2
+    %sub_fp254_2
+    // stack: Q.y - A.y, 1 / (Q.x - A.x), i, retdest
+    %mul_fp254_2
+    // stack:         lambda, i, retdest
+    %store_fp254_2(8)
+    PUSH 0
+    %load_fp254_2
+    PUSH 4
+    %load_fp254_2
+    %add_fp254_2
+    // stack:      A.x + Q.x, i, retdest
+    PUSH 8
+    %load_fp254_2
+    DUP2  DUP2
+    %mul_fp254_2
+    // stack: lambda^2, A.x + Q.x, i, retdest
+    %sub_fp254_2
+    // stack:        new A.x, i, retdest
+    %store_fp254_2(10)
+    PUSH bn_twisted_subgroup_loop
+    %jump(bn_twisted_subgroup_update_y)
+
+bn_twisted_subgroup_add_same_x:
+    // stack:      Q.x - A.x, i, retdest
+    %pop2
+    PUSH 6
+    %load_fp254_2
+    PUSH 2
+    %load_fp254_2
+    %eq_fp254_2
+    // stack:     Q.y == A.y, i, retdest
+    %jumpi(bn_twisted_subgroup_add_same_point)
+    // A = -Q, so A + Q is the identity.
+    PUSH 1
+    %mstore_bn254_pairing(12)
+    %jump(bn_twisted_subgroup_loop)
+
+bn_twisted_subgroup_add_same_point:
+    // stack:              i, retdest
+    // A = Q, so A + Q = 2 * A. This never happens for points of order
+    // BN_SCALAR, but may happen for points of other orders.
+    PUSH bn_twisted_subgroup_loop
+    %jump(bn_twisted_subgroup_double)
+
+bn_twisted_subgroup_add_to_ident:
+    // stack:              i, retdest
+    // A is the identity, so A + Q = Q.
+    PUSH 0
+    %load_fp254_2
+    %store_fp254_2(4)
+    PUSH 2
+    %load_fp254_2
+    %store_fp254_2(6)
+    PUSH 0
+    %mstore_bn254_pairing(12)
+    %jump(bn_twisted_subgroup_loop)
+
+// Sets A.y = lambda * (A.x - new A.x) - A.y, then A.x = new A.x, and jumps
+// to `ret`.
+bn_twisted_subgroup_update_y:
+    // stack:         ret, i, retdest
+    PUSH 6
+    %load_fp254_2
+    PUSH 10
+    %load_fp254_2
+    PUSH 4
+    %load_fp254_2
+    %sub_fp254_2
+    // stack: A.x - new A.x, A.y, ret, i, retdest
+    PUSH 8
+    %load_fp254_2
+    %mul_fp254_2
+    %sub_fp254_2
+    // stack:        new A.y, ret, i, retdest
+    %store_fp254_2(6)
+    PUSH 10
+    %load_fp254_2
+    %store_fp254_2(4)
+    // stack:         ret, i, retdest
+    JUMP
@@ -24,6 +24,33 @@
 %endmacro
 
 
+// Returns the inverse of a nonzero element of Fp254_2.
+%macro inv_fp254_2
+    // stack:          a, b
+    DUP2
+    DUP1
+    MULFP254
+    // stack:    b^2, a, b
+    DUP2
+    DUP1
+    MULFP254
+    // stack: a^2, b^2, a, b
+    ADDFP254
+    %inv_fp254
+    // stack:       n, a, b   where n = 1 / (a^2 + b^2)
+    SWAP2
+    // stack:       b, a, n
+    DUP3
+    MULFP254
+    PUSH 0
+    SUBFP254
+    // stack:     -nb, a, n
+    SWAP2
+    // stack:     n, a, -nb
+    MULFP254
+    // stack:       na, -nb
+%endmacro
+
 global inv_fp254_12:
     // stack:                         inp, out, retdest
     %prover_inv_fp254_12
@@ -62,6 +62,17 @@
     // stack:   x0, x1
 %endmacro 
 
+%macro store_fp254_2(ptr)
+    // stack: x, x_
+    PUSH $ptr
+    %mstore_bn254_pairing
+    // stack: x_
+    PUSH $ptr
+    %add_const(1)
+    %mstore_bn254_pairing
+    // stack:
+%endmacro
+
 /// complex conjugate
 %macro conj_fp254_2
     // stack: a,  b
@@ -111,6 +122,20 @@
     // stack:        z, z_
 %endmacro
 
+%macro sub_fp254_2
+    // stack: x, x_, y, y_
+    SWAP3
+    // stack: y_, x_, y, x
+    SWAP1
+    // stack: x_, y_, y, x
+    SUBFP254
+    // stack:     z_, y, x
+    SWAP2
+    // stack:     x, y, z_
+    SUBFP254
+    // stack:        z, z_
+%endmacro
+
 /// Given z = x + iy: Fp254_2, return complex conjugate z': Fp254_2
 /// where input is represented z.re, z.im and output as z'.im, z'.re
 /// cost: 9; note this returns y, x for the output x + yi
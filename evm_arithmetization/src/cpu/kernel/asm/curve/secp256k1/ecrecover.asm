@@ -1,4 +1,7 @@
 // ecrecover precompile.
+// Returns U256_MAX if v is not 27 or 28, or if r or s is not in [1, N).
+// Note that high-s signatures are accepted: as in Ethereum, the EIP-2
+// restriction only applies to transaction signatures.
 global ecrecover:
     // stack: hash, v, r, s, retdest
 
@@ -9,6 +9,10 @@ global main:
     // stack: expected_hash, hash
     %assert_eq
 
+    // Check that the block's hardfork is supported.
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_HARDFORK)
+    %assert_le_const(@HARDFORK_ISTANBUL)
+
     // Initialise the shift table
     %shift_table_init
 
@@ -45,6 +49,13 @@ global hash_initial_tries:
     // stack: trie_data_full_len
     %mstore_global_metadata(@GLOBAL_METADATA_TRIE_DATA_SIZE)
 
+    // Load the memory offset bound used by `ensure_reasonable_offset`. It can only be raised
+    // above the default, and must keep offsets and sizes within 32 bits.
+    PROVER_INPUT(max_memory_bytes)
+    DUP1 %assert_ge_const(@MIN_MAX_MEMORY_BYTES)
+    DUP1 %assert_le_const(@MAX_MAX_MEMORY_BYTES)
+    %mstore_global_metadata(@GLOBAL_METADATA_MAX_MEMORY_BYTES)
+
 global start_txn:
     // stack: (empty)
     // The special case of an empty trie (i.e. for the first transaction)
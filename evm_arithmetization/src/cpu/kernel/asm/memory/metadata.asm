@@ -383,9 +383,12 @@ zero_hash:
 // We do this to avoid overflows in future gas-related calculations.
 %macro ensure_reasonable_offset
     // stack: offset
-    // The memory expansion cost, (50000000 / 32)^2 / 512, is around 2^32 gas,
-    // i.e. greater than any reasonable block limit.
-    %gt_const(50000000)
+    // The bound is at least MIN_MAX_MEMORY_BYTES, whose memory expansion cost,
+    // (MIN_MAX_MEMORY_BYTES / 32)^2 / 512, is around 2^32 gas, i.e. greater than any
+    // reasonable block limit.
+    %mload_global_metadata(@GLOBAL_METADATA_MAX_MEMORY_BYTES)
+    // stack: max_memory_bytes, offset
+    LT
     // stack: is_unreasonable
     %jumpi(fault_exception)
     // stack: (empty)
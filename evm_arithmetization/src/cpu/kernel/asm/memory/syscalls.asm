@@ -183,10 +183,7 @@ global sys_extcodecopy:
         -> (address, dest_offset, offset, size, kexit_info)
     %u256_to_addr DUP1 %insert_accessed_addresses
     // stack: cold_access, address, dest_offset, offset, size, kexit_info
-    PUSH @GAS_COLDACCOUNTACCESS_MINUS_WARMACCESS
-    MUL
-    PUSH @GAS_WARMACCESS
-    ADD
+    %account_access_gas
     // stack: Gaccess, address, dest_offset, offset, size, kexit_info
 
     DUP5
@@ -48,8 +48,7 @@ global sys_sload:
     // stack: cold_access, old_value, kexit_info, value
     SWAP1 POP
     // stack: cold_access, kexit_info, value
-    %mul_const(@GAS_COLDSLOAD_MINUS_WARMACCESS)
-    %add_const(@GAS_WARMACCESS)
+    %sload_gas
     %charge_gas
     // stack: kexit_info, value
     EXIT_KERNEL
@@ -12,7 +12,7 @@ global sys_sstore:
     %stack (addr, current_value, kexit_info, slot, value) -> (addr, slot, current_value, current_value, kexit_info, slot, value)
     %insert_accessed_storage_keys
     // stack: cold_access, original_value, current_value, kexit_info, slot, value
-    %mul_const(@GAS_COLDSLOAD)
+    %sstore_cold_gas
 
     // Check for warm access.
     %stack (gas, original_value, current_value, kexit_info, slot, value) ->
@@ -28,12 +28,12 @@ global sys_sstore:
 
     // Check for sreset (set a non-zero storage slot to a non-zero value).
     // stack: gas, original_value, current_value, kexit_info, slot, value
-    DUP2 ISZERO ISZERO %mul_const(@GAS_SRESET) ADD
+    DUP2 ISZERO ISZERO %sstore_reset_gas MUL ADD
     %jump(sstore_charge_gas)
 
 sstore_warm:
     // stack: gas, original_value, current_value, kexit_info, slot, value)
-    %add_const(@GAS_WARMACCESS)
+    %sstore_warm_gas ADD
 
 sstore_charge_gas:
     %stack (gas, original_value, current_value, kexit_info, slot, value) -> (gas, kexit_info, current_value, value, original_value, slot)
@@ -53,11 +53,11 @@ sstore_refund:
     %jump(sstore_dirty_reset)
 
 sstore_dirty_clear1:
-    PUSH @REFUND_SCLEAR PUSH 0 SUB %refund_gas
+    %sstore_clear_refund PUSH 0 SUB %refund_gas
     %jump(sstore_dirty_reset)
 
 sstore_dirty_clear2:
-    PUSH @REFUND_SCLEAR %refund_gas
+    %sstore_clear_refund %refund_gas
 
 sstore_dirty_reset:
     %stack (current_value, value, original_value, slot, kexit_info) -> (original_value, value, current_value, value, original_value, slot, kexit_info)
@@ -66,10 +66,10 @@ sstore_dirty_reset:
 sstore_dirty_reset2:
     %stack (current_value, value, original_value, slot, kexit_info) -> (original_value, current_value, value, original_value, slot, kexit_info)
     ISZERO %jumpi(sstore_dirty_reset_sset)
-    PUSH @GAS_WARMACCESS PUSH @GAS_SRESET SUB %refund_gas
+    %sstore_warm_gas %sstore_reset_gas SUB %refund_gas
     %jump(sstore_no_refund)
 sstore_dirty_reset_sset:
-    PUSH @GAS_WARMACCESS PUSH @GAS_SSET SUB %refund_gas
+    %sstore_warm_gas PUSH @GAS_SSET SUB %refund_gas
     %jump(sstore_no_refund)
 
 sstore_refund_original:
@@ -77,7 +77,7 @@ sstore_refund_original:
     ISZERO %jumpi(sstore_sclear)
     %jump(sstore_no_refund)
 sstore_sclear:
-    PUSH @REFUND_SCLEAR %refund_gas
+    %sstore_clear_refund %refund_gas
     %jump(sstore_no_refund)
 
 sstore_no_refund:
@@ -59,6 +59,13 @@ impl Kernel {
         }
     }
 
+    /// The Keccak hash of the kernel code, which the CPU stores in
+    /// `GlobalMetadata::KernelHash`. A proof can only be verified by a
+    /// verifier built with the kernel of the same hash.
+    pub fn code_hash(&self) -> H256 {
+        self.code_hash
+    }
+
     pub fn to_file(&self, path: &str) {
         let kernel_serialized = serde_json::to_string(self).unwrap();
         fs::write(path, kernel_serialized).expect("Unable to write kernel to file");
@@ -56,6 +56,8 @@ pub(crate) enum GlobalMetadata {
     BlockGasUsedAfter,
     /// Current block header hash
     BlockCurrentHash,
+    /// Identifier of the hardfork whose gas schedule applies to this block.
+    BlockHardfork,
 
     /// Gas to refund at the end of the transaction.
     RefundCounter,
@@ -94,10 +96,13 @@ pub(crate) enum GlobalMetadata {
 
     KernelHash,
     KernelLen,
+
+    /// Memory offsets beyond this bound fault before the memory is expanded.
+    MaxMemoryBytes,
 }
 
 impl GlobalMetadata {
-    pub(crate) const COUNT: usize = 47;
+    pub(crate) const COUNT: usize = 49;
 
     /// Unscales this virtual offset by their respective `Segment` value.
     pub(crate) const fn unscale(&self) -> usize {
@@ -149,10 +154,12 @@ impl GlobalMetadata {
             Self::LogsDataLen,
             Self::LogsPayloadLen,
             Self::BlockCurrentHash,
+            Self::BlockHardfork,
             Self::TxnNumberBefore,
             Self::TxnNumberAfter,
             Self::KernelHash,
             Self::KernelLen,
+            Self::MaxMemoryBytes,
         ]
     }
 
@@ -184,6 +191,7 @@ impl GlobalMetadata {
             Self::BlockGasUsedBefore => "GLOBAL_METADATA_BLOCK_GAS_USED_BEFORE",
             Self::BlockGasUsedAfter => "GLOBAL_METADATA_BLOCK_GAS_USED_AFTER",
             Self::BlockCurrentHash => "GLOBAL_METADATA_BLOCK_CURRENT_HASH",
+            Self::BlockHardfork => "GLOBAL_METADATA_BLOCK_HARDFORK",
             Self::RefundCounter => "GLOBAL_METADATA_REFUND_COUNTER",
             Self::AccessedAddressesLen => "GLOBAL_METADATA_ACCESSED_ADDRESSES_LEN",
             Self::AccessedStorageKeysLen => "GLOBAL_METADATA_ACCESSED_STORAGE_KEYS_LEN",
@@ -206,6 +214,7 @@ impl GlobalMetadata {
             Self::TxnNumberAfter => "GLOBAL_METADATA_TXN_NUMBER_AFTER",
             Self::KernelHash => "GLOBAL_METADATA_KERNEL_HASH",
             Self::KernelLen => "GLOBAL_METADATA_KERNEL_LEN",
+            Self::MaxMemoryBytes => "GLOBAL_METADATA_MAX_MEMORY_BYTES",
         }
     }
 }
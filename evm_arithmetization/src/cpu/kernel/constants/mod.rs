@@ -9,6 +9,7 @@ use crate::cpu::kernel::constants::journal_entry::JournalEntry;
 use crate::cpu::kernel::constants::trie_type::PartialTrieType;
 use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
 use crate::memory::segments::Segment;
+use crate::proof::Hardfork;
 
 pub(crate) mod context_metadata;
 mod exc_bitfields;
@@ -38,6 +39,14 @@ pub(crate) fn evm_constants() -> HashMap<String, U256> {
         c.insert(name.into(), U256::from(value));
     }
 
+    for (name, value) in ISTANBUL_GAS_CONSTANTS {
+        c.insert(name.into(), U256::from(value));
+    }
+
+    for (name, hardfork) in HARDFORKS {
+        c.insert(name.into(), U256::from(hardfork.id()));
+    }
+
     for (name, value) in PRECOMPILES {
         c.insert(name.into(), U256::from(value));
     }
@@ -56,6 +65,14 @@ pub(crate) fn evm_constants() -> HashMap<String, U256> {
 
     c.insert(MAX_NONCE.0.into(), U256::from(MAX_NONCE.1));
     c.insert(CALL_STACK_LIMIT.0.into(), U256::from(CALL_STACK_LIMIT.1));
+    c.insert(
+        MIN_MAX_MEMORY_BYTES.0.into(),
+        U256::from(MIN_MAX_MEMORY_BYTES.1),
+    );
+    c.insert(
+        MAX_MAX_MEMORY_BYTES.0.into(),
+        U256::from(MAX_MAX_MEMORY_BYTES.1),
+    );
 
     for segment in Segment::all() {
         c.insert(segment.var_name().into(), (segment as usize).into());
@@ -255,6 +272,24 @@ const GAS_CONSTANTS: [(&str, u16); 36] = [
 
 const REFUND_CONSTANTS: [(&str, u16); 2] = [("REFUND_SCLEAR", 4_800), ("MAX_REFUND_QUOTIENT", 5)];
 
+/// Gas and refund constants which replace the ones above when the block's
+/// hardfork is Istanbul, i.e. before EIP-2929 and EIP-3529.
+/// `REFUND_SELFDESTRUCT_ISTANBUL` has no counterpart, as EIP-3529 removed the
+/// refund for the first selfdestruct of an address.
+const ISTANBUL_GAS_CONSTANTS: [(&str, u16); 6] = [
+    ("GAS_SLOAD_ISTANBUL", 800),
+    ("GAS_ACCOUNTACCESS_ISTANBUL", 700),
+    ("GAS_SRESET_ISTANBUL", 5_000),
+    ("REFUND_SCLEAR_ISTANBUL", 15_000),
+    ("REFUND_SELFDESTRUCT_ISTANBUL", 24_000),
+    ("MAX_REFUND_QUOTIENT_ISTANBUL", 2),
+];
+
+const HARDFORKS: [(&str, Hardfork); 2] = [
+    ("HARDFORK_SHANGHAI", Hardfork::Shanghai),
+    ("HARDFORK_ISTANBUL", Hardfork::Istanbul),
+];
+
 const PRECOMPILES: [(&str, u16); 9] = [
     ("ECREC", 1),
     ("SHA256", 2),
@@ -293,3 +328,12 @@ const CODE_SIZE_LIMIT: [(&str, u64); 3] = [
 
 const MAX_NONCE: (&str, u64) = ("MAX_NONCE", 0xffffffffffffffff);
 const CALL_STACK_LIMIT: (&str, u64) = ("CALL_STACK_LIMIT", 1024);
+
+/// The default, and smallest allowed, value of
+/// `GlobalMetadata::MaxMemoryBytes`. The expansion cost of this many bytes
+/// exceeds the largest gas limit the kernel can handle, so the bound is only
+/// reached when gas would run out anyway.
+pub(crate) const MIN_MAX_MEMORY_BYTES: (&str, u64) = ("MIN_MAX_MEMORY_BYTES", 50_000_000);
+/// The largest allowed value of `GlobalMetadata::MaxMemoryBytes`, so that an
+/// offset and a size below it never overflow 32 bits when added.
+pub(crate) const MAX_MAX_MEMORY_BYTES: (&str, u64) = ("MAX_MAX_MEMORY_BYTES", u32::MAX as u64 / 2);
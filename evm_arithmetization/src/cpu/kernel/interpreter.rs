@@ -15,12 +15,13 @@ use crate::cpu::kernel::aggregator::KERNEL;
 use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
 use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
 use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
-use crate::generation::debug_inputs;
+use crate::cpu::kernel::constants::MIN_MAX_MEMORY_BYTES;
 use crate::generation::mpt::load_all_mpts;
 use crate::generation::rlp::all_rlp_prover_inputs_reversed;
 use crate::generation::state::{
     all_withdrawals_prover_inputs_reversed, GenerationState, GenerationStateCheckpoint,
 };
+use crate::generation::{debug_inputs, CycleEstimate};
 use crate::generation::{state::State, GenerationInputs};
 use crate::keccak_sponge::columns::KECCAK_WIDTH_BYTES;
 use crate::keccak_sponge::keccak_sponge_stark::KeccakSpongeOp;
@@ -32,6 +33,7 @@ use crate::witness::memory::{
 };
 use crate::witness::operation::Operation;
 use crate::witness::state::RegistersState;
+use crate::witness::traces::{arithmetic_op_len, byte_packing_op_len, keccak_sponge_op_len};
 use crate::witness::transition::{
     decode, fill_op_flag, get_op_special_length, log_kernel_instruction, Transition,
 };
@@ -57,6 +59,9 @@ pub(crate) struct Interpreter<F: Field> {
     /// Holds the value of the clock: the clock counts the number of operations
     /// in the execution.
     pub(crate) clock: usize,
+    /// The number of rows the operations of the execution would take in each
+    /// table, apart from the CPU and memory ones.
+    pub(crate) table_rows: CycleEstimate,
 }
 
 /// Structure storing the state of the interpreter's registers.
@@ -166,6 +171,7 @@ impl<F: Field> Interpreter<F> {
             jumpdest_table: HashMap::new(),
             is_jumpdest_analysis: false,
             clock: 0,
+            table_rows: CycleEstimate::default(),
         };
         interpreter.generation_state.registers.program_counter = initial_offset;
         let initial_stack_len = initial_stack.len();
@@ -179,6 +185,11 @@ impl<F: Field> Interpreter<F> {
         }
 
         interpreter.initialize_rlp_segment();
+        // Set the default memory offset bound, for tests which don't start at `main`.
+        interpreter.set_global_metadata_field(
+            GlobalMetadata::MaxMemoryBytes,
+            MIN_MAX_MEMORY_BYTES.1.into(),
+        );
         interpreter
     }
 
@@ -195,6 +206,7 @@ impl<F: Field> Interpreter<F> {
             jumpdest_table: HashMap::new(),
             is_jumpdest_analysis: true,
             clock: 0,
+            table_rows: CycleEstimate::default(),
         }
     }
 
@@ -248,6 +260,10 @@ impl<F: Field> Interpreter<F> {
                 h2u(inputs.block_hashes.cur_hash),
             ),
             (GlobalMetadata::BlockGasUsed, metadata.block_gas_used),
+            (
+                GlobalMetadata::BlockHardfork,
+                metadata.block_hardfork.id().into(),
+            ),
             (GlobalMetadata::BlockGasUsedBefore, inputs.gas_used_before),
             (GlobalMetadata::BlockGasUsedAfter, inputs.gas_used_after),
             (GlobalMetadata::TxnNumberBefore, inputs.txn_number_before),
@@ -801,17 +817,34 @@ impl<F: Field> State<F> for Interpreter<F> {
         self.clock += 1;
     }
 
-    fn push_logic(&mut self, _op: logic::Operation) {}
+    fn push_memory(&mut self, op: MemoryOp) {
+        self.table_rows.memory_rows += 1;
+        self.generation_state.traces.memory_ops.push(op);
+    }
 
-    fn push_arithmetic(&mut self, _op: arithmetic::Operation) {}
+    fn push_logic(&mut self, _op: logic::Operation) {
+        self.table_rows.logic_rows += 1;
+    }
 
-    fn push_byte_packing(&mut self, _op: BytePackingOp) {}
+    fn push_arithmetic(&mut self, op: arithmetic::Operation) {
+        self.table_rows.arithmetic_rows += arithmetic_op_len(&op);
+    }
 
-    fn push_keccak(&mut self, _input: [u64; keccak::keccak_stark::NUM_INPUTS], _clock: usize) {}
+    fn push_byte_packing(&mut self, op: BytePackingOp) {
+        self.table_rows.byte_packing_rows += byte_packing_op_len(&op);
+    }
 
-    fn push_keccak_bytes(&mut self, _input: [u8; KECCAK_WIDTH_BYTES], _clock: usize) {}
+    fn push_keccak(&mut self, _input: [u64; keccak::keccak_stark::NUM_INPUTS], _clock: usize) {
+        self.table_rows.keccak_rows += keccak::keccak_stark::NUM_ROUNDS;
+    }
 
-    fn push_keccak_sponge(&mut self, _op: KeccakSpongeOp) {}
+    fn push_keccak_bytes(&mut self, _input: [u8; KECCAK_WIDTH_BYTES], _clock: usize) {
+        self.table_rows.keccak_rows += keccak::keccak_stark::NUM_ROUNDS;
+    }
+
+    fn push_keccak_sponge(&mut self, op: KeccakSpongeOp) {
+        self.table_rows.keccak_sponge_rows += keccak_sponge_op_len(&op);
+    }
 
     fn rollback(&mut self, checkpoint: GenerationStateCheckpoint) {
         self.generation_state.rollback(checkpoint)
@@ -921,7 +954,7 @@ impl<F: Field> Transition<F> for Interpreter<F> {
     }
 }
 
-fn get_mnemonic(opcode: u8) -> &'static str {
+pub(crate) fn get_mnemonic(opcode: u8) -> &'static str {
     match opcode {
         0x00 => "STOP",
         0x01 => "ADD",
@@ -1,6 +1,38 @@
+use ethereum_types::H256;
 use tiny_keccak::keccakf;
 
-use crate::keccak_sponge::columns::{KECCAK_WIDTH_BYTES, KECCAK_WIDTH_U32S};
+use crate::keccak_sponge::columns::{
+    KECCAK_DIGEST_U32S, KECCAK_RATE_BYTES, KECCAK_RATE_U32S, KECCAK_WIDTH_BYTES, KECCAK_WIDTH_U32S,
+};
+
+/// Computes the Keccak-256 hash of `bytes`, as done by the `KeccakSponge`
+/// table for the kernel's `KECCAK_GENERAL`.
+///
+/// This uses the original Keccak padding `0x01 || 0x00* || 0x80`, and not the
+/// `0x06` domain separator of NIST SHA3-256, which yields different digests.
+pub fn keccak256(bytes: &[u8]) -> H256 {
+    let mut padded = bytes.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().next_multiple_of(KECCAK_RATE_BYTES), 0);
+    *padded.last_mut().unwrap() |= 0x80;
+
+    let mut state = [0u32; KECCAK_WIDTH_U32S];
+    for block in padded.chunks_exact(KECCAK_RATE_BYTES) {
+        for (limb, word) in state[..KECCAK_RATE_U32S]
+            .iter_mut()
+            .zip(block.chunks_exact(4))
+        {
+            *limb ^= u32::from_le_bytes(word.try_into().unwrap());
+        }
+        keccakf_u32s(&mut state);
+    }
+
+    let digest: Vec<u8> = state[..KECCAK_DIGEST_U32S]
+        .iter()
+        .flat_map(|limb| limb.to_le_bytes())
+        .collect();
+    H256::from_slice(&digest)
+}
 
 /// Like tiny-keccak's `keccakf`, but deals with `u32` limbs instead of `u64`
 /// limbs.
@@ -31,9 +63,36 @@ pub(crate) fn keccakf_u8s(state_u8s: &mut [u8; KECCAK_WIDTH_BYTES]) {
 
 #[cfg(test)]
 mod tests {
+    use ethereum_types::H256;
+    use hex_literal::hex;
+    use keccak_hash::keccak;
     use tiny_keccak::keccakf;
 
-    use crate::cpu::kernel::keccak_util::{keccakf_u32s, keccakf_u8s};
+    use crate::cpu::kernel::keccak_util::{keccak256, keccakf_u32s, keccakf_u8s};
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            keccak256(&[]),
+            H256(hex!(
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+            ))
+        );
+        // NIST SHA3-256 gives 3a985da7...11431532 instead.
+        assert_eq!(
+            keccak256(b"abc"),
+            H256(hex!(
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+            ))
+        );
+
+        // Inputs around and beyond the rate of 136 bytes, which need padding
+        // in a separate block or several permutations.
+        for len in [135, 136, 137, 300] {
+            let input: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            assert_eq!(keccak256(&input), keccak(&input));
+        }
+    }
 
     #[test]
     #[rustfmt::skip]
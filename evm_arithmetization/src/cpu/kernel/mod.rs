@@ -3,7 +3,7 @@ pub mod assembler;
 mod ast;
 pub(crate) mod constants;
 mod cost_estimator;
-pub(crate) mod keccak_util;
+pub mod keccak_util;
 pub mod opcodes;
 mod optimizer;
 mod parser;
@@ -14,7 +14,7 @@ pub(crate) mod interpreter;
 #[cfg(test)]
 mod tests;
 
-use assembler::assemble;
+use assembler::{assemble, Kernel};
 use parser::parse;
 
 use crate::cpu::kernel::constants::evm_constants;
@@ -22,7 +22,12 @@ use crate::cpu::kernel::constants::evm_constants;
 /// Assemble files, outputting bytes.
 /// This is for debugging the kernel only.
 pub fn assemble_to_bytes(files: &[String]) -> Vec<u8> {
+    assemble_kernel(files).code
+}
+
+/// Assemble files into a kernel, which can be run in place of the default one
+/// with `GenerationOptions::kernel`.
+pub fn assemble_kernel(files: &[String]) -> Kernel {
     let parsed_files: Vec<_> = files.iter().map(|f| parse(f)).collect();
-    let kernel = assemble(parsed_files, evm_constants(), true);
-    kernel.code
+    assemble(parsed_files, evm_constants(), true)
 }
@@ -135,6 +135,7 @@ fn test_add11_yml() {
         block_base_fee: 0xa.into(),
         block_gas_used: gas_used,
         block_bloom: [0.into(); 8],
+        ..Default::default()
     };
 
     let inputs = GenerationInputs {
@@ -276,6 +277,7 @@ fn test_add11_yml_with_exception() {
         block_base_fee: 0xa.into(),
         block_gas_used: txn_gas_limit.into(),
         block_bloom: [0.into(); 8],
+        ..Default::default()
     };
 
     let inputs = GenerationInputs {
@@ -4,6 +4,7 @@ use plonky2::field::goldilocks_field::GoldilocksField as F;
 use crate::cpu::kernel::interpreter::{
     run_interpreter_with_memory, InterpreterMemoryInitialization,
 };
+use crate::cpu::kernel::tests::run_precompile;
 use crate::memory::segments::Segment::KernelGeneral;
 
 type ConvertedBlakeInputs = (u32, [u64; 8], [u64; 16], u64, u64, bool);
@@ -133,3 +134,65 @@ fn test_blake2_f_8() -> Result<()> {
         "fc59093aafa9ab43daae0e914c57635c5402d8e3d2130eb9b3cc181de7f0ecf9b22bf99a7815ce16419e200e01846e6b5df8cc7703041bbceb571de6631d2615",
     )
 }
+
+const PRECOMPILE_GAS_LIMIT: u64 = 1_000;
+
+fn test_blake2_f_precompile_eip_fails(input: &str) -> Result<()> {
+    let calldata = hex::decode(input).unwrap();
+    let (success, gas_used, returndata) = run_precompile(0x09, &calldata, PRECOMPILE_GAS_LIMIT)?;
+    assert!(!success);
+    assert_eq!(gas_used, PRECOMPILE_GAS_LIMIT);
+    assert!(returndata.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_blake2_f_precompile_0() -> Result<()> {
+    test_blake2_f_precompile_eip_fails("")
+}
+
+#[test]
+fn test_blake2_f_precompile_1() -> Result<()> {
+    test_blake2_f_precompile_eip_fails(
+        "00000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+    )
+}
+
+#[test]
+fn test_blake2_f_precompile_2() -> Result<()> {
+    test_blake2_f_precompile_eip_fails(
+        "000000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+    )
+}
+
+#[test]
+fn test_blake2_f_precompile_3() -> Result<()> {
+    test_blake2_f_precompile_eip_fails(
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000002",
+    )
+}
+
+#[test]
+fn test_blake2_f_precompile_5() -> Result<()> {
+    let calldata = hex::decode(
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+    )
+    .unwrap();
+    let (success, gas_used, returndata) = run_precompile(0x09, &calldata, PRECOMPILE_GAS_LIMIT)?;
+    assert!(success);
+    // One unit of gas per round.
+    assert_eq!(gas_used, 12);
+    assert_eq!(
+        hex::encode(returndata),
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    );
+    Ok(())
+}
+
+#[test]
+fn test_blake2_f_precompile_out_of_gas() -> Result<()> {
+    // Same as vector 8, with more rounds than the gas limit allows.
+    test_blake2_f_precompile_eip_fails(
+        "ffffffff48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+    )
+}
@@ -251,3 +251,130 @@ fn test_bn_pairing() -> Result<()> {
     assert_eq!(interpreter.stack()[0], U256::one());
     Ok(())
 }
+
+/// A point of the twisted curve which is not in the subgroup of order
+/// `BN_SCALAR`.
+const NON_SUBGROUP_TWISTED_POINT: Curve<Fp2<BN254>> = Curve {
+    x: Fp2 {
+        re: BN254 { val: U256::one() },
+        im: BN254 { val: U256::zero() },
+    },
+    y: Fp2 {
+        re: BN254 {
+            val: U256([
+                0xab4b871c0531f1bb,
+                0xaadd70e52c9830e9,
+                0xf8e2728fdb825a51,
+                0x2869111d5381f072,
+            ]),
+        },
+        im: BN254 {
+            val: U256([
+                0x1b7f8da82de048a4,
+                0x998c7f790cb4d751,
+                0x36846e70a1934187,
+                0x0d1271953ed9ea08,
+            ]),
+        },
+    },
+};
+
+fn run_bn_twisted_check_subgroup(q: Curve<Fp2<BN254>>) -> U256 {
+    let ptr: usize = 20;
+    let setup = InterpreterMemoryInitialization {
+        label: "bn_twisted_check_subgroup".to_string(),
+        stack: vec![U256::from(ptr), U256::from(0xdeadbeefu32)],
+        segment: BnPairing,
+        memory: vec![(ptr, q.to_stack())],
+    };
+    let interpreter = run_interpreter_with_memory::<F>(setup).unwrap();
+    interpreter.stack()[0]
+}
+
+fn run_bn_pairing(pairs: &[(Curve<BN254>, Curve<Fp2<BN254>>)]) -> U256 {
+    let out: usize = 100;
+    let ptr: usize = 112;
+
+    let input = pairs
+        .iter()
+        .flat_map(|(p, q)| p.to_stack().into_iter().chain(q.to_stack()))
+        .collect();
+    let setup = InterpreterMemoryInitialization {
+        label: "bn254_pairing".to_string(),
+        stack: vec![
+            U256::from(pairs.len()),
+            U256::from(ptr),
+            U256::from(out),
+            U256::from(0xdeadbeefu32),
+        ],
+        segment: BnPairing,
+        memory: vec![(ptr, input)],
+    };
+    let interpreter = run_interpreter_with_memory::<F>(setup).unwrap();
+    interpreter.stack()[0]
+}
+
+#[test]
+fn test_bn_twisted_check_subgroup() -> Result<()> {
+    let mut rng = rand::thread_rng();
+
+    let generator = Curve::<Fp2<BN254>>::GENERATOR;
+    assert_eq!(run_bn_twisted_check_subgroup(generator), U256::one());
+    let q: Curve<Fp2<BN254>> = rng.gen::<Curve<Fp2<BN254>>>();
+    assert_eq!(run_bn_twisted_check_subgroup(q), U256::one());
+
+    assert_eq!(
+        run_bn_twisted_check_subgroup(NON_SUBGROUP_TWISTED_POINT),
+        U256::zero()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_bn_pairing_unbalanced() -> Result<()> {
+    // e(G, H) != 1, as the pairing is non-degenerate.
+    let pairs = [(Curve::<BN254>::GENERATOR, Curve::<Fp2<BN254>>::GENERATOR)];
+    assert_eq!(run_bn_pairing(&pairs), U256::zero());
+
+    // e(2G, 3H) * e(-6G, H) = 1, but e(2G, 3H) * e(-5G, H) != 1.
+    let balanced = [
+        (Curve::<BN254>::int(2), Curve::<Fp2<BN254>>::int(3)),
+        (Curve::<BN254>::int(-6), Curve::<Fp2<BN254>>::GENERATOR),
+    ];
+    assert_eq!(run_bn_pairing(&balanced), U256::one());
+    let unbalanced = [
+        (Curve::<BN254>::int(2), Curve::<Fp2<BN254>>::int(3)),
+        (Curve::<BN254>::int(-5), Curve::<Fp2<BN254>>::GENERATOR),
+    ];
+    assert_eq!(run_bn_pairing(&unbalanced), U256::zero());
+    Ok(())
+}
+
+#[test]
+fn test_bn_pairing_invalid_input() -> Result<()> {
+    let p = Curve::<BN254>::GENERATOR;
+    let q = Curve::<Fp2<BN254>>::GENERATOR;
+
+    // A point which is not on the twisted curve.
+    let mut off_curve = q;
+    off_curve.y.re.val += U256::one();
+    assert_eq!(run_bn_pairing(&[(p, off_curve)]), U256::MAX);
+
+    // A point of the twisted curve outside of the subgroup of order
+    // `BN_SCALAR`, even paired with the identity.
+    assert_eq!(
+        run_bn_pairing(&[(p, NON_SUBGROUP_TWISTED_POINT)]),
+        U256::MAX
+    );
+    assert_eq!(
+        run_bn_pairing(&[(Curve::<BN254>::unit(), NON_SUBGROUP_TWISTED_POINT)]),
+        U256::MAX
+    );
+
+    // Pairs involving the identity on the twisted curve are skipped.
+    assert_eq!(
+        run_bn_pairing(&[(p, Curve::<Fp2<BN254>>::unit())]),
+        U256::one()
+    );
+    Ok(())
+}
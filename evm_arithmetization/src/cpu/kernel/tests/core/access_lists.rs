@@ -10,6 +10,7 @@ use crate::cpu::kernel::constants::global_metadata::GlobalMetadata::{
     AccessedAddressesLen, AccessedStorageKeysLen,
 };
 use crate::cpu::kernel::interpreter::Interpreter;
+use crate::cpu::kernel::tests::core::run_contract_txn;
 use crate::memory::segments::Segment::{self, AccessedAddresses, AccessedStorageKeys};
 use crate::witness::memory::MemoryAddress;
 
@@ -295,3 +296,150 @@ fn test_insert_accessed_storage_keys() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_warm_and_cold_accesses() -> Result<()> {
+    let code = [
+        // Sub-calls have a non-empty calldata.
+        0x36, // CALLDATASIZE
+        0x60, 0x9c, // PUSH1 callee
+        0x57, // JUMPI
+        // Store the gas spent by BALANCE on each precompile in slot 1.
+        0x5a, // GAS
+        0x60, 0x01, 0x31, 0x50, // PUSH1 1 BALANCE POP
+        0x60, 0x02, 0x31, 0x50, // PUSH1 2 BALANCE POP
+        0x60, 0x03, 0x31, 0x50, // PUSH1 3 BALANCE POP
+        0x60, 0x04, 0x31, 0x50, // PUSH1 4 BALANCE POP
+        0x60, 0x05, 0x31, 0x50, // PUSH1 5 BALANCE POP
+        0x60, 0x06, 0x31, 0x50, // PUSH1 6 BALANCE POP
+        0x60, 0x07, 0x31, 0x50, // PUSH1 7 BALANCE POP
+        0x60, 0x08, 0x31, 0x50, // PUSH1 8 BALANCE POP
+        0x60, 0x09, 0x31, 0x50, // PUSH1 9 BALANCE POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Store the gas spent by BALANCE on 0xaa in slots 2 and 3.
+        0x5a, // GAS
+        0x60, 0xaa, // PUSH1 0xaa
+        0x31, // BALANCE
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x5a, // GAS
+        0x60, 0xaa, // PUSH1 0xaa
+        0x31, // BALANCE
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        // Store the gas spent by SLOAD on slot 32 in slots 4 and 5.
+        0x5a, // GAS
+        0x60, 0x20, // PUSH1 32
+        0x54, // SLOAD
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        0x5a, // GAS
+        0x60, 0x20, // PUSH1 32
+        0x54, // SLOAD
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x05, // PUSH1 5
+        0x55, // SSTORE
+        // Let a sub-call access 0xbb, then store the gas spent by BALANCE on it in slot 6.
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x53, // MSTORE8
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x50, // POP
+        0x5a, // GAS
+        0x60, 0xbb, // PUSH1 0xbb
+        0x31, // BALANCE
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x06, // PUSH1 6
+        0x55, // SSTORE
+        // Let a reverted sub-call access 0xcc, then store the gas spent by BALANCE on it in slot
+        // 7.
+        0x60, 0x02, // PUSH1 2
+        0x60, 0x00, // PUSH1 0
+        0x53, // MSTORE8
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x50, // POP
+        0x5a, // GAS
+        0x60, 0xcc, // PUSH1 0xcc
+        0x31, // BALANCE
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x07, // PUSH1 7
+        0x55, // SSTORE
+        0x00, // STOP
+        // Callee: access 0xbb in mode 1, and 0xcc then revert in mode 2.
+        0x5b, // JUMPDEST
+        0x60, 0x00, // PUSH1 0
+        0x35, // CALLDATALOAD
+        0x60, 0xf8, // PUSH1 248
+        0x1c, // SHR
+        0x60, 0x02, // PUSH1 2
+        0x14, // EQ
+        0x60, 0xad, // PUSH1 revert
+        0x57, // JUMPI
+        0x60, 0xbb, // PUSH1 0xbb
+        0x31, // BALANCE
+        0x00, // STOP
+        0x5b, // JUMPDEST
+        0x60, 0xcc, // PUSH1 0xcc
+        0x31, // BALANCE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0xfd, // REVERT
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // Each access is measured with PUSH1, POP and GAS: 7.
+    // Precompiles are warm from the start of the transaction.
+    assert_eq!(storage[&U256::from(1)], (9 * (3 + 100 + 2) + 2).into());
+
+    // The first access to an address or a slot is cold, the next ones are warm.
+    assert_eq!(storage[&U256::from(2)], (7 + 2600).into());
+    assert_eq!(storage[&U256::from(3)], (7 + 100).into());
+    assert_eq!(storage[&U256::from(4)], (7 + 2100).into());
+    assert_eq!(storage[&U256::from(5)], (7 + 100).into());
+
+    // Accesses made by a sub-call stay warm after it returns, unless it
+    // reverted.
+    assert_eq!(storage[&U256::from(6)], (7 + 100).into());
+    assert_eq!(storage[&U256::from(7)], (7 + 2600).into());
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use ethereum_types::Address;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+
+#[test]
+fn test_accessed_code_hashes() -> Result<()> {
+    let callee = Address::from_low_u64_be(0xca11ee);
+    let callee_code = [
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    // An account with code which is never called.
+    let bystander = Address::from_low_u64_be(0xb157a);
+    let bystander_code = [0x00]; // STOP
+    let code = [
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xca, 0x11, 0xee, // PUSH3 callee
+        0x62, 0x01, 0x00, 0x00, // PUSH3 0x010000
+        0xf1, // CALL
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[(callee, &callee_code[..]), (bystander, &bystander_code[..])])
+        .run()?;
+    assert_eq!(outcome.storage[&0.into()], 1.into());
+
+    // The sender has no code, and isn't executed, so that only the called
+    // contracts are loaded.
+    assert_eq!(
+        outcome.accessed_code_hashes,
+        HashSet::from([keccak(code), keccak(callee_code)])
+    );
+    assert!(!outcome
+        .accessed_code_hashes
+        .contains(&keccak(bystander_code)));
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_addmod_mulmod() -> Result<()> {
+    let code = [
+        // Store ADDMOD(2, 1, 0) + 1 in slot 0.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x02, // PUSH1 2
+        0x08, // ADDMOD
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Store MULMOD(2, 3, 0) + 1 in slot 1.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x03, // PUSH1 3
+        0x60, 0x02, // PUSH1 2
+        0x09, // MULMOD
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Store ADDMOD(2^256 - 1, 2^256 - 1, 7) in slot 2.
+        0x60, 0x07, // PUSH1 7
+        0x60, 0x00, // PUSH1 0
+        0x19, // NOT
+        0x80, // DUP1
+        0x08, // ADDMOD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        // Store MULMOD(2^255, 6, 7) in slot 3.
+        0x60, 0x07, // PUSH1 7
+        0x60, 0x06, // PUSH1 6
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xff, // PUSH1 255
+        0x1b, // SHL
+        0x09, // MULMOD
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // A zero modulus gives zero.
+    assert_eq!(storage[&U256::zero()], U256::one());
+    assert_eq!(storage[&U256::one()], U256::one());
+
+    // The sum and product are reduced without being truncated to 256 bits
+    // first, which would give 0 in both cases.
+    assert_eq!(storage[&U256::from(2)], 2.into());
+    assert_eq!(storage[&U256::from(3)], 6.into());
+
+    Ok(())
+}
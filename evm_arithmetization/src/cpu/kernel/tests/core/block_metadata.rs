@@ -0,0 +1,160 @@
+use anyhow::Result;
+use ethereum_types::{BigEndianHash, H256, U256};
+use hex_literal::hex;
+
+use crate::cpu::kernel::tests::core::{contract_txn_block_metadata, ContractTxn};
+use crate::proof::{BlockMetadata, Hardfork};
+
+#[test]
+fn test_chainid() -> Result<()> {
+    let code = [
+        0x46, // CHAINID
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Store the gas spent by CHAINID and GAS in slot 1.
+        0x5a, // GAS
+        0x46, // CHAINID
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let block_metadata = BlockMetadata {
+        block_chain_id: 0x0123_4567.into(),
+        ..contract_txn_block_metadata(Hardfork::default())
+    };
+    let storage = ContractTxn::new(&code)
+        .block_metadata(block_metadata)
+        .run()?
+        .storage;
+
+    assert_eq!(storage[&U256::zero()], 0x0123_4567.into());
+    // CHAINID: 2. POP: 2. GAS: 2.
+    assert_eq!(storage[&U256::one()], 6.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_block_context_opcodes() -> Result<()> {
+    // For each opcode, store its output in slot i and its gas cost in slot i + 4.
+    let opcodes = [
+        0x41, // COINBASE
+        0x42, // TIMESTAMP
+        0x43, // NUMBER
+        0x45, // GASLIMIT
+    ];
+    let mut code = vec![];
+    for (i, &opcode) in opcodes.iter().enumerate() {
+        code.extend([opcode, 0x60, i as u8, 0x55]); // opcode PUSH1 i SSTORE
+        code.extend([0x5a, opcode, 0x50, 0x5a]); // GAS opcode POP GAS
+                                                 // SWAP1 SUB PUSH1 i+4 SSTORE
+        code.extend([0x90, 0x03, 0x60, i as u8 + 4, 0x55]);
+    }
+    let block_metadata = BlockMetadata {
+        block_beneficiary: hex!("00000000000000000000000000000000c014ba5e").into(),
+        block_timestamp: 0x6543_2100.into(),
+        block_number: 0x00ab_cdef.into(),
+        block_gaslimit: 0x0123_4567.into(),
+        ..contract_txn_block_metadata(Hardfork::default())
+    };
+    let storage = ContractTxn::new(&code)
+        .block_metadata(block_metadata)
+        .run()?
+        .storage;
+
+    assert_eq!(storage[&0.into()], 0xc014_ba5e_u64.into());
+    assert_eq!(storage[&1.into()], 0x6543_2100.into());
+    assert_eq!(storage[&2.into()], 0x00ab_cdef.into());
+    assert_eq!(storage[&3.into()], 0x0123_4567.into());
+    // Each opcode: 2. POP: 2. GAS: 2.
+    for i in 4..8u64 {
+        assert_eq!(storage[&i.into()], 6.into());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_basefee() -> Result<()> {
+    let code = [
+        0x48, // BASEFEE
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Store 1 in slot 1 if the base fee is the gas price, and 2 if it is lower.
+        0x3a, // GASPRICE
+        0x48, // BASEFEE
+        0x10, // LT
+        0x60, 0x10, // PUSH1 lower
+        0x57, // JUMPI
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+        0x5b, // JUMPDEST
+        0x60, 0x02, // PUSH1 2
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+
+    // The transaction has a gas price of 10.
+    for (base_fee, branch) in [(10, 1), (0, 2)] {
+        let block_metadata = BlockMetadata {
+            block_base_fee: base_fee.into(),
+            ..contract_txn_block_metadata(Hardfork::default())
+        };
+        let storage = ContractTxn::new(&code)
+            .block_metadata(block_metadata)
+            .run()?
+            .storage;
+
+        // A zero value leaves the slot empty.
+        assert_eq!(
+            storage.get(&U256::zero()).copied().unwrap_or_default(),
+            base_fee.into()
+        );
+        assert_eq!(storage[&U256::one()], branch.into());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_prevrandao() -> Result<()> {
+    let code = [
+        0x44, // PREVRANDAO
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Store the gas spent by PREVRANDAO, POP and GAS in slot 1.
+        0x5a, // GAS
+        0x44, // PREVRANDAO
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let prev_randao = H256(hex!(
+        "b5ad1d4e9f3ac6e0f2a8d1f0b29e3e6c6c1c4e9ad1f4e9b3c4a3f1d2e5c6b7a8"
+    ));
+    let block_metadata = BlockMetadata {
+        block_random: prev_randao,
+        ..contract_txn_block_metadata(Hardfork::default())
+    };
+    let storage = ContractTxn::new(&code)
+        .block_metadata(block_metadata)
+        .run()?
+        .storage;
+
+    assert_eq!(storage[&U256::zero()], prev_randao.into_uint());
+    // PREVRANDAO: 2. POP: 2. GAS: 2.
+    assert_eq!(storage[&U256::one()], 6.into());
+
+    Ok(())
+}
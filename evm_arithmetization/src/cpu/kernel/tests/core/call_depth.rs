@@ -0,0 +1,65 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+/// Call depth at which the transaction starts, so that the contract below
+/// only needs a few recursive calls to reach `CALL_STACK_LIMIT`.
+const INITIAL_CALL_DEPTH: u64 = 1020;
+
+#[test]
+fn test_call_fails_at_call_stack_limit() -> Result<()> {
+    // A contract which recursively calls itself.
+    let code = [
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0 (ret_size)
+        0x60, 0x00, // PUSH1 0 (ret_offset)
+        0x60, 0x00, // PUSH1 0 (args_size)
+        0x60, 0x00, // PUSH1 0 (args_offset)
+        0x60, 0x00, // PUSH1 0 (value)
+        0x30, // ADDRESS
+        0x5a, // GAS
+        0xf1, // CALL
+        0x5a, // GAS
+        // Increment the frame counter, stored in slot 0.
+        0x60, 0x00, 0x54, // PUSH1 0 SLOAD
+        0x60, 0x01, 0x01, // PUSH1 1 ADD
+        0x80, // DUP1
+        0x60, 0x00, 0x55, // PUSH1 0 SSTORE
+        // stack: counter, gas_after, success, gas_before
+        // Store the gas spent around the CALL in slot 100 + counter.
+        0x92, // SWAP3
+        0x03, // SUB
+        0x82, // DUP3
+        0x60, 0x64, 0x01, // PUSH1 100 ADD
+        0x55, // SSTORE
+        // Store success + 1 in slot counter.
+        0x60, 0x01, 0x01, // PUSH1 1 ADD
+        0x90, // SWAP1
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, INITIAL_CALL_DEPTH)?;
+
+    // The transaction runs at depth `INITIAL_CALL_DEPTH + 1`, and the call made
+    // at depth `CALL_STACK_LIMIT + 1` fails. All frames keep executing after
+    // their call returns.
+    let num_frames = 1025 - INITIAL_CALL_DEPTH;
+    assert_eq!(storage[&U256::zero()], num_frames.into());
+
+    // The innermost call returned 0, the others returned 1.
+    assert_eq!(storage[&U256::one()], U256::one());
+    for counter in 2..=num_frames {
+        assert_eq!(storage[&counter.into()], 2.into());
+    }
+
+    // The failed call only costs the warm access to the callee, and the gas
+    // it would have forwarded is given back. The other calls spent more.
+    // PUSH1 * 5, ADDRESS, GAS: 19. CALL: 100. GAS: 2.
+    assert_eq!(storage[&U256::from(101)], 121.into());
+    for counter in 2..=num_frames {
+        assert!(storage[&U256::from(100 + counter)] > 121.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::gas::call_gas;
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_call_gas_forwarding() -> Result<()> {
+    let code = [
+        // Sub-calls have a non-empty calldata. They return their remaining gas.
+        0x36, // CALLDATASIZE
+        0x60, 0x53, // PUSH1 callee
+        0x57, // JUMPI
+        // Call itself requesting more than 63/64 of the remaining gas. Store the gas before the
+        // call in slot 1, the callee gas in slot 2 and success in slot 3.
+        0x5a, // GAS
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x63, 0xff, 0xff, 0xff, 0xff, // PUSH4 0xffffffff
+        0xf1, // CALL
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Call itself with 1 wei and no requested gas. Store the callee gas in slot 4 and success
+        // in slot 5.
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x30, // ADDRESS
+        0x60, 0x00, // PUSH1 0
+        0xf1, // CALL
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        0x60, 0x05, // PUSH1 5
+        0x55, // SSTORE
+        // Send 1 wei to the origin, which has no code, with no requested gas. Store success in
+        // slot 6 and the gas spent in slot 7.
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x32, // ORIGIN
+        0x60, 0x00, // PUSH1 0
+        0xf1, // CALL
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x60, 0x06, // PUSH1 6
+        0x55, // SSTORE
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x07, // PUSH1 7
+        0x55, // SSTORE
+        0x00, // STOP
+        // Callee: return the remaining gas.
+        0x5b, // JUMPDEST
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // The requested gas is clamped to all but one 64th of the gas left after
+    // PUSH1 * 5, ADDRESS, PUSH4 (20), the memory expansion (3) and the warm
+    // access (100).
+    // CALLDATASIZE, PUSH1, JUMPI, JUMPDEST, GAS: 18.
+    let gas_before = storage[&U256::from(1)].as_u64();
+    let expected = call_gas(u64::from(u32::MAX), gas_before - 123, false) - 18;
+    assert_eq!(storage[&U256::from(2)], expected.into());
+    assert_eq!(storage[&U256::from(3)], U256::one());
+
+    // A value-bearing call gets the stipend even though it requested no gas.
+    assert_eq!(storage[&U256::from(4)], (call_gas(0, 0, true) - 18).into());
+    assert_eq!(storage[&U256::from(5)], U256::one());
+
+    // The value transfer costs 9000, and the unused stipend is given back.
+    // PUSH1 * 6, ORIGIN: 20. Warm access: 100. GAS: 2.
+    assert_eq!(storage[&U256::from(6)], U256::one());
+    assert_eq!(storage[&U256::from(7)], (20 + 100 + 9000 - 2300 + 2).into());
+
+    Ok(())
+}
+
+#[test]
+fn test_call_new_account_surcharge() -> Result<()> {
+    let code = [
+        // Send 1 wei to the fresh address 0xdead, and store the gas spent in slot 1.
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x61, 0xde, 0xad, // PUSH2 0xdead
+        0x60, 0x00, // PUSH1 0
+        0xf1, // CALL
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x50, // POP
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Send 1 wei to the origin, which exists, and store the gas spent in slot 2.
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x32, // ORIGIN
+        0x60, 0x00, // PUSH1 0
+        0xf1, // CALL
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x50, // POP
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        // Call the fresh address 0xbeef without value, and store the gas spent in slot 3.
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x61, 0xbe, 0xef, // PUSH2 0xbeef
+        0x60, 0x00, // PUSH1 0
+        0xf1, // CALL
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x50, // POP
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // PUSH1 * 6, PUSH2, GAS: 23. The fresh addresses are cold: 2600.
+    // Creating 0xdead costs 25000 on top of the value transfer.
+    assert_eq!(
+        storage[&U256::from(1)],
+        (23 + 2600 + 9000 + 25000 - 2300).into()
+    );
+    // PUSH1 * 6, ORIGIN, GAS: 22. The origin is warm: 100.
+    assert_eq!(storage[&U256::from(2)], (22 + 100 + 9000 - 2300).into());
+    // Without value, calling a fresh address doesn't create it.
+    assert_eq!(storage[&U256::from(3)], (23 + 2600).into());
+
+    Ok(())
+}
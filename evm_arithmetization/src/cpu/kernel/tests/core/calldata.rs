@@ -0,0 +1,106 @@
+use anyhow::Result;
+use ethereum_types::U256;
+use plonky2::field::goldilocks_field::GoldilocksField as F;
+
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
+use crate::witness::operation::CONTEXT_SCALING_FACTOR;
+
+const GAS_LIMIT: u64 = 100_000;
+
+/// Runs `code` in context 1 with the given calldata, and returns the gas used
+/// along with the resulting main memory.
+fn run_user_code(code: &[u8], calldata: &[u8]) -> Result<(U256, Vec<u8>)> {
+    let mut interpreter: Interpreter<F> = Interpreter::new(0, vec![]);
+    interpreter.set_code(1, code.to_vec());
+    interpreter.generation_state.set_calldata(1, calldata);
+
+    let context_metadata = [
+        (ContextMetadata::GasLimit, GAS_LIMIT.into()),
+        (ContextMetadata::ParentProgramCounter, 0xdeadbeefu32.into()),
+        (
+            ContextMetadata::ParentContext,
+            U256::one() << CONTEXT_SCALING_FACTOR,
+        ),
+    ];
+    for (field, value) in context_metadata {
+        interpreter.generation_state.memory.set(
+            MemoryAddress::new(1, Segment::ContextMetadata, field.unscale()),
+            value,
+        );
+    }
+    interpreter.set_context(1);
+    interpreter.set_is_kernel(false);
+
+    interpreter.run()?;
+
+    // sys_stop returns `success` and the leftover gas.
+    interpreter.pop().expect("Stack should not be empty");
+    let gas_left = interpreter.pop().expect("Stack should not be empty");
+    let gas_used = U256::from(GAS_LIMIT) - gas_left;
+
+    let memory = interpreter.generation_state.memory.contexts[1].segments
+        [Segment::MainMemory.unscale()]
+    .content
+    .iter()
+    .map(|byte| byte.unwrap_or_default().byte(0))
+    .collect();
+
+    Ok((gas_used, memory))
+}
+
+#[test]
+fn test_calldatacopy_zero_fills_past_calldata() -> Result<()> {
+    let calldata: Vec<u8> = (1..=40).collect();
+
+    let code = [
+        // Fill memory[32..64] with 0xff, so that zero-filling is observable.
+        0x60, 0x00, // PUSH1 0
+        0x19, // NOT
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        // Copy calldata[20..84] to memory[0..64], past the 40 bytes of calldata.
+        0x60, 0x40, // PUSH1 64 (size)
+        0x60, 0x14, // PUSH1 20 (offset)
+        0x60, 0x00, // PUSH1 0 (dest_offset)
+        0x37, // CALLDATACOPY
+        0x00, // STOP
+    ];
+
+    let (gas_used, memory) = run_user_code(&code, &calldata)?;
+
+    let mut expected_memory = calldata[20..].to_vec();
+    expected_memory.resize(64, 0);
+    assert_eq!(memory[..64], expected_memory);
+
+    // PUSH1, NOT, PUSH1: 9. MSTORE: 3 + expansion to 2 words: 6.
+    // PUSH1 * 3: 9. CALLDATACOPY: 3 + 2 copied words: 6, no further expansion.
+    assert_eq!(gas_used, 36.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_calldatacopy_out_of_bounds_charges_memory_expansion() -> Result<()> {
+    let calldata: Vec<u8> = (1..=40).collect();
+
+    let code = [
+        0x60, 0x20, // PUSH1 32 (size)
+        0x60, 0x64, // PUSH1 100 (offset)
+        0x60, 0x40, // PUSH1 64 (dest_offset)
+        0x37, // CALLDATACOPY
+        0x00, // STOP
+    ];
+
+    let (gas_used, memory) = run_user_code(&code, &calldata)?;
+
+    assert!(memory.iter().all(|&byte| byte == 0));
+
+    // PUSH1 * 3: 9. CALLDATACOPY: 3 + 1 copied word: 3, and expansion to 3
+    // words: 9.
+    assert_eq!(gas_used, 24.into());
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use ethereum_types::U256;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_codecopy() -> Result<()> {
+    let code = [
+        // Store CODESIZE in slot 0.
+        0x38, // CODESIZE
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Copy the first word of this code to memory offset 0. Store it in slot 1 and the gas
+        // spent in slot 2.
+        0x5a, // GAS
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x39, // CODECOPY
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Copy a word from past the end of this code over a non-zero word at offset 32, and store
+        // it in slot 3.
+        0x60, 0x00, // PUSH1 0
+        0x19, // NOT
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x61, 0x10, 0x00, // PUSH2 0x1000
+        0x60, 0x20, // PUSH1 32
+        0x39, // CODECOPY
+        0x60, 0x20, // PUSH1 32
+        0x51, // MLOAD
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        // Copy a word starting 2 bytes before the end of this code over a non-zero word at
+        // offset 64, and store it in slot 4.
+        0x60, 0x00, // PUSH1 0
+        0x19, // NOT
+        0x60, 0x40, // PUSH1 64
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x02, // PUSH1 2
+        0x38, // CODESIZE
+        0x03, // SUB
+        0x60, 0x40, // PUSH1 64
+        0x39, // CODECOPY
+        0x60, 0x40, // PUSH1 64
+        0x51, // MLOAD
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    assert_eq!(storage[&U256::zero()], code.len().into());
+    assert_eq!(storage[&U256::from(1)], U256::from_big_endian(&code[..32]));
+    // PUSH1 * 3, GAS: 11. CODECOPY: 3. Copying a word: 3. Memory expansion to a
+    // word: 3.
+    assert_eq!(storage[&U256::from(2)], (11 + 3 + 3 + 3).into());
+
+    // Bytes past the end of the code are copied as zeros.
+    assert!(!storage.contains_key(&U256::from(3)));
+    let mut last_bytes = [0; 32];
+    last_bytes[..2].copy_from_slice(&code[code.len() - 2..]);
+    assert_eq!(storage[&U256::from(4)], U256::from_big_endian(&last_bytes));
+
+    Ok(())
+}
+
+#[test]
+fn test_codecopy_in_init_code() -> Result<()> {
+    // Init code deploying a copy of itself.
+    let init_code = [
+        0x38, // CODESIZE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x39, // CODECOPY
+        0x38, // CODESIZE
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ];
+    let mut code = vec![0x69]; // PUSH10 init_code
+    code.extend(init_code);
+    code.extend([
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        // Deploy the init code, and store the code size of the new account in slot 0 and its
+        // code hash in slot 1.
+        0x60, 0x0a, // PUSH1 10
+        0x60, 0x16, // PUSH1 22
+        0x60, 0x00, // PUSH1 0
+        0xf0, // CREATE
+        0x80, // DUP1
+        0x3b, // EXTCODESIZE
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x3f, // EXTCODEHASH
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+    ]);
+    let storage = run_contract_txn(&code, 0)?;
+
+    // During the creation, CODESIZE and CODECOPY refer to the init code rather
+    // than to the code of the creator.
+    assert_eq!(storage[&U256::zero()], init_code.len().into());
+    assert_eq!(
+        storage[&U256::from(1)],
+        U256::from_big_endian(keccak(init_code).as_bytes())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+
+const SENDER: [u8; 20] = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+const CONTRACT: [u8; 20] = hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87");
+const CALLEE: u64 = 0xca11ee;
+const DELEGATE: u64 = 0xde1e9a7e;
+
+/// Writes `ORIGIN`, `CALLER`, `ADDRESS` and `CALLVALUE` to the 4 words of
+/// memory starting at 0.
+const WRITE_CONTEXT: [u8; 16] = [
+    0x32, 0x60, 0x00, 0x52, // ORIGIN PUSH1 0 MSTORE
+    0x33, 0x60, 0x20, 0x52, // CALLER PUSH1 32 MSTORE
+    0x30, 0x60, 0x40, 0x52, // ADDRESS PUSH1 64 MSTORE
+    0x34, 0x60, 0x60, 0x52, // CALLVALUE PUSH1 96 MSTORE
+];
+
+#[test]
+fn test_context_opcodes_in_nested_frames() -> Result<()> {
+    // Returns its context.
+    let mut delegate_code = WRITE_CONTEXT.to_vec();
+    delegate_code.extend([
+        0x60, 0x80, // PUSH1 128
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ]);
+
+    // Returns its context, followed by the context of the delegate code when
+    // delegate-called.
+    let mut callee_code = WRITE_CONTEXT.to_vec();
+    callee_code.extend([
+        0x60, 0x80, // PUSH1 128
+        0x60, 0x80, // PUSH1 128
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x63, 0xde, 0x1e, 0x9a, 0x7e, // PUSH4 DELEGATE
+        0x5a, // GAS
+        0xf4, // DELEGATECALL
+        0x50, // POP
+        0x61, 0x01, 0x00, // PUSH2 256
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ]);
+
+    // Stores its context in slots 0 to 3, calls the callee with a value of 7,
+    // and stores the returned contexts in slots 4 to 11.
+    let mut code = vec![
+        0x32, 0x60, 0x00, 0x55, // ORIGIN PUSH1 0 SSTORE
+        0x33, 0x60, 0x01, 0x55, // CALLER PUSH1 1 SSTORE
+        0x30, 0x60, 0x02, 0x55, // ADDRESS PUSH1 2 SSTORE
+        0x34, 0x60, 0x03, 0x55, // CALLVALUE PUSH1 3 SSTORE
+        0x61, 0x01, 0x00, // PUSH2 256
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x07, // PUSH1 7
+        0x62, 0xca, 0x11, 0xee, // PUSH3 CALLEE
+        0x5a, // GAS
+        0xf1, // CALL
+        0x60, 0x0c, // PUSH1 12
+        0x55, // SSTORE
+    ];
+    for i in 0..8u8 {
+        // PUSH1 32i MLOAD PUSH1 4+i SSTORE
+        code.extend([0x60, 32 * i, 0x51, 0x60, 4 + i, 0x55]);
+    }
+    code.push(0x00); // STOP
+
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[
+            (Address::from_low_u64_be(CALLEE), &callee_code),
+            (Address::from_low_u64_be(DELEGATE), &delegate_code),
+        ])
+        .run()?;
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+    assert_eq!(slot(12), U256::one());
+
+    let sender = U256::from_big_endian(&SENDER);
+    let contract = U256::from_big_endian(&CONTRACT);
+    let callee = U256::from(CALLEE);
+    let contexts = [
+        // The contract is called by the sender, with the transaction value.
+        [sender, sender, contract, 100000.into()],
+        // The callee is called by the contract, with a value of 7.
+        [sender, contract, callee, 7.into()],
+        // The delegate code runs in the context of the callee.
+        [sender, contract, callee, 7.into()],
+    ];
+    for (depth, context) in contexts.iter().enumerate() {
+        for (i, &value) in context.iter().enumerate() {
+            assert_eq!(
+                slot((4 * depth + i) as u64),
+                value,
+                "context value {i} at depth {depth}"
+            );
+        }
+    }
+
+    Ok(())
+}
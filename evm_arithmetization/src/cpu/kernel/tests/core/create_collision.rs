@@ -0,0 +1,98 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::{ContractTxn, ContractTxnOutcome};
+use crate::generation::mpt::AccountRlp;
+
+/// Creates a contract with empty init code with `CREATE2`, with salt 0, and
+/// stores the returned address in slot 0.
+const CREATE2_CODE: [u8; 12] = [
+    0x60, 0x00, // PUSH1 0
+    0x60, 0x00, // PUSH1 0
+    0x60, 0x00, // PUSH1 0
+    0x60, 0x00, // PUSH1 0
+    0xf5, // CREATE2
+    0x60, 0x00, // PUSH1 0
+    0x55, // SSTORE
+];
+
+/// The address created by `CREATE2_CODE`.
+const CREATED: [u8; 20] = hex!("7707eef26246411c350df2b069d5f877dd9812f3");
+
+fn run_create2(existing_account: Option<AccountRlp>) -> Result<ContractTxnOutcome> {
+    let extra_accounts: Vec<_> = existing_account
+        .into_iter()
+        .map(|account| (Address::from(CREATED), account))
+        .collect();
+    ContractTxn::new(&CREATE2_CODE)
+        .accounts(&extra_accounts)
+        .run()
+}
+
+#[test]
+fn test_create_at_fresh_address() -> Result<()> {
+    let outcome = run_create2(None)?;
+
+    assert_eq!(
+        outcome.storage[&U256::zero()],
+        U256::from_big_endian(&CREATED)
+    );
+    let created = &outcome.accounts[&keccak(CREATED)];
+    assert_eq!(created.nonce, 1);
+    assert_eq!(created.balance, U256::zero());
+    assert_eq!(created.code_hash, keccak([]));
+
+    Ok(())
+}
+
+#[test]
+fn test_create_at_address_with_code() -> Result<()> {
+    let existing = AccountRlp {
+        code_hash: keccak([0x00]),
+        ..AccountRlp::default()
+    };
+    let outcome = run_create2(Some(existing.clone()))?;
+
+    // The creation fails, and the existing account is left untouched.
+    assert!(!outcome.storage.contains_key(&U256::zero()));
+    let account = &outcome.accounts[&keccak(CREATED)];
+    assert_eq!(account.nonce, 0);
+    assert_eq!(account.code_hash, existing.code_hash);
+
+    Ok(())
+}
+
+#[test]
+fn test_create_at_address_with_nonce() -> Result<()> {
+    let existing = AccountRlp {
+        nonce: 1.into(),
+        ..AccountRlp::default()
+    };
+    let outcome = run_create2(Some(existing))?;
+
+    assert!(!outcome.storage.contains_key(&U256::zero()));
+
+    Ok(())
+}
+
+#[test]
+fn test_create_at_prefunded_address() -> Result<()> {
+    let existing = AccountRlp {
+        balance: 5.into(),
+        ..AccountRlp::default()
+    };
+    let outcome = run_create2(Some(existing))?;
+
+    // An account with a balance only doesn't collide, and keeps its balance.
+    assert_eq!(
+        outcome.storage[&U256::zero()],
+        U256::from_big_endian(&CREATED)
+    );
+    let created = &outcome.accounts[&keccak(CREATED)];
+    assert_eq!(created.nonce, 1);
+    assert_eq!(created.balance, 5.into());
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::{HashedPartialTrie, Node, PartialTrie};
+
+use crate::cpu::kernel::tests::core::{ContractTxn, ContractTxnOutcome};
+use crate::generation::mpt::AccountRlp;
+
+const SENDER: [u8; 20] = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+const CONTRACT: [u8; 20] = hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87");
+const CALLEE: u64 = 0xca11ee;
+
+/// Stores `CALLER` in slot 0, `CALLVALUE` in slot 1 and 42 in slot 2.
+const CALLEE_CODE: [u8; 14] = [
+    0x33, // CALLER
+    0x60, 0x00, // PUSH1 0
+    0x55, // SSTORE
+    0x34, // CALLVALUE
+    0x60, 0x01, // PUSH1 1
+    0x55, // SSTORE
+    0x60, 0x2a, // PUSH1 42
+    0x60, 0x02, // PUSH1 2
+    0x55, // SSTORE
+    0x00, // STOP
+];
+
+/// Runs a transaction calling a contract which calls the callee with the given
+/// call opcode and value, and stores the call result in slot 3.
+fn run_with_callee(call_opcode: u8, value: Option<u8>) -> Result<ContractTxnOutcome> {
+    let mut code = vec![
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+    ];
+    if let Some(value) = value {
+        code.extend([0x60, value]); // PUSH1 value
+    }
+    code.extend([
+        0x62,
+        0xca,
+        0x11,
+        0xee, // PUSH3 CALLEE
+        0x62,
+        0x02,
+        0x00,
+        0x00, // PUSH3 0x020000
+        call_opcode,
+        0x60,
+        0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x00, // STOP
+    ]);
+    ContractTxn::new(&code)
+        .contracts(&[(Address::from_low_u64_be(CALLEE), &CALLEE_CODE)])
+        .run()
+}
+
+/// Returns whether the callee still has an empty storage.
+fn callee_storage_is_empty(outcome: &ContractTxnOutcome) -> Result<bool> {
+    let callee = Address::from_low_u64_be(CALLEE);
+    let account: AccountRlp = rlp::decode(
+        outcome
+            .state_trie
+            .get(Nibbles::from_h256_be(keccak(callee)))
+            .unwrap(),
+    )?;
+    Ok(account.storage_root == HashedPartialTrie::from(Node::Empty).hash())
+}
+
+#[test]
+fn test_delegatecall_context() -> Result<()> {
+    let outcome = run_with_callee(0xf4, None)?; // DELEGATECALL
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    // The callee code writes to the storage of the delegating contract, and
+    // sees the sender and value of the transaction.
+    assert_eq!(slot(3), U256::one());
+    assert_eq!(slot(0), U256::from_big_endian(&SENDER));
+    assert_eq!(slot(1), 100000.into());
+    assert_eq!(slot(2), 42.into());
+    assert!(callee_storage_is_empty(&outcome)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_callcode_context() -> Result<()> {
+    let outcome = run_with_callee(0xf2, Some(7))?; // CALLCODE
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    // The callee code writes to the storage of the calling contract, which is
+    // its caller, with the value of the CALLCODE.
+    assert_eq!(slot(3), U256::one());
+    assert_eq!(slot(0), U256::from_big_endian(&CONTRACT));
+    assert_eq!(slot(1), 7.into());
+    assert_eq!(slot(2), 42.into());
+    assert!(callee_storage_is_empty(&outcome)?);
+
+    // The value is sent by the calling contract to itself.
+    let callee = Address::from_low_u64_be(CALLEE);
+    assert_eq!(outcome.accounts[&keccak(callee)].balance, U256::zero());
+
+    Ok(())
+}
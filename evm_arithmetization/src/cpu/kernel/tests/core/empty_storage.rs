@@ -0,0 +1,71 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::PartialTrie;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::generation::mpt::{empty_trie_hash, AccountRlp};
+
+const CALLEE: u64 = 0xca11ee;
+
+/// Returns the value of slot 0 as 32 bytes of return data.
+const CALLEE_CODE: [u8; 11] = [
+    0x60, 0x00, // PUSH1 0
+    0x54, // SLOAD
+    0x60, 0x00, // PUSH1 0
+    0x52, // MSTORE
+    0x60, 0x20, // PUSH1 32
+    0x60, 0x00, // PUSH1 0
+    0xf3, // RETURN
+];
+
+#[test]
+fn test_sload_from_empty_storage() -> Result<()> {
+    // The contract has an explicit empty storage trie in `TrieInputs`, while
+    // the storage trie of the callee is omitted.
+    let code = [
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xca, 0x11, 0xee, // PUSH3 CALLEE
+        0x5a, // GAS
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x15, // ISZERO
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x54, // SLOAD
+        0x15, // ISZERO
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let callee = Address::from_low_u64_be(CALLEE);
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[(callee, &CALLEE_CODE)])
+        .run()?;
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    // The call succeeds, and both storages read zero.
+    assert_eq!(slot(1), U256::one());
+    assert_eq!(slot(2), U256::one());
+    assert_eq!(slot(3), U256::one());
+
+    // The storage root of the callee is still the empty trie hash.
+    let account: AccountRlp = rlp::decode(
+        outcome
+            .state_trie
+            .get(Nibbles::from_h256_be(keccak(callee)))
+            .unwrap(),
+    )?;
+    assert_eq!(account.storage_root, empty_trie_hash());
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+
+#[test]
+fn test_execution_trace() -> Result<()> {
+    let code = [
+        0x60, 0x02, // PUSH1 2
+        0x60, 0x03, // PUSH1 3
+        0x01, // ADD
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let outcome = ContractTxn::new(&code).record_execution_trace().run()?;
+    let trace = outcome.execution_trace.unwrap();
+
+    let steps: Vec<_> = trace.iter().map(|step| (step.pc, step.opcode)).collect();
+    assert_eq!(
+        steps,
+        [
+            (0, 0x60),
+            (2, 0x60),
+            (4, 0x01),
+            (5, 0x60),
+            (7, 0x55),
+            (8, 0x00)
+        ]
+    );
+
+    // The gas limit of 400000, minus the intrinsic gas of 21000.
+    assert_eq!(trace[0].gas_remaining, 379000);
+    let gas_deltas: Vec<_> = trace
+        .windows(2)
+        .map(|steps| steps[0].gas_remaining - steps[1].gas_remaining)
+        .collect();
+    // PUSH1, ADD: 3. Cold SSTORE of a fresh slot: 22100.
+    assert_eq!(gas_deltas, [3, 3, 3, 3, 22100]);
+
+    assert!(trace[0].stack_top.is_empty());
+    assert_eq!(trace[2].stack_top, [U256::from(3), U256::from(2)]);
+    assert_eq!(trace[4].stack_top, [U256::zero(), U256::from(5)]);
+    // The top-level call is at depth 1.
+    assert!(trace.iter().all(|step| step.depth == 1));
+
+    Ok(())
+}
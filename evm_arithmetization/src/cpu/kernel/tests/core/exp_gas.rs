@@ -0,0 +1,43 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_exp_gas_and_wraparound() -> Result<()> {
+    // For each (exponent, base), compute the power and store it in slot 2i, and the
+    // gas spent in slot 2i + 1.
+    let cases: [&[u8]; 4] = [
+        &[0x60, 0x00, 0x60, 0x02],       // PUSH1 0 PUSH1 2
+        &[0x60, 0xff, 0x60, 0x02],       // PUSH1 255 PUSH1 2
+        &[0x61, 0x01, 0x00, 0x60, 0x02], // PUSH2 256 PUSH1 2
+        &[0x60, 0x00, 0x19, 0x60, 0x03], // PUSH1 0 NOT PUSH1 3
+    ];
+    let mut code = vec![];
+    for (i, operands) in cases.iter().enumerate() {
+        code.push(0x5a); // GAS
+        code.extend_from_slice(operands);
+        code.extend([0x0a, 0x5a, 0x90]); // EXP GAS SWAP1
+        code.extend([0x60, 2 * i as u8, 0x55]); // PUSH1 2i SSTORE
+        code.extend([0x90, 0x03]); // SWAP1 SUB
+        code.extend([0x60, 2 * i as u8 + 1, 0x55]); // PUSH1 2i+1 SSTORE
+    }
+    let storage = run_contract_txn(&code, 0)?;
+    let slot = |i: u64| storage.get(&i.into()).copied().unwrap_or_default();
+
+    // EXP costs 10, plus 50 per byte of the exponent. PUSH1 * 2, GAS: 8.
+    assert_eq!(slot(0), U256::one());
+    assert_eq!(slot(1), (8 + 10).into());
+    assert_eq!(slot(2), U256::one() << 255);
+    assert_eq!(slot(3), (8 + 10 + 50).into());
+
+    // 2^256 wraps around to 0. PUSH2, PUSH1, GAS: 8.
+    assert_eq!(slot(4), U256::zero());
+    assert_eq!(slot(5), (8 + 10 + 50 * 2).into());
+
+    // 3^(2^256 - 1) is the inverse of 3 modulo 2^256. PUSH1 * 2, NOT, GAS: 11.
+    assert_eq!(slot(6).overflowing_mul(3.into()).0, U256::one());
+    assert_eq!(slot(7), (11 + 10 + 50 * 32).into());
+
+    Ok(())
+}
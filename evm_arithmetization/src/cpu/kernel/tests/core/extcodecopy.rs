@@ -0,0 +1,80 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_extcodecopy() -> Result<()> {
+    let code = [
+        // Copy the first word of this code to memory offset 0. Store it in slot 1 and the gas
+        // spent in slot 2.
+        0x5a, // GAS
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x3c, // EXTCODECOPY
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Copy a word from past the end of this code over a non-zero word at offset 32, and store
+        // it in slot 3.
+        0x60, 0x00, // PUSH1 0
+        0x19, // NOT
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x61, 0x10, 0x00, // PUSH2 0x1000
+        0x60, 0x20, // PUSH1 32
+        0x30, // ADDRESS
+        0x3c, // EXTCODECOPY
+        0x60, 0x20, // PUSH1 32
+        0x51, // MLOAD
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        // Copy a word from the non-existent account 0xdead over a non-zero word at offset 64.
+        // Store it in slot 4 and the gas spent in slot 5.
+        0x60, 0x00, // PUSH1 0
+        0x19, // NOT
+        0x60, 0x40, // PUSH1 64
+        0x52, // MSTORE
+        0x5a, // GAS
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x40, // PUSH1 64
+        0x61, 0xde, 0xad, // PUSH2 0xdead
+        0x3c, // EXTCODECOPY
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x05, // PUSH1 5
+        0x55, // SSTORE
+        0x60, 0x40, // PUSH1 64
+        0x51, // MLOAD
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    assert_eq!(storage[&U256::from(1)], U256::from_big_endian(&code[..32]));
+    // PUSH1 * 3, ADDRESS, GAS: 13. Warm access: 100. Copying a word: 3.
+    // Memory expansion to a word: 3.
+    assert_eq!(storage[&U256::from(2)], (13 + 100 + 3 + 3).into());
+
+    // Bytes past the end of the code are copied as zeros.
+    assert!(!storage.contains_key(&U256::from(3)));
+
+    // A non-existent account has no code, so only zeros are copied.
+    assert!(!storage.contains_key(&U256::from(4)));
+    // PUSH1 * 3, PUSH2, GAS: 14. Cold access: 2600. Copying a word: 3.
+    assert_eq!(storage[&U256::from(5)], (14 + 2600 + 3).into());
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::generation::mpt::AccountRlp;
+
+#[test]
+fn test_final_accounts_after_transfer() -> Result<()> {
+    // The transaction only transfers its value of 100000 to the contract.
+    let code = [0x00]; // STOP
+    let outcome = ContractTxn::new(&code).run()?;
+
+    let sender = keccak(Address::from(hex!(
+        "a94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+    )));
+    let to = keccak(Address::from(hex!(
+        "095e7baea6a6c7c4c2dfeb977efac326af552d87"
+    )));
+    let beneficiary = keccak(Address::from(hex!(
+        "2adc25665018aa1fe0e6bc666dac8fc2697ff9ba"
+    )));
+
+    let initial_balance = U256::from(0x0de0b6b3a7640000u64);
+    let value = U256::from(100000);
+    let mut expected = vec![
+        (
+            sender,
+            AccountRlp {
+                nonce: 1.into(),
+                balance: initial_balance - 21000 * 10 - value,
+                ..AccountRlp::default()
+            },
+        ),
+        (
+            to,
+            AccountRlp {
+                balance: initial_balance + value,
+                code_hash: keccak(code),
+                ..AccountRlp::default()
+            },
+        ),
+        // The gas price equals the base fee, so the beneficiary is unchanged.
+        (
+            beneficiary,
+            AccountRlp {
+                nonce: 1.into(),
+                ..AccountRlp::default()
+            },
+        ),
+    ];
+    expected.sort_by_key(|&(state_key, _)| state_key);
+    assert_eq!(outcome.final_accounts, expected);
+
+    Ok(())
+}
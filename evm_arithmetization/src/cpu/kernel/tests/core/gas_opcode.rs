@@ -0,0 +1,31 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_gas_opcode() -> Result<()> {
+    let code = [
+        0x5a, // GAS
+        0x60, 0x05, // PUSH1 5
+        0x01, // ADD
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Store the difference between two successive GAS in slot 1.
+        0x5a, // GAS
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // GAS pushes the gas left after its own cost: the gas limit of 400000,
+    // minus the intrinsic gas of 21000, minus 2.
+    assert_eq!(storage[&U256::zero()], (400000 - 21000 - 2 + 5).into());
+    assert_eq!(storage[&U256::one()], 2.into());
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use ethereum_types::U256;
+use hex_literal::hex;
+
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
+
+/// Stores the gas price in slot 0.
+const CODE: [u8; 4] = [
+    0x3a, // GASPRICE
+    0x60, 0x00, // PUSH1 0
+    0x55, // SSTORE
+];
+
+#[test]
+fn test_gasprice_legacy_txn() -> Result<()> {
+    let storage = run_contract_txn(&CODE, 0)?;
+    assert_eq!(storage[&U256::zero()], 0xa.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_gasprice_type_2_txn() -> Result<()> {
+    // A type-2 call to the contract with a gas limit of 400000, a max fee per
+    // gas of 20 and a max priority fee per gas of 2.
+    let txn = hex!("02f8630180021483061a8094095e7baea6a6c7c4c2dfeb977efac326af552d878080c080a0bb50e2d89a4ed70663d080659fe0ad4b9bc3e06c17a227433966cb59ceee020da032a88278709e0011c92d406322e5863dd48c379aa49d33979ee8193e29dbce2e");
+    let outcome = ContractTxn::new(&CODE).txn(&txn).run()?;
+
+    // The effective gas price is min(20, 10 + 2), with a base fee of 10.
+    assert_eq!(outcome.storage[&U256::zero()], 12.into());
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::{ContractTxn, ContractTxnOutcome};
+
+const SENDER: [u8; 20] = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+const CONTRACT: [u8; 20] = hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87");
+const BENEFICIARY: [u8; 20] = hex!("2adc25665018aa1fe0e6bc666dac8fc2697ff9ba");
+
+/// The initial balance of the sender and of the contract.
+const INITIAL_BALANCE: u64 = 0x0de0b6b3a7640000;
+
+/// Sets slot 0, then resets it, which earns a refund above the EIP-3529 cap.
+const CODE: [u8; 11] = [
+    0x60, 0x01, // PUSH1 1
+    0x60, 0x00, // PUSH1 0
+    0x55, // SSTORE
+    0x60, 0x00, // PUSH1 0
+    0x60, 0x00, // PUSH1 0
+    0x55, // SSTORE
+    0x00, // STOP
+];
+
+/// The gas used by a transaction running `CODE`, refund included.
+fn gas_used() -> U256 {
+    // Intrinsic gas: 21000. PUSH1 * 4: 12. Cold SSTORE of a zero slot: 22100.
+    // Warm SSTORE of a dirty slot: 100.
+    let gas_used = 21000 + 12 + 22100 + 100;
+    // The refund of 20000 - 100 is capped at `gas_used / 5`.
+    (gas_used - gas_used / 5).into()
+}
+
+fn balance(outcome: &ContractTxnOutcome, address: [u8; 20]) -> U256 {
+    outcome.accounts[&keccak(Address::from(address))].balance
+}
+
+#[test]
+fn test_legacy_txn_settlement() -> Result<()> {
+    let outcome = ContractTxn::new(&CODE).run()?;
+    let gas_used = gas_used();
+    assert_eq!(outcome.receipt.cum_gas_used, gas_used);
+    assert_eq!(outcome.leftover_gas, U256::from(400000) - gas_used);
+
+    // The sender pays the gas used at the gas price of 10, and the value of
+    // 100000, which the contract receives.
+    let value = U256::from(100000);
+    assert_eq!(
+        balance(&outcome, SENDER),
+        U256::from(INITIAL_BALANCE) - gas_used * 10 - value
+    );
+    assert_eq!(
+        balance(&outcome, CONTRACT),
+        U256::from(INITIAL_BALANCE) + value
+    );
+    // The gas price equals the base fee, so there is no tip.
+    assert_eq!(balance(&outcome, BENEFICIARY), U256::zero());
+
+    Ok(())
+}
+
+#[test]
+fn test_type_2_txn_settlement() -> Result<()> {
+    // A type-2 call to the contract with a gas limit of 400000, a max fee per
+    // gas of 20 and a max priority fee per gas of 2, without value.
+    let txn = hex!("02f8630180021483061a8094095e7baea6a6c7c4c2dfeb977efac326af552d878080c080a0bb50e2d89a4ed70663d080659fe0ad4b9bc3e06c17a227433966cb59ceee020da032a88278709e0011c92d406322e5863dd48c379aa49d33979ee8193e29dbce2e");
+    let outcome = ContractTxn::new(&CODE).txn(&txn).run()?;
+    let gas_used = gas_used();
+    assert_eq!(outcome.receipt.cum_gas_used, gas_used);
+
+    // The sender only pays for the gas used, at the effective gas price of
+    // min(20, 10 + 2). The beneficiary gets the tip of 2 per gas, and the base
+    // fee of 10 per gas is burnt.
+    assert_eq!(
+        balance(&outcome, SENDER),
+        U256::from(INITIAL_BALANCE) - gas_used * 12
+    );
+    assert_eq!(balance(&outcome, CONTRACT), U256::from(INITIAL_BALANCE));
+    assert_eq!(balance(&outcome, BENEFICIARY), gas_used * 2);
+
+    Ok(())
+}
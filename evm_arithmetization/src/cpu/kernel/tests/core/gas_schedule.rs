@@ -0,0 +1,90 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::proof::Hardfork;
+
+#[test]
+fn test_sload_gas_depends_on_hardfork() -> Result<()> {
+    // A contract loading slot 0 twice, and storing the gas spent by each load
+    // in slots 1 and 2.
+    let code = [
+        0x5a, // GAS
+        0x60, 0x00, 0x54, // PUSH1 0 SLOAD
+        0x50, // POP
+        0x5a, // GAS
+        0x90, 0x03, // SWAP1 SUB
+        0x60, 0x01, 0x55, // PUSH1 1 SSTORE
+        0x5a, // GAS
+        0x60, 0x00, 0x54, // PUSH1 0 SLOAD
+        0x50, // POP
+        0x5a, // GAS
+        0x90, 0x03, // SWAP1 SUB
+        0x60, 0x02, 0x55, // PUSH1 2 SSTORE
+        0x00, // STOP
+    ];
+
+    // PUSH1, POP, GAS: 7.
+    // Shanghai charges 2100 for a cold load and 100 for a warm one.
+    let storage = ContractTxn::new(&code)
+        .hardfork(Hardfork::Shanghai)
+        .run()?
+        .storage;
+    assert_eq!(storage[&U256::one()], 2107.into());
+    assert_eq!(storage[&U256::from(2)], 107.into());
+
+    // Istanbul charges 800 for every load.
+    let storage = ContractTxn::new(&code)
+        .hardfork(Hardfork::Istanbul)
+        .run()?
+        .storage;
+    assert_eq!(storage[&U256::one()], 807.into());
+    assert_eq!(storage[&U256::from(2)], 807.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_selfdestruct_refund_depends_on_hardfork() -> Result<()> {
+    // A contract storing to four fresh slots, then calling a contract which
+    // selfdestructs twice.
+    let mut code = vec![];
+    for slot in 1..=4 {
+        code.extend_from_slice(&[0x60, 0x01, 0x60, slot, 0x55]); // PUSH1 1 PUSH1 slot SSTORE
+    }
+    for _ in 0..2 {
+        code.extend_from_slice(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, // PUSH1 0 (x5)
+            0x62, 0xca, 0x11, 0xee, // PUSH3 0xca11ee
+            0x5a, // GAS
+            0xf1, // CALL
+            0x50, // POP
+        ]);
+    }
+    code.push(0x00); // STOP
+    let callee_code = [
+        0x30, // ADDRESS
+        0xff, // SELFDESTRUCT
+    ];
+    let callee = Address::from_low_u64_be(0xca11ee);
+
+    // Both hardforks charge 21000 for the transaction, 4 * (6 + 20000) for the
+    // stores, and 2 * (20 + 5002 + 2) for the calls besides their access cost.
+    // Shanghai also charges 2100 for every cold store, and 2600 for the first,
+    // cold, call and 100 for the second.
+    let outcome = ContractTxn::new(&code)
+        .hardfork(Hardfork::Shanghai)
+        .contracts(&[(callee, &callee_code)])
+        .run()?;
+    assert_eq!(outcome.receipt.cum_gas_used, 122_172.into());
+
+    // Istanbul charges 700 for every call, and refunds 24000 for the first
+    // selfdestruct of the callee only.
+    let outcome = ContractTxn::new(&code)
+        .hardfork(Hardfork::Istanbul)
+        .contracts(&[(callee, &callee_code)])
+        .run()?;
+    assert_eq!(outcome.receipt.cum_gas_used, (112_472 - 24_000).into());
+
+    Ok(())
+}
@@ -1,14 +1,24 @@
 use anyhow::Result;
 use ethereum_types::U256;
+use hex_literal::hex;
+use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::{HashedPartialTrie, Node, PartialTrie};
 use plonky2::field::goldilocks_field::GoldilocksField as F;
 
 use crate::cpu::kernel::aggregator::KERNEL;
 use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
 use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
 use crate::cpu::kernel::interpreter::Interpreter;
+use crate::generation::mpt::AccountRlp;
+use crate::generation::TrieInputs;
+use crate::memory::segments::Segment;
+use crate::GenerationInputs;
 
 const GAS_TX: u32 = 21_000;
 const GAS_TXCREATE: u32 = 32_000;
+const GAS_TXDATAZERO: u32 = 4;
+const GAS_TXDATANONZERO: u32 = 16;
 
 #[test]
 fn test_intrinsic_gas() -> Result<()> {
@@ -29,3 +39,71 @@ fn test_intrinsic_gas() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_intrinsic_gas_data() -> Result<()> {
+    let intrinsic_gas = KERNEL.global_labels["intrinsic_gas"];
+
+    // A message transaction with 3 zero and 5 nonzero data bytes.
+    let data = [0x00, 0x01, 0xff, 0x00, 0x10, 0x00, 0x20, 0x30];
+    let mut interpreter: Interpreter<F> =
+        Interpreter::new(intrinsic_gas, vec![0xdeadbeefu32.into()]);
+    interpreter.set_txn_field(NormalizedTxnField::To, 123.into());
+    interpreter.set_txn_field(NormalizedTxnField::DataLen, data.len().into());
+    interpreter.set_memory_segment_bytes(Segment::TxnData, data.to_vec());
+    interpreter.run()?;
+    let expected = GAS_TX + 3 * GAS_TXDATAZERO + 5 * GAS_TXDATANONZERO;
+    assert_eq!(interpreter.stack(), vec![expected.into()]);
+
+    Ok(())
+}
+
+/// Runs the validity checks of a transfer with the given gas limit, and
+/// returns whether the transaction was rejected.
+fn is_transfer_rejected(gas_limit: u32) -> Result<bool> {
+    let process_normalized_txn = KERNEL.global_labels["process_normalized_txn"];
+    let invalid_txn = KERNEL.global_labels["invalid_txn"];
+    let buy_gas = KERNEL.global_labels["buy_gas"];
+
+    // The sender must exist for the transaction to be valid.
+    let sender = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+    let mut state_trie = HashedPartialTrie::from(Node::Empty);
+    state_trie.insert(
+        Nibbles::from_bytes_be(keccak(sender).as_bytes()).unwrap(),
+        rlp::encode(&AccountRlp::default()).to_vec(),
+    );
+    let inputs = GenerationInputs {
+        tries: TrieInputs {
+            state_trie,
+            transactions_trie: Node::Empty.into(),
+            receipts_trie: Node::Empty.into(),
+            storage_tries: vec![],
+        },
+        ..Default::default()
+    };
+
+    let mut interpreter: Interpreter<F> = Interpreter::new_with_generation_inputs(
+        process_normalized_txn,
+        vec![0xdeadbeefu32.into()],
+        inputs,
+    );
+    let state_trie_ptr = interpreter.generation_state.trie_root_ptrs.state_root_ptr;
+    interpreter.set_global_metadata_field(GlobalMetadata::StateTrieRoot, state_trie_ptr.into());
+    interpreter.set_global_metadata_field(GlobalMetadata::BlockGasLimit, 30_000_000.into());
+    interpreter.set_txn_field(NormalizedTxnField::Origin, U256::from_big_endian(&sender));
+    interpreter.set_txn_field(NormalizedTxnField::To, 123.into());
+    interpreter.set_txn_field(NormalizedTxnField::GasLimit, gas_limit.into());
+    interpreter.halt_offsets = vec![invalid_txn, buy_gas];
+    interpreter.run()?;
+
+    Ok(interpreter.generation_state.registers.program_counter == invalid_txn)
+}
+
+#[test]
+fn test_intrinsic_gas_floor() -> Result<()> {
+    // A transfer with exactly its intrinsic gas is valid, but not one with less.
+    assert!(!is_transfer_rejected(GAS_TX)?);
+    assert!(is_transfer_rejected(GAS_TX - 1)?);
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_invalid_opcodes_consume_all_gas() -> Result<()> {
+    // A contract which calls itself with a mode byte as calldata, and records
+    // the outcome of each call.
+    let code = [
+        // Dispatch on the first calldata byte.
+        0x60, 0x00, // PUSH1 0
+        0x35, // CALLDATALOAD
+        0x60, 0xf8, // PUSH1 248
+        0x1c, // SHR
+        0x80, // DUP1
+        0x60, 0x01, // PUSH1 1
+        0x14, // EQ
+        0x60, 0x98, // PUSH1 mode1
+        0x57, // JUMPI
+        0x80, // DUP1
+        0x60, 0x02, // PUSH1 2
+        0x14, // EQ
+        0x60, 0xab, // PUSH1 mode2
+        0x57, // JUMPI
+        0x80, // DUP1
+        0x60, 0x03, // PUSH1 3
+        0x14, // EQ
+        0x60, 0xb2, // PUSH1 mode3
+        0x57, // JUMPI
+        0x50, // POP
+        // Call mode 1 (REVERT). Store success + 1 in slot 10, RETURNDATASIZE in slot 11 and the
+        // gas spent in slot 12.
+        0x5a, // GAS
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x5a, // GAS
+        0x3d, // RETURNDATASIZE
+        0x60, 0x0b, // PUSH1 11
+        0x55, // SSTORE
+        0x90, // SWAP1
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x0a, // PUSH1 10
+        0x55, // SSTORE
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x0c, // PUSH1 12
+        0x55, // SSTORE
+        // Call mode 2 (INVALID). Store success + 1 in slot 20, RETURNDATASIZE in slot 21 and the
+        // gas spent in slot 22.
+        0x5a, // GAS
+        0x60, 0x02, // PUSH1 2
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x5a, // GAS
+        0x3d, // RETURNDATASIZE
+        0x60, 0x15, // PUSH1 21
+        0x55, // SSTORE
+        0x90, // SWAP1
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x14, // PUSH1 20
+        0x55, // SSTORE
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x16, // PUSH1 22
+        0x55, // SSTORE
+        // Call mode 3 (an undefined opcode). Store success + 1 in slot 30, RETURNDATASIZE in slot
+        // 31 and the gas spent in slot 32.
+        0x5a, // GAS
+        0x60, 0x03, // PUSH1 3
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x5a, // GAS
+        0x3d, // RETURNDATASIZE
+        0x60, 0x1f, // PUSH1 31
+        0x55, // SSTORE
+        0x90, // SWAP1
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x1e, // PUSH1 30
+        0x55, // SSTORE
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x20, // PUSH1 32
+        0x55, // SSTORE
+        0x00, // STOP
+        // Mode 1: write slot 1, then revert with a 4-byte reason.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x63, 0xde, 0xad, 0xbe, 0xef, // PUSH4 0xdeadbeef
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x04, // PUSH1 4
+        0x60, 0x1c, // PUSH1 28
+        0xfd, // REVERT
+        // Mode 2: write slot 2, then execute INVALID.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0xfe, // INVALID
+        // Mode 3: write slot 3, then execute an undefined opcode.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x0c, // UNDEFINED
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // The state changes of all three calls were reverted.
+    for slot in 1..=3 {
+        assert!(!storage.contains_key(&U256::from(slot)));
+    }
+
+    // All three calls failed.
+    for slot in [10, 20, 30] {
+        assert_eq!(storage[&U256::from(slot)], U256::one());
+    }
+
+    // `REVERT` returns data, and gives back the remaining gas.
+    assert_eq!(storage[&U256::from(11)], 4.into());
+    assert!(storage[&U256::from(12)] < 0x8000.into());
+
+    // `INVALID` and undefined opcodes don't return any data, and consume all
+    // the gas given to the call.
+    // PUSH1 * 8, SHL, MSTORE, PUSH2: 33. ADDRESS, GAS: 4. CALL: 100 + 0x8000.
+    // Storing a zero return data size leaves the slot empty.
+    for slot in [21, 31] {
+        assert!(!storage.contains_key(&U256::from(slot)));
+    }
+    for slot in [22, 32] {
+        assert_eq!(storage[&U256::from(slot)], (137 + 0x8000).into());
+    }
+
+    Ok(())
+}
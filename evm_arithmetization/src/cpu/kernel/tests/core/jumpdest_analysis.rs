@@ -7,6 +7,7 @@ use plonky2::field::goldilocks_field::GoldilocksField as F;
 use crate::cpu::kernel::aggregator::KERNEL;
 use crate::cpu::kernel::interpreter::Interpreter;
 use crate::cpu::kernel::opcodes::{get_opcode, get_push_opcode};
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
 use crate::witness::operation::CONTEXT_SCALING_FACTOR;
 
 #[test]
@@ -210,3 +211,64 @@ fn test_verify_non_jumpdest() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_jump_to_jumpdest() -> Result<()> {
+    let code = [
+        0x60, 0x09, // PUSH1 9
+        0x56, // JUMP
+        // Skipped: store 1 in slot 1.
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+        0x5b, // JUMPDEST
+        0x60, 0x2a, // PUSH1 42
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    assert_eq!(storage.get(&0.into()), Some(&42.into()));
+    assert_eq!(storage.get(&1.into()), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_jump_into_push_data() -> Result<()> {
+    let code = [
+        0x60, 0x04, // PUSH1 4
+        0x56, // JUMP
+        0x61, 0x5b, 0x5b, // PUSH2 0x5b5b, whose data looks like two JUMPDESTs
+        0x60, 0x2a, // PUSH1 42
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let outcome = ContractTxn::new(&code).run_failing()?;
+
+    // The jump halts exceptionally, consuming all the gas.
+    assert!(outcome.storage.is_empty());
+    assert_eq!(outcome.leftover_gas, U256::zero());
+
+    Ok(())
+}
+
+#[test]
+fn test_jump_past_end_of_code() -> Result<()> {
+    let code = [
+        0x60, 0xff, // PUSH1 255
+        0x56, // JUMP
+        0x5b, // JUMPDEST
+        0x60, 0x2a, // PUSH1 42
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let outcome = ContractTxn::new(&code).run_failing()?;
+
+    // Code past the end reads as STOP, which is not a JUMPDEST.
+    assert!(outcome.storage.is_empty());
+    assert_eq!(outcome.leftover_gas, U256::zero());
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use ethereum_types::U256;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
+
+#[test]
+fn test_keccak256_of_word() -> Result<()> {
+    let bytes: [u8; 32] = core::array::from_fn(|i| i as u8);
+    let mut code = vec![0x7f]; // PUSH32 0x000102...1f
+    code.extend(bytes);
+    code.extend([
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x20, // KECCAK256
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Hash the word again, and store the gas spent in slot 1.
+        0x5a, // GAS
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x20, // KECCAK256
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+    ]);
+    let storage = run_contract_txn(&code, 0)?;
+
+    assert_eq!(
+        storage[&U256::zero()],
+        U256::from_big_endian(keccak(bytes).as_bytes())
+    );
+    // GAS: 2, PUSH1 * 2: 6, KECCAK256 of one word: 30 + 6, POP: 2.
+    assert_eq!(storage[&U256::one()], 46.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_keccak256_of_empty_range() -> Result<()> {
+    let code = [
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xff, 0xff, 0xff, // PUSH3 0xffffff
+        0x20, // KECCAK256
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        // Hash zero bytes at a high offset again, and store the gas spent in
+        // slot 1.
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xff, 0xff, 0xff, // PUSH3 0xffffff
+        0x20, // KECCAK256
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Store MSIZE == 0 in slot 2.
+        0x59, // MSIZE
+        0x15, // ISZERO
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    assert_eq!(
+        storage[&U256::zero()],
+        U256::from_big_endian(keccak([]).as_bytes())
+    );
+    // GAS: 2, PUSH1 and PUSH3: 6, KECCAK256 of no word: 30, POP: 2. The memory
+    // is not expanded.
+    assert_eq!(storage[&U256::one()], 40.into());
+    assert_eq!(storage[&U256::from(2)], U256::one());
+
+    Ok(())
+}
+
+/// Returns code hashing the memory ranges of the given lengths at offset 0.
+/// Its length doesn't depend on them, so that the kernel hashes it the same
+/// way.
+fn keccak256_code(lens: &[u16]) -> Vec<u8> {
+    let mut code = vec![];
+    for len in lens {
+        code.push(0x61); // PUSH2 len
+        code.extend(len.to_be_bytes());
+        code.extend([
+            0x60, 0x00, // PUSH1 0
+            0x20, // KECCAK256
+            0x50, // POP
+        ]);
+    }
+    code.push(0x00); // STOP
+    code
+}
+
+#[test]
+fn test_keccak256_permutations() -> Result<()> {
+    // Empty ranges are not hashed.
+    let baseline = ContractTxn::new(&keccak256_code(&[0; 5])).run()?;
+    let outcome = ContractTxn::new(&keccak256_code(&[1, 135, 136, 271, 272])).run()?;
+
+    // A message of n bytes is padded to n / 136 + 1 blocks of 136 bytes:
+    // 1 + 1 + 2 + 2 + 3 permutations.
+    assert_eq!(
+        outcome.keccak_permutations - baseline.keccak_permutations,
+        9
+    );
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use ethereum_types::{Address, H256, U256};
+use hex_literal::hex;
+
+use crate::cpu::gas::memory_expansion_cost;
+use crate::cpu::kernel::tests::core::ContractTxn;
+
+/// The address of the contract called by `ContractTxn`.
+const CONTRACT: [u8; 20] = hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87");
+
+#[test]
+fn test_log0_with_empty_data() -> Result<()> {
+    let code = [
+        0x5a, // GAS
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0xa0, // LOG0
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let outcome = ContractTxn::new(&code).run()?;
+
+    // PUSH1, PUSH1: 6. LOG0: 375. GAS: 2.
+    assert_eq!(outcome.storage[&U256::zero()], 383.into());
+
+    let [log] = outcome.receipt.logs.as_slice() else {
+        panic!("Expected a single log, got {:?}", outcome.receipt.logs);
+    };
+    assert_eq!(log.address, Address::from(CONTRACT));
+    assert!(log.topics.is_empty());
+    assert!(log.data.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_log4_with_memory_expansion() -> Result<()> {
+    let code = [
+        0x60, 0xab, // PUSH1 0xab
+        0x60, 0x00, // PUSH1 0
+        0x53, // MSTORE8
+        // Log 64 bytes, expanding the memory from 1 to 2 words.
+        0x5a, // GAS
+        0x60, 0x04, // PUSH1 4
+        0x60, 0x03, // PUSH1 3
+        0x60, 0x02, // PUSH1 2
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x40, // PUSH1 64
+        0x60, 0x00, // PUSH1 0
+        0xa4, // LOG4
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let outcome = ContractTxn::new(&code).run()?;
+
+    // 6 PUSH1s: 18. LOG4: 375 + 4 * 375 + 8 * 64. GAS: 2.
+    let expected = 18 + 375 + 4 * 375 + 8 * 64 + memory_expansion_cost(1, 2) + 2;
+    assert_eq!(outcome.storage[&U256::zero()], expected.into());
+
+    let [log] = outcome.receipt.logs.as_slice() else {
+        panic!("Expected a single log, got {:?}", outcome.receipt.logs);
+    };
+    let mut data = vec![0; 64];
+    data[0] = 0xab;
+    assert_eq!(log.address, Address::from(CONTRACT));
+    assert_eq!(
+        log.topics,
+        (1..=4u64).map(H256::from_low_u64_be).collect::<Vec<_>>()
+    );
+    assert_eq!(log.data.to_vec(), data);
+
+    Ok(())
+}
+
+#[test]
+fn test_log_in_static_context() -> Result<()> {
+    let callee = Address::from_low_u64_be(0xca11ee);
+    let callee_code = [
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0xa0, // LOG0
+    ];
+    let code = [
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xca, 0x11, 0xee, // PUSH3 callee
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xfa, // STATICCALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[(callee, &callee_code[..])])
+        .run()?;
+
+    // The LOG0 faults, so the call fails and emits no log.
+    assert_eq!(outcome.storage[&U256::zero()], 1.into());
+    assert!(outcome.receipt.logs.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::gas::memory_expansion_cost;
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
+
+#[test]
+fn test_memory_expansion_matches_kernel() -> Result<()> {
+    // Memory sizes, in words, reached by successive `MSTORE`s.
+    let sizes: [u64; 5] = [1, 512, 513, 1536, 2048];
+
+    // For each size, store a word at the end of the memory and record the gas
+    // spent in the slot with the same index.
+    let mut code = vec![];
+    for (i, &num_words) in sizes.iter().enumerate() {
+        let offset = ((num_words - 1) * 32) as u16;
+        code.extend([0x5a, 0x60, 0x00, 0x61]); // GAS PUSH1 0 PUSH2
+        code.extend(offset.to_be_bytes());
+        code.extend([0x52, 0x5a, 0x90, 0x03]); // MSTORE GAS SWAP1 SUB
+        code.extend([0x60, i as u8, 0x55]); // PUSH1 i SSTORE
+    }
+    code.push(0x00); // STOP
+
+    let storage = run_contract_txn(&code, 0)?;
+
+    // PUSH1, PUSH2, MSTORE: 9. GAS: 2.
+    let mut current_words = 0;
+    for (i, &num_words) in sizes.iter().enumerate() {
+        let expected = 11 + memory_expansion_cost(current_words, num_words);
+        assert_eq!(storage[&U256::from(i)], expected.into());
+        current_words = num_words;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_mstore_past_max_memory_size() -> Result<()> {
+    let code = [
+        // Sub-calls have a non-empty calldata.
+        0x36, // CALLDATASIZE
+        0x60, 0x1b, // PUSH1 callee
+        0x57, // JUMPI
+        // Call itself with 0x10000 gas, and store success + 1 in slot 0.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x62, 0x01, 0x00, 0x00, // PUSH3 0x010000
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x00, // STOP
+        // Callee: store a word past the maximum memory size.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x63, 0x02, 0xfa, 0xf0, 0x81, // PUSH4 0x02faf081
+        0x52, // MSTORE
+        0x00, // STOP
+    ];
+    let outcome = ContractTxn::new(&code).run()?;
+
+    // The sub-call faults, consuming all of its gas.
+    assert_eq!(outcome.storage[&U256::zero()], 1.into());
+    assert!(outcome.leftover_gas < (400000 - 21000 - 0x10000).into());
+
+    Ok(())
+}
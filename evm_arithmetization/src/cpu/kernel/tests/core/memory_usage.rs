@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::memory::segments::Segment;
+
+/// Runs a transaction copying `len` bytes of calldata to memory, and returns
+/// the number of main memory addresses holding a value at the end.
+fn main_memory_usage_after_calldatacopy(len: u16) -> Result<usize> {
+    let mut code = vec![0x61]; // PUSH2 len
+    code.extend(len.to_be_bytes());
+    code.extend([
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x37, // CALLDATACOPY
+        0x00, // STOP
+    ]);
+    let outcome = ContractTxn::new(&code).run()?;
+    // Segments without any value are absent from the usage.
+    Ok(outcome
+        .memory_usage
+        .get(&Segment::MainMemory)
+        .copied()
+        .unwrap_or(0))
+}
+
+#[test]
+fn test_memory_usage_counts_copied_bytes() -> Result<()> {
+    let without_copy = main_memory_usage_after_calldatacopy(0)?;
+    let with_copy = main_memory_usage_after_calldatacopy(1024)?;
+
+    // Each copied byte is written to its own main memory address.
+    assert_eq!(with_copy - without_copy, 1024);
+
+    Ok(())
+}
@@ -1,4 +1,467 @@
 mod access_lists;
+mod accessed_code;
+mod addmod_mulmod;
+mod block_metadata;
+mod call_depth;
+mod call_gas;
+mod calldata;
+mod codecopy;
+mod context_opcodes;
 mod create_addresses;
+mod create_collision;
+mod delegate_call;
+mod empty_storage;
+mod execution_trace;
+mod exp_gas;
+mod extcodecopy;
+mod final_accounts;
+mod gas_opcode;
+mod gas_price;
+mod gas_refund;
+mod gas_schedule;
 mod intrinsic_gas;
+mod invalid_opcode;
 mod jumpdest_analysis;
+mod keccak256;
+mod logs;
+mod memory_expansion;
+mod memory_usage;
+mod mstore8;
+mod out_of_gas;
+mod precompiles;
+mod return_revert;
+mod returndata;
+mod shifts;
+mod signextend;
+mod sstore;
+mod state_clearing;
+mod static_call;
+mod storage_access_log;
+mod txn_cycles;
+mod txn_validity;
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use ethereum_types::{Address, BigEndianHash, H256, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::{HashedPartialTrie, Node, PartialTrie};
+use plonky2::field::goldilocks_field::GoldilocksField as F;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::generation::mpt::{AccountRlp, LegacyReceiptRlp};
+use crate::generation::state::{StorageAccess, TraceStep};
+use crate::generation::trie_extractor::{
+    get_state_trie, read_receipt_trie_value, read_state_trie_value, read_storage_trie_value,
+    read_trie, AccountTrieRecord,
+};
+use crate::generation::TrieInputs;
+use crate::memory::segments::Segment;
+use crate::proof::{BlockHashes, BlockMetadata, Hardfork, TrieRoots};
+use crate::util::u256_to_usize;
+use crate::witness::memory::MemoryAddress;
+use crate::GenerationInputs;
+
+/// Runs a transaction calling a contract with the given code, starting at the
+/// given call depth, and returns the contract storage once the transaction has
+/// been processed.
+pub(crate) fn run_contract_txn(
+    code: &[u8],
+    initial_call_depth: u64,
+) -> Result<HashMap<U256, U256>> {
+    Ok(ContractTxn::new(code)
+        .initial_call_depth(initial_call_depth)
+        .run()?
+        .storage)
+}
+
+/// A transaction calling a contract, run in the interpreter against the
+/// initial state of the harness: the block beneficiary, the sender of
+/// `CONTRACT_TXN` and the called contract, along with any additional account.
+pub(crate) struct ContractTxn<'a> {
+    code: &'a [u8],
+    txn: &'a [u8],
+    initial_call_depth: u64,
+    block_metadata: BlockMetadata,
+    extra_accounts: Vec<(Address, AccountRlp)>,
+    extra_code: Vec<&'a [u8]>,
+    max_txn_cycles: Option<usize>,
+    previous: Option<&'a ContractTxnOutcome>,
+    record_storage_accesses: bool,
+    record_execution_trace: bool,
+}
+
+impl<'a> ContractTxn<'a> {
+    /// Sends `CONTRACT_TXN` to a contract with the given code, at call depth 0,
+    /// in a block with the metadata of `contract_txn_block_metadata`.
+    pub(crate) fn new(code: &'a [u8]) -> Self {
+        Self {
+            code,
+            txn: &CONTRACT_TXN,
+            initial_call_depth: 0,
+            block_metadata: contract_txn_block_metadata(Hardfork::default()),
+            extra_accounts: vec![],
+            extra_code: vec![],
+            max_txn_cycles: None,
+            previous: None,
+            record_storage_accesses: false,
+            record_execution_trace: false,
+        }
+    }
+
+    /// Processes the given signed transaction instead of `CONTRACT_TXN`. It
+    /// must be sent by the same account, with nonce 0, or with the next nonce
+    /// if run `after` another transaction.
+    pub(crate) const fn txn(mut self, txn: &'a [u8]) -> Self {
+        self.txn = txn;
+        self
+    }
+
+    /// Starts the transaction at the given call depth.
+    pub(crate) const fn initial_call_depth(mut self, initial_call_depth: u64) -> Self {
+        self.initial_call_depth = initial_call_depth;
+        self
+    }
+
+    /// Uses the gas schedule of the given hardfork.
+    pub(crate) const fn hardfork(mut self, hardfork: Hardfork) -> Self {
+        self.block_metadata.block_hardfork = hardfork;
+        self
+    }
+
+    /// Runs the transaction in a block with the given metadata.
+    pub(crate) fn block_metadata(mut self, block_metadata: BlockMetadata) -> Self {
+        self.block_metadata = block_metadata;
+        self
+    }
+
+    /// Adds the given accounts to the initial state trie.
+    pub(crate) fn accounts(mut self, accounts: &[(Address, AccountRlp)]) -> Self {
+        self.extra_accounts.extend_from_slice(accounts);
+        self
+    }
+
+    /// Adds contracts with the given addresses and code to the initial state
+    /// trie.
+    pub(crate) fn contracts(mut self, contracts: &[(Address, &'a [u8])]) -> Self {
+        for &(address, code) in contracts {
+            let account = AccountRlp {
+                code_hash: keccak(code),
+                ..AccountRlp::default()
+            };
+            self.extra_accounts.push((address, account));
+            self.extra_code.push(code);
+        }
+        self
+    }
+
+    /// Fails if the transaction uses more than `max_txn_cycles` CPU cycles.
+    pub(crate) const fn max_txn_cycles(mut self, max_txn_cycles: usize) -> Self {
+        self.max_txn_cycles = Some(max_txn_cycles);
+        self
+    }
+
+    /// Runs the transaction in the same block as `previous`, starting from the
+    /// state it left.
+    pub(crate) const fn after(mut self, previous: &'a ContractTxnOutcome) -> Self {
+        self.previous = Some(previous);
+        self
+    }
+
+    /// Records the storage accesses into
+    /// `ContractTxnOutcome::storage_accesses`.
+    pub(crate) const fn record_storage_accesses(mut self) -> Self {
+        self.record_storage_accesses = true;
+        self
+    }
+
+    /// Records the executed user instructions into
+    /// `ContractTxnOutcome::execution_trace`.
+    pub(crate) const fn record_execution_trace(mut self) -> Self {
+        self.record_execution_trace = true;
+        self
+    }
+
+    /// Runs the transaction, and asserts that it succeeds.
+    pub(crate) fn run(self) -> Result<ContractTxnOutcome> {
+        let outcome = self.execute()?;
+        assert!(outcome.receipt.status);
+        Ok(outcome)
+    }
+
+    /// Runs the transaction, and asserts that it fails.
+    pub(crate) fn run_failing(self) -> Result<ContractTxnOutcome> {
+        let outcome = self.execute()?;
+        assert!(!outcome.receipt.status);
+        Ok(outcome)
+    }
+
+    /// Runs the transaction, whether it succeeds or not.
+    pub(crate) fn execute(self) -> Result<ContractTxnOutcome> {
+        let Self {
+            code,
+            txn,
+            initial_call_depth,
+            block_metadata,
+            extra_accounts,
+            extra_code,
+            max_txn_cycles,
+            previous,
+            record_storage_accesses,
+            record_execution_trace,
+        } = self;
+
+        let beneficiary = block_metadata.block_beneficiary;
+        let sender = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        let to = hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87");
+
+        let to_hashed = keccak(to);
+
+        let code_hash = keccak(code);
+
+        let mut contract_code = HashMap::new();
+        contract_code.insert(keccak(vec![]), vec![]);
+        contract_code.insert(code_hash, code.to_vec());
+        for code in extra_code {
+            contract_code.insert(keccak(code), code.to_vec());
+        }
+
+        let beneficiary_account_before = AccountRlp {
+            nonce: 1.into(),
+            ..AccountRlp::default()
+        };
+        let sender_account_before = AccountRlp {
+            balance: 0x0de0b6b3a7640000u64.into(),
+            ..AccountRlp::default()
+        };
+        let to_account_before = AccountRlp {
+            balance: 0x0de0b6b3a7640000u64.into(),
+            code_hash,
+            ..AccountRlp::default()
+        };
+
+        let mut state_trie_before = HashedPartialTrie::from(Node::Empty);
+        for (address, account) in [
+            (beneficiary, &beneficiary_account_before),
+            (Address::from(sender), &sender_account_before),
+            (Address::from(to), &to_account_before),
+        ]
+        .into_iter()
+        .chain(
+            extra_accounts
+                .iter()
+                .map(|(address, account)| (*address, account)),
+        ) {
+            state_trie_before.insert(
+                Nibbles::from_bytes_be(keccak(address).as_bytes()).unwrap(),
+                rlp::encode(account).to_vec(),
+            );
+        }
+
+        let mut storage_trie_before = HashedPartialTrie::from(Node::Empty);
+        let mut txn_number_before = U256::zero();
+        let mut gas_used_before = U256::zero();
+        if let Some(previous) = previous {
+            state_trie_before = previous.state_trie.clone();
+            storage_trie_before = previous.storage_trie.clone();
+            txn_number_before = U256::one();
+            gas_used_before = previous.receipt.cum_gas_used;
+        }
+
+        let tries_before = TrieInputs {
+            state_trie: state_trie_before,
+            transactions_trie: Node::Empty.into(),
+            receipts_trie: Node::Empty.into(),
+            storage_tries: vec![(to_hashed, storage_trie_before)],
+        };
+
+        let inputs = GenerationInputs {
+            signed_txn: Some(txn.to_vec()),
+            withdrawals: vec![],
+            tries: tries_before,
+            // We stop before checking the final tries.
+            trie_roots_after: TrieRoots {
+                state_root: H256::zero(),
+                transactions_root: H256::zero(),
+                receipts_root: H256::zero(),
+            },
+            contract_code,
+            block_metadata,
+            checkpoint_state_trie_root: HashedPartialTrie::from(Node::Empty).hash(),
+            txn_number_before,
+            gas_used_before,
+            gas_used_after: 0.into(),
+            block_hashes: BlockHashes {
+                prev_hashes: vec![H256::default(); 256],
+                cur_hash: H256::default(),
+            },
+        };
+
+        let initial_offset = KERNEL.global_labels["main"];
+        let mut interpreter: Interpreter<F> =
+            Interpreter::new_with_generation_inputs(initial_offset, vec![], inputs);
+        interpreter
+            .halt_offsets
+            .push(KERNEL.global_labels["txn_after"]);
+        interpreter
+            .set_global_metadata_field(GlobalMetadata::CallStackDepth, initial_call_depth.into());
+        interpreter.set_is_kernel(true);
+        if record_storage_accesses {
+            interpreter.generation_state.record_storage_accesses();
+        }
+        if record_execution_trace {
+            interpreter.generation_state.record_execution_trace();
+        }
+        interpreter.generation_state.txn_cycles.max_cycles = max_txn_cycles;
+        interpreter.run()?;
+
+        // stack: success, leftover_gas, ...
+        let stack = interpreter.stack();
+        let leftover_gas = stack[stack.len() - 2];
+
+        // Carry on until the receipt has been inserted.
+        interpreter.halt_offsets = vec![KERNEL.global_labels["perform_final_checks"]];
+        interpreter.run()?;
+
+        // Merge the preinitialized trie data with the values written during
+        // execution, so that the tries can be read back from memory.
+        let memory = &interpreter.generation_state.memory;
+        let preinitialized_len = memory
+            .get_preinitialized_segment(Segment::TrieData)
+            .map_or(0, |segment| segment.content.len());
+        let trie_data_len = memory.contexts[0].segments[Segment::TrieData.unscale()]
+            .content
+            .len()
+            .max(preinitialized_len);
+        let trie_data: Vec<_> = (0..trie_data_len)
+            .map(|virt| Some(memory.get_with_init(MemoryAddress::new(0, Segment::TrieData, virt))))
+            .collect();
+        *interpreter.get_trie_data_mut() = trie_data;
+
+        let memory = &interpreter.generation_state.memory;
+        let state_trie_ptr =
+            u256_to_usize(interpreter.get_global_metadata_field(GlobalMetadata::StateTrieRoot))
+                .unwrap();
+        let accounts = read_trie(memory, state_trie_ptr, read_state_trie_value).unwrap();
+        let state_trie = get_state_trie(memory, state_trie_ptr).unwrap();
+        let storage_trie = interpreter
+            .generation_state
+            .final_storage_trie(Address::from(to))
+            .unwrap()
+            .unwrap();
+        let receipt_trie_ptr =
+            u256_to_usize(interpreter.get_global_metadata_field(GlobalMetadata::ReceiptTrieRoot))
+                .unwrap();
+        let receipts = read_trie(memory, receipt_trie_ptr, read_receipt_trie_value).unwrap();
+        let receipt = receipts.into_values().next().unwrap().1;
+        let storage_ptr = accounts[&Nibbles::from_h256_be(to_hashed)].storage_ptr;
+        let storage = read_trie(memory, storage_ptr, |slice| {
+            Ok(read_storage_trie_value(slice))
+        })
+        .unwrap();
+
+        let storage = storage
+            .into_iter()
+            .map(|(key, value)| {
+                let slot = (0..=255u8)
+                    .map(U256::from)
+                    .find(|slot| {
+                        let mut bytes = [0; 32];
+                        slot.to_big_endian(&mut bytes);
+                        Nibbles::from_h256_be(keccak(bytes)) == key
+                    })
+                    .expect("Unexpected storage slot");
+                (slot, value)
+            })
+            .collect();
+        let accounts = accounts
+            .into_iter()
+            .map(|(key, account)| (H256::from_uint(&key.try_into_u256().unwrap()), account))
+            .collect();
+        let storage_accesses = interpreter
+            .generation_state
+            .take_storage_access_log()
+            .map(|log| log.accesses);
+        let execution_trace = interpreter.generation_state.take_execution_trace();
+
+        Ok(ContractTxnOutcome {
+            storage,
+            accounts,
+            leftover_gas,
+            storage_accesses,
+            receipt,
+            state_trie,
+            storage_trie,
+            txn_cycles: interpreter.generation_state.txn_cycles.cycles(),
+            cycles: interpreter.clock,
+            execution_trace,
+            accessed_code_hashes: interpreter.generation_state.accessed_code_hashes(),
+            memory_usage: interpreter.generation_state.memory.usage(),
+            final_accounts: interpreter.generation_state.final_accounts().unwrap(),
+            keccak_permutations: interpreter.table_rows.keccak_permutations(),
+        })
+    }
+}
+
+/// The state left by a transaction run with `ContractTxn`.
+pub(crate) struct ContractTxnOutcome {
+    /// The storage of the called contract.
+    pub(crate) storage: HashMap<U256, U256>,
+    /// The accounts of the final state trie, by state key.
+    pub(crate) accounts: HashMap<H256, AccountTrieRecord>,
+    /// The gas left to the sender, refunds included.
+    pub(crate) leftover_gas: U256,
+    /// The storage reads and writes of the transaction, in execution order, if
+    /// recorded.
+    pub(crate) storage_accesses: Option<Vec<StorageAccess>>,
+    /// The receipt of the transaction.
+    pub(crate) receipt: LegacyReceiptRlp,
+    /// The final state trie.
+    pub(crate) state_trie: HashedPartialTrie,
+    /// The storage trie of the called contract, as returned by
+    /// `GenerationState::final_storage_trie`.
+    pub(crate) storage_trie: HashedPartialTrie,
+    /// The CPU cycles spent processing the transaction, as recorded by
+    /// `TxnCycles`.
+    pub(crate) txn_cycles: Vec<usize>,
+    /// The CPU cycles of the whole run, up to the final checks.
+    pub(crate) cycles: usize,
+    /// The user instructions executed by the transaction, if recorded.
+    pub(crate) execution_trace: Option<Vec<TraceStep>>,
+    /// The hashes of the codes loaded by the kernel.
+    pub(crate) accessed_code_hashes: HashSet<H256>,
+    /// The number of memory addresses holding a value in each segment.
+    pub(crate) memory_usage: HashMap<Segment, usize>,
+    /// The accounts of the final state trie, as returned by
+    /// `GenerationState::final_accounts`.
+    pub(crate) final_accounts: Vec<(H256, AccountRlp)>,
+    /// The Keccak-f permutations of the whole run, up to the final checks.
+    pub(crate) keccak_permutations: usize,
+}
+
+/// The metadata of the block in which `ContractTxn` runs its transaction, by
+/// default.
+pub(crate) fn contract_txn_block_metadata(hardfork: Hardfork) -> BlockMetadata {
+    BlockMetadata {
+        block_beneficiary: Address::from(hex!("2adc25665018aa1fe0e6bc666dac8fc2697ff9ba")),
+        block_timestamp: 0x03e8.into(),
+        block_number: 1.into(),
+        block_difficulty: 0x020000.into(),
+        block_random: H256::from_uint(&0x020000.into()),
+        block_gaslimit: 0xff112233u32.into(),
+        block_chain_id: 1.into(),
+        block_base_fee: 0xa.into(),
+        block_gas_used: 0.into(),
+        block_bloom: [0.into(); 8],
+        block_hardfork: hardfork,
+    }
+}
+
+/// A legacy call to the contract with a gas limit of 400000 and a gas price of
+/// 10, sent by the account whose private key is
+/// `45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8`.
+pub(crate) const CONTRACT_TXN: [u8; 101] = hex!("f863800a83061a8094095e7baea6a6c7c4c2dfeb977efac326af552d87830186a0801ba0ffb600e63115a7362e7811894a91d8ba4330e526f22121c994c4692035dfdfd5a06198379fcac8de3dbfac48b165df4bf88e2088f294b61efb9a65fe2281c76e16");
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_mstore8_writes_a_single_byte() -> Result<()> {
+    let code = [
+        0x60, 0x00, // PUSH1 0
+        0x19, // NOT
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x61, 0x12, 0x34, // PUSH2 0x1234
+        0x60, 0x05, // PUSH1 5
+        0x53, // MSTORE8
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x59, // MSIZE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // Only byte 5 of the word is overwritten, with the low byte of 0x1234.
+    let mut expected = [0xff; 32];
+    expected[5] = 0x34;
+    assert_eq!(storage[&0.into()], U256::from_big_endian(&expected));
+    assert_eq!(storage[&1.into()], 32.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_mstore8_memory_expansion() -> Result<()> {
+    let code = [
+        0x5a, // GAS
+        0x60, 0xab, // PUSH1 0xab
+        0x60, 0x40, // PUSH1 0x40
+        0x53, // MSTORE8
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x59, // MSIZE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x60, 0x40, // PUSH1 0x40
+        0x51, // MLOAD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // Writing byte 0x40 expands the memory to 3 words, which costs 9. MSTORE8: 3.
+    // PUSH1 * 2, GAS: 8.
+    assert_eq!(storage[&0.into()], (8 + 3 + 9).into());
+    assert_eq!(storage[&1.into()], 0x60.into());
+    assert_eq!(storage[&2.into()], U256::from(0xab) << 248);
+
+    Ok(())
+}
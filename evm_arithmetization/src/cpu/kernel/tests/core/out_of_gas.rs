@@ -0,0 +1,38 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+
+#[test]
+fn test_out_of_gas_txn() -> Result<()> {
+    let code = [
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x56, // JUMP
+    ];
+    let outcome = ContractTxn::new(&code).run_failing()?;
+
+    // All the gas is consumed, and charged to the sender at a gas price of 10.
+    assert_eq!(outcome.leftover_gas, U256::zero());
+    assert_eq!(outcome.receipt.cum_gas_used, 400000.into());
+    let sender = Address::from(hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b"));
+    assert_eq!(
+        outcome.accounts[&keccak(sender)].balance,
+        U256::from(0x0de0b6b3a7640000u64) - 400000 * 10
+    );
+
+    // The storage write is reverted, and the transaction value is sent back.
+    assert!(outcome.storage.is_empty());
+    let contract = Address::from(hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87"));
+    assert_eq!(
+        outcome.accounts[&keccak(contract)].balance,
+        0x0de0b6b3a7640000u64.into()
+    );
+
+    Ok(())
+}
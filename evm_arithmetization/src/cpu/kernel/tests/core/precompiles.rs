@@ -0,0 +1,148 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
+
+/// Returns the code of a contract calling the precompile at `address` with
+/// `input`, first with one gas less than `gas_cost` and then with `gas_cost`.
+/// It stores `success_0 + 2 * success_1` in slot 0, the size of the returned
+/// data in slot 1, and the first word of the returned data in slot 2.
+fn precompile_call_code(address: u8, input: &[u8], gas_cost: u16) -> Vec<u8> {
+    let len = input.len() as u8;
+    let mut code = vec![];
+    if !input.is_empty() {
+        // Store the input at the end of the first word of memory.
+        code.push(0x5f + len); // PUSHn
+        code.extend(input);
+        code.extend([0x60, 0x00, 0x52]); // PUSH1 0 MSTORE
+    }
+    for gas in [gas_cost - 1, gas_cost] {
+        code.extend([0x60, 0x20, 0x60, 0x40]); // PUSH1 32 PUSH1 0x40
+        code.extend([0x60, len, 0x60, 32 - len]); // PUSH1 len PUSH1 (32 - len)
+        code.extend([0x60, 0x00, 0x60, address, 0x61]); // PUSH1 0 PUSH1 address PUSH2
+        code.extend(gas.to_be_bytes());
+        code.push(0xf1); // CALL
+    }
+    code.extend([0x60, 0x01, 0x1b, 0x01]); // PUSH1 1 SHL ADD
+    code.extend([0x60, 0x00, 0x55]); // PUSH1 0 SSTORE
+    code.extend([0x3d, 0x60, 0x01, 0x55]); // RETURNDATASIZE PUSH1 1 SSTORE
+    code.extend([0x60, 0x40, 0x51, 0x60, 0x02, 0x55]); // PUSH1 0x40 MLOAD PUSH1 2 SSTORE
+    code.push(0x00); // STOP
+    code
+}
+
+#[test]
+fn test_hashing_precompiles() -> Result<()> {
+    let abc_word = U256::from_big_endian(b"abc") << 232;
+    let cases: [(u8, &[u8], u16, usize, U256); 6] = [
+        // SHA-256: 60 + 12 * words.
+        (
+            0x02,
+            b"",
+            60,
+            32,
+            hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").into(),
+        ),
+        (
+            0x02,
+            b"abc",
+            72,
+            32,
+            hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad").into(),
+        ),
+        // RIPEMD-160: 600 + 120 * words, with the hash left-padded to 32 bytes.
+        (
+            0x03,
+            b"",
+            600,
+            32,
+            U256::from_big_endian(&hex!("9c1185a5c5e9fc54612808977ee8f548b2258d31")),
+        ),
+        (
+            0x03,
+            b"abc",
+            720,
+            32,
+            U256::from_big_endian(&hex!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")),
+        ),
+        // Identity: 15 + 3 * words.
+        (0x04, b"", 15, 0, U256::zero()),
+        (0x04, b"abc", 18, 3, abc_word),
+    ];
+
+    for (address, input, gas_cost, output_len, output) in cases {
+        let code = precompile_call_code(address, input, gas_cost);
+        let storage = run_contract_txn(&code, 0)?;
+        let slot = |i: u64| storage.get(&i.into()).copied().unwrap_or_default();
+
+        // The call runs out of gas with one gas less than the precompile cost.
+        assert_eq!(slot(0), 2.into(), "precompile {address} on {input:?}");
+        assert_eq!(slot(1), output_len.into());
+        assert_eq!(slot(2), output);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_value_transfer_to_precompile() -> Result<()> {
+    let code = [
+        // Store the cost of `BALANCE(0x04)`, plus 7, in slot 3.
+        0x5a, // GAS
+        0x60, 0x04, // PUSH1 4
+        0x31, // BALANCE
+        0x50, // POP
+        0x5a, // GAS
+        0x90, // SWAP1
+        0x03, // SUB
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        // Call the identity precompile on "abc", with 42 wei.
+        0x62, b'a', b'b', b'c', // PUSH3 "abc"
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x40, // PUSH1 0x40
+        0x60, 0x03, // PUSH1 3
+        0x60, 0x1d, // PUSH1 29
+        0x60, 0x2a, // PUSH1 42
+        0x60, 0x04, // PUSH1 4
+        0x61, 0x10, 0x00, // PUSH2 0x1000
+        0xf1, // CALL
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x60, 0x40, // PUSH1 0x40
+        0x51, // MLOAD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let outcome = ContractTxn::new(&code).run()?;
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    // Precompiles are warm from the start of the transaction, even though
+    // they aren't in the state trie.
+    assert_eq!(slot(3), (100 + 7).into());
+
+    // The precompile ran...
+    assert_eq!(slot(0), U256::one());
+    assert_eq!(slot(1), 3.into());
+    assert_eq!(slot(2), U256::from_big_endian(b"abc") << 232);
+
+    // ...and was credited with the call value.
+    let precompile = Address::from_low_u64_be(0x04);
+    assert_eq!(outcome.accounts[&keccak(precompile)].balance, 42.into());
+    // The contract also received the 100000 wei of the transaction.
+    let contract = Address::from(hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87"));
+    assert_eq!(
+        outcome.accounts[&keccak(contract)].balance,
+        U256::from(0x0de0b6b3a7640000u64) + 100000 - 42
+    );
+
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use anyhow::Result;
+use ethereum_types::{Address, H256, U256};
+use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::{HashedPartialTrie, Node, PartialTrie};
+
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
+use crate::generation::mpt::AccountRlp;
+
+#[test]
+fn test_return_and_revert() -> Result<()> {
+    let code = [
+        // Sub-calls have a non-empty calldata.
+        0x36, // CALLDATASIZE
+        0x60, 0x92, // PUSH1 callee
+        0x57, // JUMPI
+        // Deploy the code 0x00. Store the code size of the new account in slot 1 and
+        // RETURNDATASIZE in slot 2.
+        0x64, 0x60, 0x01, 0x60, 0x00, 0xf3, // PUSH5 0x60016000f3
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x05, // PUSH1 5
+        0x60, 0x1b, // PUSH1 27
+        0x60, 0x00, // PUSH1 0
+        0xf0, // CREATE
+        0x3b, // EXTCODESIZE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        // Revert with 1 byte of data. Store the created address + 1 in slot 3 and RETURNDATASIZE
+        // in slot 4.
+        0x64, 0x60, 0x01, 0x60, 0x00, 0xfd, // PUSH5 0x60016000fd
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x05, // PUSH1 5
+        0x60, 0x1b, // PUSH1 27
+        0x60, 0x00, // PUSH1 0
+        0xf0, // CREATE
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        // Call itself in mode 3, which uses up the gas of the sub-call. Store the address it
+        // created + 1 in slot 5.
+        0x60, 0x03, // PUSH1 3
+        0x60, 0x00, // PUSH1 0
+        0x53, // MSTORE8
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x62, 0x01, 0x00, 0x00, // PUSH3 0x010000
+        0xf1, // CALL
+        0x50, // POP
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x05, // PUSH1 5
+        0x55, // SSTORE
+        // Call itself in mode 1. Store success + 1 in slot 6 and RETURNDATASIZE in slot 7.
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x53, // MSTORE8
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x06, // PUSH1 6
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x07, // PUSH1 7
+        0x55, // SSTORE
+        // Call itself in mode 2. Store success + 1 in slot 8 and RETURNDATASIZE in slot 9.
+        0x60, 0x02, // PUSH1 2
+        0x60, 0x00, // PUSH1 0
+        0x53, // MSTORE8
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x08, // PUSH1 8
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x09, // PUSH1 9
+        0x55, // SSTORE
+        0x00, // STOP
+        // Callee: dispatch on the first calldata byte.
+        0x5b, // JUMPDEST
+        0x60, 0x00, // PUSH1 0
+        0x35, // CALLDATALOAD
+        0x60, 0xf8, // PUSH1 248
+        0x1c, // SHR
+        0x80, // DUP1
+        0x60, 0x03, // PUSH1 3
+        0x14, // EQ
+        0x60, 0xb2, // PUSH1 mode3
+        0x57, // JUMPI
+        // Modes 1 and 2: return or revert with no data at a high offset.
+        0x60, 0x02, // PUSH1 2
+        0x14, // EQ
+        0x60, 0x00, // PUSH1 0
+        0x90, // SWAP1
+        0x63, 0xff, 0xff, 0xff, 0xff, // PUSH4 0xffffffff
+        0x90, // SWAP1
+        0x60, 0xb0, // PUSH1 revert
+        0x57, // JUMPI
+        0xf3, // RETURN
+        0x5b, // JUMPDEST
+        0xfd, // REVERT
+        // Mode 3: try to deploy code starting with 0xEF, and return the created address.
+        0x5b, // JUMPDEST
+        0x69, 0x60, 0xef, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00,
+        0xf3, // PUSH10 0x60ef60005360016000f3
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x0a, // PUSH1 10
+        0x60, 0x16, // PUSH1 22
+        0x60, 0x00, // PUSH1 0
+        0xf0, // CREATE
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+    let slot = |i: u64| storage.get(&i.into()).copied().unwrap_or_default();
+
+    // RETURN in a creation frame deploys the returned code, and leaves no
+    // return data to the creator.
+    assert_eq!(slot(1), 1.into());
+    assert_eq!(slot(2), 0.into());
+
+    // REVERT in a creation frame deploys nothing, but gives its data back.
+    assert_eq!(slot(3), 1.into());
+    assert_eq!(slot(4), 1.into());
+
+    // Code starting with 0xEF is rejected.
+    assert_eq!(slot(5), 1.into());
+
+    // Returning or reverting with no data doesn't expand the memory, however
+    // high the offset.
+    assert_eq!(slot(6), 2.into());
+    assert_eq!(slot(7), 0.into());
+    assert_eq!(slot(8), 1.into());
+    assert_eq!(slot(9), 0.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_implicit_stop_at_end_of_code() -> Result<()> {
+    let callee = Address::from_low_u64_be(0xca11ee);
+    // Stores 1 in slot 0, and runs off the end of the code.
+    let callee_code = [
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+    ];
+    let code = [
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xca, 0x11, 0xee, // PUSH3 callee
+        0x62, 0x01, 0x00, 0x00, // PUSH3 0x010000
+        0xf1, // CALL
+        0x60, 0x00, // PUSH1 0
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Also run off the end of the code.
+        0x60, 0x2a, // PUSH1 42
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+    ];
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[(callee, &callee_code[..])])
+        .run()?;
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    // The call succeeded with empty return data, and the transaction itself
+    // succeeded too, so that both storage writes are committed.
+    assert_eq!(slot(0), U256::one());
+    assert_eq!(slot(1), U256::zero());
+    assert_eq!(slot(2), 42.into());
+
+    let mut callee_storage = HashedPartialTrie::from(Node::Empty);
+    callee_storage.insert(
+        Nibbles::from_h256_be(keccak(H256::zero())),
+        rlp::encode(&U256::one()).to_vec(),
+    );
+    let callee_account: AccountRlp = rlp::decode(
+        outcome
+            .state_trie
+            .get(Nibbles::from_h256_be(keccak(callee)))
+            .unwrap(),
+    )?;
+    assert_eq!(callee_account.storage_root, callee_storage.hash());
+
+    Ok(())
+}
@@ -0,0 +1,395 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
+
+#[test]
+fn test_returndata() -> Result<()> {
+    // A contract which, when called without calldata, calls itself in various
+    // modes given by the first calldata byte, and records the return data.
+    let code = [
+        // Dispatch on the first calldata byte.
+        0x60, 0x00, // PUSH1 0
+        0x35, // CALLDATALOAD
+        0x60, 0xf8, // PUSH1 248
+        0x1c, // SHR
+        0x80, // DUP1
+        0x60, 0x01, // PUSH1 1
+        0x14, // EQ
+        0x60, 0xe3, // PUSH1 mode1
+        0x57, // JUMPI
+        0x80, // DUP1
+        0x60, 0x02, // PUSH1 2
+        0x14, // EQ
+        0x60, 0xee, // PUSH1 mode2
+        0x57, // JUMPI
+        0x80, // DUP1
+        0x60, 0x03, // PUSH1 3
+        0x14, // EQ
+        0x60, 0xfc, // PUSH1 mode3
+        0x57, // JUMPI
+        0x50, // POP
+        // Call mode 1, store RETURNDATASIZE in slot 1 and the copied word in slot 2.
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x50, // POP
+        0x3d, // RETURNDATASIZE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x3e, // RETURNDATACOPY
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        // Call mode 2, store RETURNDATASIZE in slot 3, success + 1 in slot 4 and the revert reason
+        // in slot 5.
+        0x60, 0x02, // PUSH1 2
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x3d, // RETURNDATASIZE
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        0x60, 0x04, // PUSH1 4
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x3e, // RETURNDATACOPY
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0xe0, // PUSH1 224
+        0x1c, // SHR
+        0x60, 0x05, // PUSH1 5
+        0x55, // SSTORE
+        // Call mode 3, store success + 1 in slot 6.
+        0x60, 0x03, // PUSH1 3
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x06, // PUSH1 6
+        0x55, // SSTORE
+        // Call mode 1 again, then CREATE a contract. Store RETURNDATASIZE in slot 7 and (address
+        // != 0) + 1 in slot 8.
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x50, // POP
+        // Init code: PUSH1 0 PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN.
+        0x7f, 0x60, 0x00, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+        0x00, // PUSH32 0x600060005360016000f300000000000000000000000000000000000000000000
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x0a, // PUSH1 10
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0xf0, // CREATE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x07, // PUSH1 7
+        0x55, // SSTORE
+        0x15, // ISZERO
+        0x15, // ISZERO
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x08, // PUSH1 8
+        0x55, // SSTORE
+        0x00, // STOP
+        // Mode 1: return a 32-byte word.
+        0x5b, // JUMPDEST
+        0x60, 0x2a, // PUSH1 42
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+        // Mode 2: revert with a 4-byte reason.
+        0x5b, // JUMPDEST
+        0x63, 0xde, 0xad, 0xbe, 0xef, // PUSH4 0xdeadbeef
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x04, // PUSH1 4
+        0x60, 0x1c, // PUSH1 28
+        0xfd, // REVERT
+        // Mode 3: call mode 1, and copy past the end of its return data.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x50, // POP
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x3e, // RETURNDATACOPY
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+    let slot = |i: u64| storage.get(&i.into()).copied().unwrap_or_default();
+
+    // In-bounds copy of the return data.
+    assert_eq!(slot(1), 32.into());
+    assert_eq!(slot(2), 42.into());
+
+    // After a revert, the return data holds the revert reason.
+    assert_eq!(slot(3), 4.into());
+    assert_eq!(slot(4), 1.into());
+    assert_eq!(slot(5), 0xdeadbeefu32.into());
+
+    // Copying past the end of the return data makes the frame fail.
+    assert_eq!(slot(6), 1.into());
+
+    // After a successful CREATE, the return data is empty.
+    assert_eq!(slot(7), 0.into());
+    assert_eq!(slot(8), 2.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_returndata_longer_than_output() -> Result<()> {
+    // Returns the 64 bytes 0x11 || 0x22, as two words.
+    let callee_code = [
+        0x60, 0x11, // PUSH1 0x11
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x22, // PUSH1 0x22
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        0x60, 0x40, // PUSH1 64
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ];
+    // Calls the callee with a 32-byte output region, right before a word set
+    // to 0xff, and records the memory and return data afterwards.
+    let code = [
+        0x60, 0xff, // PUSH1 0xff
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xca, 0x11, 0xee, // PUSH3 0xca11ee
+        0x5a, // GAS
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x51, // MLOAD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x60, 0x20, // PUSH1 32
+        0x51, // MLOAD
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x40, // PUSH1 64
+        0x3e, // RETURNDATACOPY
+        0x60, 0x40, // PUSH1 64
+        0x51, // MLOAD
+        0x60, 0x05, // PUSH1 5
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let callee = Address::from_low_u64_be(0xca11ee);
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[(callee, &callee_code)])
+        .run()?;
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    assert_eq!(slot(1), U256::one());
+
+    // Only the first 32 bytes of the return data are copied to memory.
+    assert_eq!(slot(2), 0x11.into());
+    assert_eq!(slot(3), 0xff.into());
+
+    // The whole return data is still available.
+    assert_eq!(slot(4), 64.into());
+    assert_eq!(slot(5), 0x22.into());
+
+    Ok(())
+}
+
+/// Reverts with the 36 bytes `0x08c379a0 || 42`, as a 4-byte selector followed
+/// by a word.
+const REVERTING_CODE: [u8; 21] = [
+    0x63, 0x08, 0xc3, 0x79, 0xa0, // PUSH4 0x08c379a0
+    0x60, 0xe0, // PUSH1 224
+    0x1b, // SHL
+    0x60, 0x00, // PUSH1 0
+    0x52, // MSTORE
+    0x60, 0x2a, // PUSH1 42
+    0x60, 0x04, // PUSH1 4
+    0x52, // MSTORE
+    0x60, 0x24, // PUSH1 36
+    0x60, 0x00, // PUSH1 0
+    0xfd, // REVERT
+];
+
+#[test]
+fn test_returndata_after_revert() -> Result<()> {
+    // Calls the reverting callee, and copies its revert reason to 0x100.
+    let code = [
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xca, 0x11, 0xee, // PUSH3 0xca11ee
+        0x5a, // GAS
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x60, 0x24, // PUSH1 36
+        0x60, 0x00, // PUSH1 0
+        0x61, 0x01, 0x00, // PUSH2 0x100
+        0x3e, // RETURNDATACOPY
+        0x61, 0x01, 0x00, // PUSH2 0x100
+        0x51, // MLOAD
+        0x60, 0xe0, // PUSH1 224
+        0x1c, // SHR
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x61, 0x01, 0x04, // PUSH2 0x104
+        0x51, // MLOAD
+        0x60, 0x04, // PUSH1 4
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let callee = Address::from_low_u64_be(0xca11ee);
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[(callee, &REVERTING_CODE)])
+        .run()?;
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    // The call failed, and its return data is the whole revert reason.
+    assert_eq!(slot(1), U256::one());
+    assert_eq!(slot(2), 36.into());
+    assert_eq!(slot(3), 0x08c379a0u32.into());
+    assert_eq!(slot(4), 42.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_returndata_after_out_of_gas() -> Result<()> {
+    // Loops forever.
+    let looping_code = [
+        0x5b, // JUMPDEST
+        0x60, 0x00, // PUSH1 0
+        0x56, // JUMP
+    ];
+    // Calls the reverting callee to fill the return data, then calls the
+    // looping callee with 10000 gas.
+    let code = [
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0xca, 0x11, 0xee, // PUSH3 0xca11ee
+        0x5a, // GAS
+        0xf1, // CALL
+        0x50, // POP
+        0x3d, // RETURNDATASIZE
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x62, 0x10, 0x09, 0x00, // PUSH3 0x100900
+        0x61, 0x27, 0x10, // PUSH2 10000
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x3d, // RETURNDATASIZE
+        0x60, 0x03, // PUSH1 3
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let reverting = Address::from_low_u64_be(0xca11ee);
+    let looping = Address::from_low_u64_be(0x100900);
+    let outcome = ContractTxn::new(&code)
+        .contracts(&[(reverting, &REVERTING_CODE), (looping, &looping_code)])
+        .run()?;
+    let slot = |i: u64| outcome.storage.get(&i.into()).copied().unwrap_or_default();
+
+    assert_eq!(slot(1), 36.into());
+
+    // The call ran out of gas, and cleared the return data of the previous one.
+    assert_eq!(slot(2), U256::one());
+    assert_eq!(slot(3), U256::zero());
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+const SHL: u8 = 0x1b;
+const SHR: u8 = 0x1c;
+const SAR: u8 = 0x1d;
+
+#[test]
+fn test_shifts() -> Result<()> {
+    let min = U256::one() << 255;
+    let minus = |x: u64| U256::zero().overflowing_sub(x.into()).0;
+    let cases = [
+        (SHL, 0, U256::from(0x1234), U256::from(0x1234)),
+        (SHL, 255, U256::one(), min),
+        (SHL, 256, U256::one(), U256::zero()),
+        (SHR, 0, min, min),
+        (SHR, 255, min, U256::one()),
+        (SHR, 256, min, U256::zero()),
+        (SAR, 0, minus(2), minus(2)),
+        (SAR, 4, minus(32), minus(2)),
+        (SAR, 255, min, U256::MAX),
+        (SAR, 255, min >> 1, U256::zero()),
+        (SAR, 256, U256::from(0x1234), U256::zero()),
+        (SAR, 300, minus(2), U256::MAX),
+    ];
+
+    // Store the result of each case in the slot with the same index.
+    let mut code = vec![];
+    for (i, &(opcode, shift, value, _)) in cases.iter().enumerate() {
+        code.push(0x7f); // PUSH32 value
+        let mut bytes = [0; 32];
+        value.to_big_endian(&mut bytes);
+        code.extend(bytes);
+        code.push(0x61); // PUSH2 shift
+        code.extend((shift as u16).to_be_bytes());
+        code.push(opcode);
+        code.extend([0x60, i as u8, 0x55]); // PUSH1 i SSTORE
+    }
+    let storage = run_contract_txn(&code, 0)?;
+
+    for (i, &(_, _, _, expected)) in cases.iter().enumerate() {
+        let result = storage.get(&i.into()).copied().unwrap_or_default();
+        assert_eq!(result, expected, "Wrong result for case {}", i);
+    }
+
+    Ok(())
+}
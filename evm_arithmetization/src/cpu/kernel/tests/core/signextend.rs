@@ -0,0 +1,38 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_signextend() -> Result<()> {
+    // For each (value, byte index), sign-extend the value and store it in slot i.
+    let cases: [&[u8]; 6] = [
+        &[0x60, 0xff, 0x60, 0x00],             // PUSH1 0xff PUSH1 0
+        &[0x60, 0x7f, 0x60, 0x00],             // PUSH1 0x7f PUSH1 0
+        &[0x62, 0xab, 0x80, 0x01, 0x60, 0x01], // PUSH3 0xab8001 PUSH1 1
+        &[0x62, 0xab, 0x12, 0x34, 0x60, 0x01], // PUSH3 0xab1234 PUSH1 1
+        &[0x62, 0xab, 0x80, 0x01, 0x60, 0x20], // PUSH3 0xab8001 PUSH1 32
+        &[0x60, 0x80, 0x60, 0x00, 0x19],       // PUSH1 0x80 PUSH1 0 NOT
+    ];
+    let mut code = vec![];
+    for (i, operands) in cases.iter().enumerate() {
+        code.extend_from_slice(operands);
+        code.extend([0x0b, 0x60, i as u8, 0x55]); // SIGNEXTEND PUSH1 i SSTORE
+    }
+    let storage = run_contract_txn(&code, 0)?;
+    let slot = |i: u64| storage.get(&i.into()).copied().unwrap_or_default();
+
+    // A negative byte is extended with ones, a positive one with zeros.
+    assert_eq!(slot(0), U256::MAX);
+    assert_eq!(slot(1), 0x7f.into());
+
+    // Bytes above the sign byte are overwritten.
+    assert_eq!(slot(2), U256::MAX - 0x7ffe);
+    assert_eq!(slot(3), 0x1234.into());
+
+    // From byte 31 on, SIGNEXTEND is a no-op.
+    assert_eq!(slot(4), 0xab8001.into());
+    assert_eq!(slot(5), 0x80.into());
+
+    Ok(())
+}
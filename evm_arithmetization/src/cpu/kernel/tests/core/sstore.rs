@@ -0,0 +1,111 @@
+use anyhow::Result;
+use ethereum_types::{Address, BigEndianHash, H256, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::PartialTrie;
+
+use crate::cpu::kernel::tests::core::{run_contract_txn, ContractTxn};
+use crate::generation::mpt::AccountRlp;
+
+#[test]
+fn test_sstore_dirty_slot_refund() -> Result<()> {
+    let code = [
+        0x60, 0x01, 0x60, 0x02, 0x55, // PUSH1 1 PUSH1 2 SSTORE
+        0x60, 0x01, 0x60, 0x03, 0x55, // PUSH1 1 PUSH1 3 SSTORE
+        0x60, 0x01, 0x60, 0x04, 0x55, // PUSH1 1 PUSH1 4 SSTORE
+        // Set slot 1, then reset it to its original value in the same
+        // transaction.
+        0x60, 0x01, 0x60, 0x01, 0x55, // PUSH1 1 PUSH1 1 SSTORE
+        0x60, 0x00, 0x60, 0x01, 0x55, // PUSH1 0 PUSH1 1 SSTORE
+        0x00, // STOP
+    ];
+    let outcome = ContractTxn::new(&code).run()?;
+    assert!(!outcome.storage.contains_key(&U256::one()));
+
+    // Intrinsic gas: 21000. PUSH1 * 10: 30. Cold SSTOREs of a zero slot: 4 *
+    // 22100. Warm SSTORE of a dirty slot: 100.
+    let gas_used = 21000 + 30 + 4 * 22100 + 100;
+    // Resetting slot 1 refunds the SSTORE price of a fresh slot, minus the
+    // warm SSTORE price. This is below the `gas_used / 5` cap.
+    let refund = 20000 - 100;
+    assert_eq!(outcome.leftover_gas, (400000 - gas_used + refund).into());
+
+    Ok(())
+}
+
+#[test]
+fn test_sstore_gas_sentry() -> Result<()> {
+    let code = [
+        // Sub-calls have a non-empty calldata.
+        0x36, // CALLDATASIZE
+        0x60, 0x2f, // PUSH1 callee
+        0x57, // JUMPI
+        // Call itself with 2322 gas, and store success + 1 in slot 1.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x09, 0x12, // PUSH2 2322
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        // Call itself with 2323 gas, and store success + 1 in slot 2.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x09, 0x13, // PUSH2 2323
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x00, // STOP
+        // Callee: write 0 to the empty slot 16, which costs 2200.
+        0x5b, // JUMPDEST
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x10, // PUSH1 16
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+
+    // CALLDATASIZE, PUSH1, JUMPI, JUMPDEST, PUSH1 * 2: 22. With 2322 gas, the
+    // SSTORE is reached with 2300 gas left, and fails even though it would
+    // only cost 2200.
+    assert_eq!(storage[&U256::from(1)], 1.into());
+    assert_eq!(storage[&U256::from(2)], 2.into());
+
+    Ok(())
+}
+
+#[test]
+fn test_final_storage_trie() -> Result<()> {
+    let code = [
+        0x61, 0x12, 0x34, 0x60, 0x07, 0x55, // PUSH2 0x1234 PUSH1 7 SSTORE
+        0x00, // STOP
+    ];
+    let outcome = ContractTxn::new(&code).run()?;
+
+    let slot_key = Nibbles::from_h256_be(keccak(H256::from_uint(&7.into())));
+    let value: U256 = rlp::decode(outcome.storage_trie.get(slot_key).unwrap())?;
+    assert_eq!(value, 0x1234.into());
+
+    let contract = Address::from(hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87"));
+    let account: AccountRlp = rlp::decode(
+        outcome
+            .state_trie
+            .get(Nibbles::from_h256_be(keccak(contract)))
+            .unwrap(),
+    )?;
+    assert_eq!(account.storage_root, outcome.storage_trie.hash());
+
+    Ok(())
+}
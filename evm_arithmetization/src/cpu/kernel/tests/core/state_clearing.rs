@@ -0,0 +1,53 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::generation::mpt::AccountRlp;
+
+#[test]
+fn test_touched_empty_accounts_are_deleted() -> Result<()> {
+    let touched = Address::from_low_u64_be(0xdead);
+    let untouched = Address::from_low_u64_be(0xbeef);
+    let funded = Address::from_low_u64_be(0xcafe);
+
+    let code = [
+        // Touch 0xdead with a call without value.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x61, 0xde, 0xad, // PUSH2 0xdead
+        0x60, 0x00, // PUSH1 0
+        0xf1, // CALL
+        0x50, // POP
+        // Send 1 wei to 0xcafe.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x61, 0xca, 0xfe, // PUSH2 0xcafe
+        0x60, 0x00, // PUSH1 0
+        0xf1, // CALL
+        0x50, // POP
+        0x00, // STOP
+    ];
+    let empty_accounts =
+        [touched, untouched, funded].map(|address| (address, AccountRlp::default()));
+    let accounts = ContractTxn::new(&code)
+        .accounts(&empty_accounts)
+        .run()?
+        .accounts;
+
+    // The touched account is still empty at the end of the transaction, so
+    // it is deleted. The untouched empty account is left alone.
+    assert!(!accounts.contains_key(&keccak(touched)));
+    assert!(accounts.contains_key(&keccak(untouched)));
+
+    // The funded account was touched too, but isn't empty anymore.
+    assert_eq!(accounts[&keccak(funded)].balance, U256::one());
+
+    Ok(())
+}
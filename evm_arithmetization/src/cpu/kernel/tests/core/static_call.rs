@@ -0,0 +1,201 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::tests::core::run_contract_txn;
+
+#[test]
+fn test_static_context_write_protection() -> Result<()> {
+    // A contract which, when called without calldata, calls itself in various
+    // modes given by the first calldata byte, and records the results.
+    let code = [
+        // Dispatch on the first calldata byte.
+        0x60, 0x00, // PUSH1 0
+        0x35, // CALLDATALOAD
+        0x60, 0xf8, // PUSH1 248
+        0x1c, // SHR
+        0x80, // DUP1
+        0x60, 0x01, // PUSH1 1
+        0x14, // EQ
+        0x60, 0xc3, // PUSH1 mode1
+        0x57, // JUMPI
+        0x80, // DUP1
+        0x60, 0x02, // PUSH1 2
+        0x14, // EQ
+        0x60, 0xca, // PUSH1 mode2
+        0x57, // JUMPI
+        0x80, // DUP1
+        0x60, 0x03, // PUSH1 3
+        0x14, // EQ
+        0x60, 0xd9, // PUSH1 mode3
+        0x57, // JUMPI
+        0x50, // POP
+        // STATICCALL to mode 1, storing success + 1 in slot 11 and the returned word + 1 in slot
+        // 21.
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xfa, // STATICCALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x0b, // PUSH1 11
+        0x55, // SSTORE
+        0x60, 0x20, // PUSH1 32
+        0x51, // MLOAD
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x15, // PUSH1 21
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        // STATICCALL to mode 2, storing success + 1 in slot 12 and the returned word + 1 in slot
+        // 22.
+        0x60, 0x02, // PUSH1 2
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xfa, // STATICCALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x0c, // PUSH1 12
+        0x55, // SSTORE
+        0x60, 0x20, // PUSH1 32
+        0x51, // MLOAD
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x16, // PUSH1 22
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        // STATICCALL to mode 3, storing success + 1 in slot 13 and the returned word + 1 in slot
+        // 23.
+        0x60, 0x03, // PUSH1 3
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xfa, // STATICCALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x0d, // PUSH1 13
+        0x55, // SSTORE
+        0x60, 0x20, // PUSH1 32
+        0x51, // MLOAD
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x17, // PUSH1 23
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        // CALL to mode 1, storing success + 1 in slot 14 and the returned word + 1 in slot 24.
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x61, 0x80, 0x00, // PUSH2 0x8000
+        0xf1, // CALL
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x0e, // PUSH1 14
+        0x55, // SSTORE
+        0x60, 0x20, // PUSH1 32
+        0x51, // MLOAD
+        0x60, 0x01, // PUSH1 1
+        0x01, // ADD
+        0x60, 0x18, // PUSH1 24
+        0x55, // SSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x20, // PUSH1 32
+        0x52, // MSTORE
+        0x00, // STOP
+        // Mode 1: write 1 to slot 5.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x05, // PUSH1 5
+        0x55, // SSTORE
+        0x00, // STOP
+        // Mode 2: return the word in slot 0, plus 7.
+        0x5b, // JUMPDEST
+        0x60, 0x00, // PUSH1 0
+        0x54, // SLOAD
+        0x60, 0x07, // PUSH1 7
+        0x01, // ADD
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+        // Mode 3: CALL to mode 1, and return the resulting success flag.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x60, 0xf8, // PUSH1 248
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x5a, // GAS
+        0xf1, // CALL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ];
+    let storage = run_contract_txn(&code, 0)?;
+    let slot = |i: u64| storage.get(&i.into()).copied().unwrap_or_default();
+
+    // SSTORE within a STATICCALL makes the sub-call fail, with no return data.
+    assert_eq!(slot(11), 1.into());
+    assert_eq!(slot(21), 1.into());
+
+    // SLOAD within a STATICCALL succeeds.
+    assert_eq!(slot(12), 2.into());
+    assert_eq!(slot(22), 8.into());
+
+    // A CALL from within a static context is static too: the SSTORE below it
+    // fails, while the frame making the CALL itself succeeds.
+    assert_eq!(slot(13), 2.into());
+    assert_eq!(slot(23), 1.into());
+
+    // Outside of a static context, the same SSTORE succeeds.
+    assert_eq!(slot(14), 2.into());
+    assert_eq!(slot(24), 1.into());
+    assert_eq!(slot(5), 1.into());
+
+    Ok(())
+}
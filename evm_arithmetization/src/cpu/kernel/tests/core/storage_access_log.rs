@@ -0,0 +1,78 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::generation::state::StorageAccess;
+
+#[test]
+fn test_storage_access_log() -> Result<()> {
+    let code = [
+        // Sub-calls have a non-empty calldata.
+        0x36, // CALLDATASIZE
+        0x60, 0x22, // PUSH1 callee
+        0x57, // JUMPI
+        // Write 5 to slot 1, then read it back.
+        0x60, 0x05, // PUSH1 5
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x60, 0x01, // PUSH1 1
+        0x54, // SLOAD
+        0x50, // POP
+        // Call itself.
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x30, // ADDRESS
+        0x5a, // GAS
+        0xf1, // CALL
+        0x50, // POP
+        // Copy slot 1 to slot 2.
+        0x60, 0x01, // PUSH1 1
+        0x54, // SLOAD
+        0x60, 0x02, // PUSH1 2
+        0x55, // SSTORE
+        0x00, // STOP
+        // Callee: add 2 to slot 1.
+        0x5b, // JUMPDEST
+        0x60, 0x01, // PUSH1 1
+        0x54, // SLOAD
+        0x60, 0x02, // PUSH1 2
+        0x01, // ADD
+        0x60, 0x01, // PUSH1 1
+        0x55, // SSTORE
+        0x00, // STOP
+    ];
+    let outcome = ContractTxn::new(&code).record_storage_accesses().run()?;
+
+    let address = Address::from(hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87"));
+    let read = |slot: u64, value: u64| StorageAccess::Read {
+        address,
+        slot: slot.into(),
+        value: value.into(),
+    };
+    let write = |slot: u64, old_value: u64, new_value: u64| StorageAccess::Write {
+        address,
+        slot: slot.into(),
+        old_value: old_value.into(),
+        new_value: new_value.into(),
+    };
+
+    // The accesses of the sub-call are logged between those of the caller.
+    assert_eq!(
+        outcome.storage_accesses.unwrap(),
+        vec![
+            write(1, 0, 5),
+            read(1, 5),
+            read(1, 5),
+            write(1, 5, 7),
+            read(1, 7),
+            write(2, 0, 7),
+        ]
+    );
+    assert_eq!(outcome.storage[&U256::from(2)], 7.into());
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::generation::GenerationError;
+
+/// Returns code storing a nonzero value in each of the first `num_slots`
+/// slots.
+fn sstores(num_slots: u8) -> Vec<u8> {
+    (0..num_slots)
+        .flat_map(|slot| [0x60, 0x01, 0x60, slot, 0x55]) // PUSH1 1 PUSH1 slot SSTORE
+        .collect()
+}
+
+#[test]
+fn test_txn_cycles() -> Result<()> {
+    let mut txn_cycles = vec![];
+    for num_slots in [0, 4, 16] {
+        let outcome = ContractTxn::new(&sstores(num_slots)).run()?;
+        let [cycles] = outcome.txn_cycles[..] else {
+            panic!(
+                "Expected a single transaction, got {:?}",
+                outcome.txn_cycles
+            );
+        };
+        assert!(cycles > 0 && cycles < outcome.cycles);
+        txn_cycles.push(cycles);
+    }
+
+    // More complex transactions use more cycles.
+    assert!(txn_cycles.windows(2).all(|w| w[0] < w[1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_txn_cycle_limit() -> Result<()> {
+    let code = sstores(4);
+    let outcome = ContractTxn::new(&code)
+        .max_txn_cycles(usize::MAX)
+        .execute()?;
+    let cycles = outcome.txn_cycles[0];
+
+    // The limit is inclusive.
+    ContractTxn::new(&code).max_txn_cycles(cycles).execute()?;
+
+    let Err(err) = ContractTxn::new(&code).max_txn_cycles(cycles - 1).execute() else {
+        panic!("Expected the transaction to exceed the cycle limit");
+    };
+    assert_eq!(
+        err.downcast_ref::<GenerationError>(),
+        Some(&GenerationError::TxnCycleLimitExceeded { limit: cycles - 1 })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use anyhow::Result;
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::{HashedPartialTrie, Node, PartialTrie};
+use plonky2::field::goldilocks_field::GoldilocksField as F;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::cpu::kernel::tests::core::ContractTxn;
+use crate::generation::mpt::AccountRlp;
+use crate::generation::{GenerationError, InvalidTxnReason, TrieInputs};
+use crate::GenerationInputs;
+
+const SENDER: [u8; 20] = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+
+/// Runs the validity checks of a transfer with 21000 gas, from a sender with
+/// the given account and with the given transaction fields, and returns the
+/// reason why the transaction was rejected, if it has a typed rejection.
+/// Transactions failing the other checks go on to `txn_after`.
+fn transfer_rejection(
+    sender_account: AccountRlp,
+    txn_fields: &[(NormalizedTxnField, U256)],
+) -> Result<Option<InvalidTxnReason>> {
+    let process_normalized_txn = KERNEL.global_labels["process_normalized_txn"];
+    let buy_gas = KERNEL.global_labels["buy_gas"];
+    let txn_after = KERNEL.global_labels["txn_after"];
+
+    let mut state_trie = HashedPartialTrie::from(Node::Empty);
+    state_trie.insert(
+        Nibbles::from_bytes_be(keccak(SENDER).as_bytes()).unwrap(),
+        rlp::encode(&sender_account).to_vec(),
+    );
+    let inputs = GenerationInputs {
+        tries: TrieInputs {
+            state_trie,
+            transactions_trie: Node::Empty.into(),
+            receipts_trie: Node::Empty.into(),
+            storage_tries: vec![],
+        },
+        ..Default::default()
+    };
+
+    let mut interpreter: Interpreter<F> = Interpreter::new_with_generation_inputs(
+        process_normalized_txn,
+        vec![0xdeadbeefu32.into()],
+        inputs,
+    );
+    let state_trie_ptr = interpreter.generation_state.trie_root_ptrs.state_root_ptr;
+    interpreter.set_global_metadata_field(GlobalMetadata::StateTrieRoot, state_trie_ptr.into());
+    interpreter.set_global_metadata_field(GlobalMetadata::BlockGasLimit, 30_000_000.into());
+    interpreter.set_txn_field(NormalizedTxnField::Origin, U256::from_big_endian(&SENDER));
+    interpreter.set_txn_field(NormalizedTxnField::To, 123.into());
+    interpreter.set_txn_field(NormalizedTxnField::GasLimit, 21_000.into());
+    for &(field, value) in txn_fields {
+        interpreter.set_txn_field(field, value);
+    }
+    interpreter.halt_offsets = vec![buy_gas, txn_after];
+
+    match interpreter.run() {
+        Ok(()) => Ok(None),
+        Err(err) => match err.downcast_ref::<GenerationError>() {
+            Some(&GenerationError::InvalidTxn(reason)) => Ok(Some(reason)),
+            _ => Err(err),
+        },
+    }
+}
+
+#[test]
+fn test_nonce_mismatch_is_rejected() -> Result<()> {
+    let sender_account = AccountRlp {
+        nonce: 5.into(),
+        ..AccountRlp::default()
+    };
+    for (txn_nonce, expected) in [
+        (5, None),
+        (4, Some(InvalidTxnReason::NonceMismatch)),
+        (6, Some(InvalidTxnReason::NonceMismatch)),
+    ] {
+        let fields = [(NormalizedTxnField::Nonce, txn_nonce.into())];
+        assert_eq!(
+            transfer_rejection(sender_account.clone(), &fields)?,
+            expected,
+            "transaction nonce {txn_nonce}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_other_invalid_txns_are_not_rejected() -> Result<()> {
+    // The gas limit doesn't cover the intrinsic gas of the transfer.
+    let fields = [(NormalizedTxnField::GasLimit, 20_999.into())];
+    assert_eq!(transfer_rejection(AccountRlp::default(), &fields)?, None);
+
+    // The transaction is for another chain than the block's.
+    let fields = [
+        (NormalizedTxnField::ChainIdPresent, U256::one()),
+        (NormalizedTxnField::ChainId, 5.into()),
+    ];
+    assert_eq!(transfer_rejection(AccountRlp::default(), &fields)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_insufficient_balance_is_rejected() -> Result<()> {
+    // At a gas price of 10, the gas of the transfer costs 210000 wei.
+    let gas_price = U256::from(10);
+    let gas_cost = U256::from(210_000);
+    for (balance, value, expected) in [
+        (gas_cost + 7, 7.into(), None),
+        (
+            gas_cost - 1,
+            U256::zero(),
+            Some(InvalidTxnReason::InsufficientBalance),
+        ),
+        (
+            gas_cost + 6,
+            7.into(),
+            Some(InvalidTxnReason::InsufficientBalance),
+        ),
+    ] {
+        let sender_account = AccountRlp {
+            balance,
+            ..AccountRlp::default()
+        };
+        let fields = [
+            (NormalizedTxnField::MaxFeePerGas, gas_price),
+            (NormalizedTxnField::MaxPriorityFeePerGas, gas_price),
+            (NormalizedTxnField::Value, value),
+        ];
+        assert_eq!(
+            transfer_rejection(sender_account, &fields)?,
+            expected,
+            "balance {balance}, value {value}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_sender_with_code_is_rejected() -> Result<()> {
+    let eoa = AccountRlp::default();
+    assert_eq!(transfer_rejection(eoa, &[])?, None);
+
+    let contract = AccountRlp {
+        code_hash: keccak([0x00]),
+        ..AccountRlp::default()
+    };
+    assert_eq!(
+        transfer_rejection(contract, &[])?,
+        Some(InvalidTxnReason::SenderHasCode)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sender_nonce_is_incremented() -> Result<()> {
+    // The transaction has nonce 0, like its sender.
+    let outcome = ContractTxn::new(&[0x00]).run()?;
+    let sender = Address::from(SENDER);
+    assert_eq!(outcome.accounts[&keccak(sender)].nonce, 1);
+
+    Ok(())
+}
+
+/// The same call as `CONTRACT_TXN`, with nonce 1.
+const SECOND_TXN: [u8; 101] = hex!("f863010a83061a8094095e7baea6a6c7c4c2dfeb977efac326af552d87830186a0801ca0f3359d19793e0bc89bc1379dc362496bc1c0edb1d0624c695855b3ba3d566896a0719996638326fc229937faef2721ebda9072ab0838a2306f47b2272ccf0248a5");
+
+#[test]
+fn test_consecutive_nonces_in_block() -> Result<()> {
+    let first = ContractTxn::new(&[0x00]).run()?;
+    let second = ContractTxn::new(&[0x00])
+        .txn(&SECOND_TXN)
+        .after(&first)
+        .execute()?;
+    assert!(second.receipt.status);
+
+    // Each transaction costs 21000 gas at a gas price of 10, plus its value.
+    let sender = &second.accounts[&keccak(Address::from(SENDER))];
+    assert_eq!(sender.nonce, 2);
+    assert_eq!(
+        sender.balance,
+        U256::from(0x0de0b6b3a7640000u64) - 2 * (21_000 * 10 + 100_000)
+    );
+    assert_eq!(second.receipt.cum_gas_used, U256::from(2 * 21_000));
+
+    Ok(())
+}
+
+#[test]
+fn test_repeated_nonce_in_block_is_rejected() -> Result<()> {
+    let first = ContractTxn::new(&[0x00]).run()?;
+    let err = ContractTxn::new(&[0x00])
+        .after(&first)
+        .execute()
+        .err()
+        .expect("the second transaction should be rejected");
+    assert!(matches!(
+        err.downcast_ref::<GenerationError>(),
+        Some(&GenerationError::InvalidTxn(
+            InvalidTxnReason::NonceMismatch
+        ))
+    ));
+
+    Ok(())
+}
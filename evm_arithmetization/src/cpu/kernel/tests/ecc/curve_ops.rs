@@ -7,7 +7,7 @@ mod bn {
 
     use crate::cpu::kernel::aggregator::KERNEL;
     use crate::cpu::kernel::interpreter::{run_interpreter, Interpreter};
-    use crate::cpu::kernel::tests::u256ify;
+    use crate::cpu::kernel::tests::{run_precompile, u256ify};
     use crate::memory::segments::Segment;
     use crate::witness::memory::MemoryAddress;
 
@@ -171,6 +171,83 @@ mod bn {
         Ok(())
     }
 
+    fn words_to_calldata(words: &[U256]) -> Vec<u8> {
+        words
+            .iter()
+            .flat_map(|word| {
+                let mut bytes = [0; 32];
+                word.to_big_endian(&mut bytes);
+                bytes
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bn_precompiles() -> Result<()> {
+        const BN_ADD: u8 = 0x06;
+        const BN_MUL: u8 = 0x07;
+        const GAS_LIMIT: u64 = 10_000;
+
+        let bn_base = U256::from_str_radix(
+            "0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+            16,
+        )?;
+        let point0 = u256ify([
+            "0x1feee7ec986e198890cb83be8b8ba09ee953b3f149db6d9bfdaa5c308a33e58d",
+            "0x2051cc9a9edd46231604fd88f351e95ec72a285be93e289ac59cb48561efb2c6",
+        ])?;
+        let (x0, y0) = (point0[0], point0[1]);
+        let neg_y0 = bn_base - y0;
+        // point3 = 2 * point0
+        let point3 = u256ify([
+            "0x17da2b7b1a01c8dfdf0f5a6415833c7d755d219aa7e2c4cd0ac83d87d0ca4217",
+            "0xc9ace9de14aac8114541b50c19320eb40f0eeac3621526d9e34dbcf4c3a6c0f",
+        ])?;
+        let identity = words_to_calldata(&[U256::zero(), U256::zero()]);
+
+        // point0 + point0 = point3.
+        let calldata = words_to_calldata(&[x0, y0, x0, y0]);
+        let (success, gas_used, returndata) = run_precompile(BN_ADD, &calldata, GAS_LIMIT)?;
+        assert!(success);
+        assert_eq!(gas_used, 150);
+        assert_eq!(returndata, words_to_calldata(&point3));
+
+        // point0 + (-point0) = identity, encoded as (0, 0).
+        let calldata = words_to_calldata(&[x0, y0, x0, neg_y0]);
+        let (success, gas_used, returndata) = run_precompile(BN_ADD, &calldata, GAS_LIMIT)?;
+        assert!(success);
+        assert_eq!(gas_used, 150);
+        assert_eq!(returndata, identity);
+
+        // 2 * point0 = point3.
+        let calldata = words_to_calldata(&[x0, y0, U256::from(2)]);
+        let (success, gas_used, returndata) = run_precompile(BN_MUL, &calldata, GAS_LIMIT)?;
+        assert!(success);
+        assert_eq!(gas_used, 6_000);
+        assert_eq!(returndata, words_to_calldata(&point3));
+
+        // 0 * point0 = identity, encoded as (0, 0).
+        let calldata = words_to_calldata(&[x0, y0, U256::zero()]);
+        let (success, gas_used, returndata) = run_precompile(BN_MUL, &calldata, GAS_LIMIT)?;
+        assert!(success);
+        assert_eq!(gas_used, 6_000);
+        assert_eq!(returndata, identity);
+
+        // Points off the curve make both precompiles fail, consuming all gas.
+        let calldata = words_to_calldata(&[x0, y0, U256::zero(), U256::from(3)]);
+        let (success, gas_used, returndata) = run_precompile(BN_ADD, &calldata, GAS_LIMIT)?;
+        assert!(!success);
+        assert_eq!(gas_used, GAS_LIMIT);
+        assert!(returndata.is_empty());
+        let calldata = words_to_calldata(&[x0, y0 + 1, U256::from(2)]);
+        let (success, gas_used, returndata) = run_precompile(BN_MUL, &calldata, GAS_LIMIT)?;
+        assert!(!success);
+        assert_eq!(gas_used, GAS_LIMIT);
+        assert!(returndata.is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_glv_verify_data() -> Result<()> {
         let glv = KERNEL.global_labels["bn_glv_decompose"];
@@ -6,7 +6,7 @@ use plonky2::field::goldilocks_field::GoldilocksField as F;
 
 use crate::cpu::kernel::aggregator::KERNEL;
 use crate::cpu::kernel::interpreter::run_interpreter;
-use crate::cpu::kernel::tests::u256ify;
+use crate::cpu::kernel::tests::{run_precompile, u256ify};
 
 fn test_valid_ecrecover(hash: &str, v: &str, r: &str, s: &str, expected: &str) -> Result<()> {
     let ecrecover = KERNEL.global_labels["ecrecover"];
@@ -100,3 +100,54 @@ fn test_ecrecover() -> Result<()> {
 
     Ok(())
 }
+
+const ECREC: u8 = 0x01;
+const GAS_LIMIT: u64 = 10_000;
+
+fn run_ecrec_precompile(hash: &str, v: &str, r: &str, s: &str) -> Result<Vec<u8>> {
+    let calldata = u256ify([hash, v, r, s])?
+        .into_iter()
+        .flat_map(|word| {
+            let mut bytes = [0; 32];
+            word.to_big_endian(&mut bytes);
+            bytes
+        })
+        .collect::<Vec<_>>();
+    let (success, gas_used, returndata) = run_precompile(ECREC, &calldata, GAS_LIMIT)?;
+    // Invalid signatures don't make the call fail.
+    assert!(success);
+    assert_eq!(gas_used, 3_000);
+
+    Ok(returndata)
+}
+
+#[test]
+fn test_ecrec_precompile() -> Result<()> {
+    const HASH: &str = "0x55f77e8909b1f1c9531c4a309bb2d40388e9ed4b87830c8f90363c6b36255fb9";
+    const R: &str = "0xd667c5a20fa899b253924099e10ae92998626718585b8171eb98de468bbebc";
+    const S: &str = "0x58351f48ce34bf134ee611fb5bf255a5733f0029561d345a7d46bfa344b60ac0";
+
+    // The address is returned as a 32-byte word, left-padded with zeros.
+    let mut expected = vec![0; 12];
+    expected.extend(hex::decode("67f3c0Da351384838d7F7641AB0fCAcF853E1844").unwrap());
+    assert_eq!(run_ecrec_precompile(HASH, "0x1b", R, S)?, expected);
+
+    // As in Ethereum, high-s signatures are accepted by the precompile: EIP-2
+    // only restricts transaction signatures. (-s, v') recovers the same
+    // address as (s, v).
+    let secp_scalar =
+        U256::from_str("0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")?;
+    let high_s = format!("{:#x}", secp_scalar - U256::from_str(S)?);
+    assert_eq!(run_ecrec_precompile(HASH, "0x1c", R, &high_s)?, expected);
+
+    // Invalid signatures yield an empty output.
+    assert!(run_ecrec_precompile(HASH, "0x1d", R, S)?.is_empty());
+    assert!(run_ecrec_precompile(HASH, "0x0", R, S)?.is_empty());
+    let v_high_bits = "0x100000000000000000000000000000000000000000000000000000000000001b";
+    assert!(run_ecrec_precompile(HASH, v_high_bits, R, S)?.is_empty());
+    assert!(run_ecrec_precompile(HASH, "0x1b", "0x0", S)?.is_empty());
+    assert!(run_ecrec_precompile(HASH, "0x1b", R, "0x0")?.is_empty());
+    assert!(run_ecrec_precompile(HASH, "0x1b", R, &format!("{secp_scalar:#x}"))?.is_empty());
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use keccak_hash::keccak;
 
 use crate::cpu::kernel::aggregator::{combined_kernel, KERNEL};
 
@@ -11,3 +12,11 @@ fn test_kernel_code_hash_consistency() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_kernel_code_hash() -> Result<()> {
+    assert_eq!(KERNEL.code_hash(), keccak(&KERNEL.code));
+    assert_eq!(KERNEL.code_hash(), combined_kernel().code_hash());
+
+    Ok(())
+}
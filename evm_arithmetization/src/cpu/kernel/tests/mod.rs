@@ -14,6 +14,7 @@ mod kernel_consistency;
 mod log;
 mod mpt;
 mod packing;
+mod prover_input;
 mod receipt;
 mod rlp;
 mod signed_syscalls;
@@ -23,6 +24,14 @@ use std::str::FromStr;
 
 use anyhow::Result;
 use ethereum_types::U256;
+use plonky2::field::goldilocks_field::GoldilocksField as F;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
+use crate::witness::operation::CONTEXT_SCALING_FACTOR;
 
 pub(crate) fn u256ify<'a>(hexes: impl IntoIterator<Item = &'a str>) -> Result<Vec<U256>> {
     Ok(hexes
@@ -30,3 +39,45 @@ pub(crate) fn u256ify<'a>(hexes: impl IntoIterator<Item = &'a str>) -> Result<Ve
         .map(U256::from_str)
         .collect::<Result<Vec<_>, _>>()?)
 }
+
+/// Runs the precompile at `address` in context 1 on the given calldata, and
+/// returns the success flag, the gas used and the data returned to context 0.
+pub(crate) fn run_precompile(
+    address: u8,
+    calldata: &[u8],
+    gas_limit: u64,
+) -> Result<(bool, u64, Vec<u8>)> {
+    let handle_precompiles = KERNEL.global_labels["handle_precompiles"];
+    let terminate_common = KERNEL.global_labels["terminate_common"];
+
+    let new_ctx = U256::one() << CONTEXT_SCALING_FACTOR;
+    let initial_stack = vec![new_ctx, 0xdeadbeefu32.into(), address.into()];
+    let mut interpreter: Interpreter<F> = Interpreter::new(handle_precompiles, initial_stack);
+    interpreter.halt_offsets.push(terminate_common);
+    interpreter.generation_state.set_calldata(1, calldata);
+    interpreter.generation_state.memory.set(
+        MemoryAddress::new(
+            1,
+            Segment::ContextMetadata,
+            ContextMetadata::GasLimit.unscale(),
+        ),
+        gas_limit.into(),
+    );
+
+    interpreter.run()?;
+
+    // stack: success, leftover_gas
+    let success = interpreter.pop().expect("Stack should not be empty");
+    let leftover_gas = interpreter.pop().expect("Stack should not be empty");
+    let returndata_size =
+        interpreter.get_context_metadata_field(0, ContextMetadata::ReturndataSize);
+    let returndata = interpreter.get_memory_segment_bytes(Segment::Returndata)
+        [..returndata_size.as_usize()]
+        .to_vec();
+
+    Ok((
+        !success.is_zero(),
+        gas_limit - leftover_gas.as_u64(),
+        returndata,
+    ))
+}
@@ -5,6 +5,7 @@ use plonky2::field::goldilocks_field::GoldilocksField as F;
 use crate::cpu::kernel::aggregator::KERNEL;
 use crate::cpu::kernel::interpreter::Interpreter;
 use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
 
 #[test]
 fn test_mstore_unpacking() -> Result<()> {
@@ -27,3 +28,29 @@ fn test_mstore_unpacking() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_read_u256_after_mstore() -> Result<()> {
+    let mstore_unpacking = KERNEL.global_labels["mstore_unpacking"];
+
+    let bytes: [u8; 32] = core::array::from_fn(|i| 0xe0 + i as u8);
+    let value = U256::from_big_endian(&bytes);
+    let offset = 0x45;
+    let retdest = 0xDEADBEEFu32.into();
+    let addr = (Segment::MainMemory as usize + offset).into();
+    let initial_stack = vec![retdest, 32.into(), value, addr];
+
+    let mut interpreter: Interpreter<F> = Interpreter::new(mstore_unpacking, initial_stack);
+
+    interpreter.run()?;
+    let state = &interpreter.generation_state;
+    let address = MemoryAddress::new(0, Segment::MainMemory, offset);
+    assert_eq!(state.read_u256(address), value);
+    assert_eq!(state.read_bytes(address, 32), bytes);
+
+    // Reading across the end of the stored word pads it with zeros.
+    let shifted = MemoryAddress::new(0, Segment::MainMemory, offset + 16);
+    assert_eq!(state.read_u256(shifted), value << 128);
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use anyhow::Result;
+use ethereum_types::{BigEndianHash, H256, U256, U512};
+use keccak_hash::keccak;
+use plonky2::field::goldilocks_field::GoldilocksField as F;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::generation::prover_input::{
+    div_mod_hint, ProverInputFn, ProverInputRequest, ProverInputSource,
+};
+use crate::generation::CodeProvider;
+use crate::memory::segments::Segment;
+use crate::witness::errors::{ProgramError, ProverInputError};
+use crate::witness::memory::MemoryAddress;
+use crate::witness::operation::CONTEXT_SCALING_FACTOR;
+use crate::GenerationInputs;
+
+/// A `ProverInputSource` recording the requested prover inputs, and
+/// overriding the receipt trie pointer.
+#[derive(Debug, Default)]
+struct RecordingSource {
+    requests: RefCell<Vec<(ProverInputFn, usize)>>,
+}
+
+const RECEIPT_TRIE_PTR: usize = 0x1234;
+
+impl ProverInputSource for RecordingSource {
+    fn prover_input(&self, request: &ProverInputRequest) -> Option<U256> {
+        self.requests
+            .borrow_mut()
+            .push((request.input_fn.clone(), request.context));
+        (*request.input_fn == trie_ptr("receipt")).then(|| RECEIPT_TRIE_PTR.into())
+    }
+}
+
+fn trie_ptr(trie: &str) -> ProverInputFn {
+    vec!["trie_ptr".to_string(), trie.to_string()].into()
+}
+
+#[test]
+fn test_prover_input_source() -> Result<()> {
+    let source = Rc::new(RecordingSource::default());
+
+    let initial_offset = KERNEL.global_labels["main"];
+    let mut interpreter: Interpreter<F> = Interpreter::new_with_generation_inputs(
+        initial_offset,
+        vec![],
+        GenerationInputs::default(),
+    );
+    interpreter
+        .generation_state
+        .set_prover_input_source(source.clone());
+    interpreter
+        .halt_offsets
+        .push(KERNEL.global_labels["hash_initial_tries"]);
+    interpreter.set_is_kernel(true);
+    interpreter.run()?;
+
+    // The trie pointers are requested in order, in the kernel context.
+    assert_eq!(
+        *source.requests.borrow(),
+        vec![
+            (trie_ptr("state"), 0),
+            (trie_ptr("txn"), 0),
+            (trie_ptr("receipt"), 0)
+        ]
+    );
+
+    // Inputs not provided by the source are computed from the in-memory tries.
+    let state_trie_ptr = interpreter.generation_state.trie_root_ptrs.state_root_ptr;
+    assert_eq!(
+        interpreter.get_global_metadata_field(GlobalMetadata::StateTrieRoot),
+        state_trie_ptr.into()
+    );
+    assert_eq!(
+        interpreter.get_global_metadata_field(GlobalMetadata::ReceiptTrieRoot),
+        RECEIPT_TRIE_PTR.into()
+    );
+
+    Ok(())
+}
+
+/// A `CodeProvider` recording the hashes of the codes it is asked for.
+#[derive(Debug, Default)]
+struct RecordingCodeProvider {
+    codes: HashMap<H256, Vec<u8>>,
+    fetched: RefCell<Vec<H256>>,
+}
+
+impl CodeProvider for RecordingCodeProvider {
+    fn get(&self, hash: H256) -> Option<Vec<u8>> {
+        self.fetched.borrow_mut().push(hash);
+        self.codes.get(&hash).cloned()
+    }
+}
+
+#[test]
+fn test_code_provider() -> Result<()> {
+    let in_memory_code = vec![0x60, 0x01, 0x00];
+    let provided_code = vec![0x60, 0x02, 0x60, 0x03, 0x01];
+    let unused_code = vec![0x00];
+    let unknown_hash = keccak([0xfe]);
+
+    let mut inputs = GenerationInputs::default();
+    inputs
+        .contract_code
+        .insert(keccak(&in_memory_code), in_memory_code.clone());
+    let provider = Rc::new(RecordingCodeProvider {
+        codes: HashMap::from([
+            (keccak(&provided_code), provided_code.clone()),
+            (keccak(&unused_code), unused_code),
+        ]),
+        ..Default::default()
+    });
+
+    let mut interpreter: Interpreter<F> =
+        Interpreter::new_with_generation_inputs(0, vec![], inputs);
+    interpreter
+        .generation_state
+        .set_code_provider(provider.clone());
+
+    let account_code: ProverInputFn = vec!["account_code".to_string()].into();
+    let context = 1;
+    // Loads the code with the given hash in `context`, as the kernel does.
+    let mut load_code = |codehash: H256| -> Result<Vec<u8>, ProgramError> {
+        interpreter.generation_state.registers.stack_len = 0;
+        interpreter.push(U256::from(context) << CONTEXT_SCALING_FACTOR)?;
+        interpreter.push(codehash.into_uint())?;
+        let state = &mut interpreter.generation_state;
+        let len = state.prover_input(&account_code)?;
+        Ok((0..len.as_usize())
+            .map(|i| {
+                let address = MemoryAddress::new(context, Segment::Code, i);
+                state.memory.get_with_init(address).as_u32() as u8
+            })
+            .collect())
+    };
+
+    // Codes in `contract_code` are never fetched, and fetched codes are kept.
+    assert_eq!(load_code(keccak(&in_memory_code)).unwrap(), in_memory_code);
+    assert_eq!(load_code(keccak(&provided_code)).unwrap(), provided_code);
+    assert_eq!(load_code(keccak(&provided_code)).unwrap(), provided_code);
+    assert!(matches!(
+        load_code(unknown_hash),
+        Err(ProgramError::ProverInputError(
+            ProverInputError::CodeHashNotFound
+        ))
+    ));
+
+    assert_eq!(
+        *provider.fetched.borrow(),
+        vec![keccak(&provided_code), unknown_hash]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_div_mod_hint() {
+    let check = |a: U256, b: U256| {
+        let (q, r) = div_mod_hint(a, b);
+        assert_eq!(q.full_mul(b) + U512::from(r), U512::from(a));
+        assert!(r < b);
+        (q, r)
+    };
+
+    // Division by zero yields a zero quotient and remainder.
+    assert_eq!(div_mod_hint(U256::MAX, U256::zero()), (0.into(), 0.into()));
+    assert_eq!(
+        div_mod_hint(U256::zero(), U256::zero()),
+        (0.into(), 0.into())
+    );
+
+    // Exact division.
+    assert_eq!(check(1000.into(), 8.into()), (125.into(), 0.into()));
+    assert_eq!(check(U256::MAX, U256::MAX), (1.into(), 0.into()));
+    assert_eq!(check(U256::MAX, 3.into()), (U256::MAX / 3, 0.into()));
+
+    // Division with remainder.
+    assert_eq!(check(1001.into(), 8.into()), (125.into(), 1.into()));
+    assert_eq!(check(7.into(), 10.into()), (0.into(), 7.into()));
+    assert_eq!(
+        check(U256::MAX, U256::one() << 255),
+        (1.into(), U256::MAX >> 1)
+    );
+}
@@ -6,7 +6,7 @@ pub(crate) mod control_flow;
 pub mod cpu_stark;
 pub(crate) mod decode;
 mod dup_swap;
-mod gas;
+pub(crate) mod gas;
 mod halt;
 mod jumps;
 pub mod kernel;
@@ -1193,6 +1193,12 @@ where
     /// transaction, aggregation proofs can cover an arbitrary range, up to
     /// an entire block with all its transactions.
     ///
+    /// The state shared by two contiguous proofs is made of their public
+    /// values only: the trie roots, and the transaction number and gas used
+    /// of their `ExtraBlockData`. No kernel memory is carried over, since
+    /// each transaction proof starts from a fresh kernel memory initialized
+    /// from its input tries.
+    ///
     /// # Arguments
     ///
     /// - `lhs_is_agg`: a boolean indicating whether the left child proof is an
@@ -1,12 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Debug;
+use std::io::{Read, Write};
+use std::rc::Rc;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use ethereum_types::{Address, BigEndianHash, H256, U256};
+use keccak_hash::keccak;
 use log::log_enabled;
+use mpt_trie::nibbles::Nibbles;
 use mpt_trie::partial_trie::{HashedPartialTrie, PartialTrie};
+use mpt_trie::trie_ops::ValOrHash;
 use plonky2::field::extension::Extendable;
 use plonky2::field::polynomial::PolynomialValues;
-use plonky2::field::types::Field;
+use plonky2::field::types::{Field, PrimeField64};
 use plonky2::hash::hash_types::RichField;
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
@@ -20,21 +26,31 @@ use GlobalMetadata::{
 use crate::all_stark::{AllStark, NUM_TABLES};
 use crate::cpu::columns::CpuColumnsView;
 use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::assembler::Kernel;
 use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::constants::{MAX_MAX_MEMORY_BYTES, MIN_MAX_MEMORY_BYTES};
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::generation::mpt::{AccountRlp, TrieRootPtrs};
+use crate::generation::prover_input::ReplayedProverInputs;
 use crate::generation::state::GenerationState;
 use crate::generation::trie_extractor::{get_receipt_trie, get_state_trie, get_txn_trie};
 use crate::memory::segments::Segment;
 use crate::proof::{BlockHashes, BlockMetadata, ExtraBlockData, PublicValues, TrieRoots};
 use crate::util::{h2u, u256_to_usize};
-use crate::witness::memory::{MemoryAddress, MemoryChannel};
+use crate::witness::memory::{MemoryAddress, MemoryChannel, MemoryState};
+use crate::witness::traces::Traces;
 
 pub mod mpt;
 pub(crate) mod prover_input;
 pub(crate) mod rlp;
 pub(crate) mod state;
-mod trie_extractor;
+pub(crate) mod trie_extractor;
 
+pub use self::prover_input::{
+    div_mod_hint, CodeProvider, ProverInputFn, ProverInputRequest, ProverInputSource,
+};
 use self::state::State;
+pub use self::state::{StorageAccess, TraceStep, TRACE_STACK_ITEMS};
 use crate::witness::util::mem_write_log;
 
 /// Inputs needed for trace generation.
@@ -68,6 +84,8 @@ pub struct GenerationInputs {
 
     /// Mapping between smart contract code hashes and the contract byte code.
     /// All account smart contracts that are invoked will have an entry present.
+    /// It is serialized in hash order, regardless of the insertion order.
+    #[serde(serialize_with = "serialize_sorted")]
     pub contract_code: HashMap<H256, Vec<u8>>,
 
     /// Information contained in the block header.
@@ -78,6 +96,100 @@ pub struct GenerationInputs {
     pub block_hashes: BlockHashes,
 }
 
+/// Serializes a `HashMap` in key order, so that the serialization of
+/// `GenerationInputs` is deterministic.
+fn serialize_sorted<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    K: Ord + Serialize,
+    V: Serialize,
+{
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+/// Magic bytes prefixing the binary encoding of `GenerationInputs`.
+const GENERATION_INPUTS_MAGIC: [u8; 4] = *b"ZKGI";
+
+/// Schema version of the binary encoding of `GenerationInputs`. It must be
+/// bumped whenever `GenerationInputs` or its encoding change in a
+/// backward-incompatible way, so that provers running another version reject
+/// the inputs instead of misinterpreting them.
+pub const INPUTS_SCHEMA_VERSION: u32 = 2;
+
+/// Compression flags of the binary encoding of `GenerationInputs`.
+const UNCOMPRESSED: u8 = 0;
+const ZSTD_COMPRESSED: u8 = 1;
+
+impl GenerationInputs {
+    /// Serializes these inputs with bincode, compressing them with zstd if the
+    /// `compression` feature is enabled.
+    ///
+    /// The encoding starts with a 9-byte header: the magic bytes `ZKGI`, the
+    /// schema version as a little-endian `u32`, and a compression flag (0 for
+    /// none, 1 for zstd), followed by the (possibly compressed) bincode
+    /// payload.
+    pub fn to_bincode_bytes(&self) -> Vec<u8> {
+        let payload = bincode::serialize(self).expect("GenerationInputs can always be serialized");
+
+        #[cfg(feature = "compression")]
+        let (flag, payload) = (
+            ZSTD_COMPRESSED,
+            zstd::encode_all(payload.as_slice(), 0).expect("Compression into memory cannot fail"),
+        );
+        #[cfg(not(feature = "compression"))]
+        let flag = UNCOMPRESSED;
+
+        let mut bytes = Vec::with_capacity(GENERATION_INPUTS_MAGIC.len() + 5 + payload.len());
+        bytes.extend_from_slice(&GENERATION_INPUTS_MAGIC);
+        bytes.extend_from_slice(&INPUTS_SCHEMA_VERSION.to_le_bytes());
+        bytes.push(flag);
+        bytes.extend_from_slice(&payload);
+        bytes
+    }
+
+    /// Deserializes inputs encoded with `to_bincode_bytes`.
+    ///
+    /// Decoding a zstd-compressed payload requires the `compression` feature.
+    /// Inputs encoded with another schema version are rejected with a
+    /// `GenerationError::VersionMismatch`.
+    pub fn from_bincode_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let (magic, bytes) = bytes
+            .split_first_chunk::<4>()
+            .ok_or_else(|| anyhow!("Encoded GenerationInputs are too short."))?;
+        if *magic != GENERATION_INPUTS_MAGIC {
+            return Err(anyhow!("Invalid magic bytes for GenerationInputs."));
+        }
+        let (version, bytes) = bytes
+            .split_first_chunk::<4>()
+            .ok_or_else(|| anyhow!("Encoded GenerationInputs are too short."))?;
+        let version = u32::from_le_bytes(*version);
+        if version != INPUTS_SCHEMA_VERSION {
+            return Err(GenerationError::VersionMismatch {
+                expected: INPUTS_SCHEMA_VERSION,
+                found: version,
+            }
+            .into());
+        }
+        let [flag, payload @ ..] = bytes else {
+            return Err(anyhow!("Encoded GenerationInputs are too short."));
+        };
+
+        match *flag {
+            UNCOMPRESSED => Ok(bincode::deserialize(payload)?),
+            #[cfg(feature = "compression")]
+            ZSTD_COMPRESSED => Ok(bincode::deserialize(&zstd::decode_all(payload)?)?),
+            #[cfg(not(feature = "compression"))]
+            ZSTD_COMPRESSED => Err(anyhow!(
+                "Decoding compressed GenerationInputs requires the `compression` feature."
+            )),
+            _ => Err(anyhow!(
+                "Unknown GenerationInputs compression flag {}.",
+                flag
+            )),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct TrieInputs {
     /// A partial version of the state trie prior to these transactions. It
@@ -101,9 +213,307 @@ pub struct TrieInputs {
     pub storage_tries: Vec<(H256, HashedPartialTrie)>,
 }
 
+impl TrieInputs {
+    /// Checks that each storage trie hashes to the `storage_root` of its
+    /// account in the state trie.
+    ///
+    /// Storage tries of accounts absent from the state trie, or hidden behind a
+    /// hash node, cannot be checked and are accepted.
+    pub fn validate(&self) -> Result<(), GenerationError> {
+        for (state_key, storage_trie) in &self.storage_tries {
+            let Some(account_rlp) = self.state_trie.get(Nibbles::from_h256_be(*state_key)) else {
+                continue;
+            };
+            let Ok(account) = ::rlp::decode::<AccountRlp>(account_rlp) else {
+                continue;
+            };
+
+            let storage_trie_root = storage_trie.hash();
+            if storage_trie_root != account.storage_root {
+                return Err(GenerationError::StorageRootMismatch {
+                    state_key: *state_key,
+                    account_storage_root: account.storage_root,
+                    storage_trie_root,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Expected sizes of the main per-context memory segments.
+///
+/// These are only allocation hints: they are used to pre-allocate the memory
+/// backing store of the kernel context and of the top-level context of the
+/// transaction, to avoid repeatedly growing it during execution. The contexts
+/// of sub-calls are not pre-allocated. The hints have no effect on the
+/// generated traces.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct SegmentCapacities {
+    /// Expected number of elements in the stack segment.
+    pub stack: usize,
+    /// Expected number of bytes in the main memory segment.
+    pub main_memory: usize,
+    /// Expected number of bytes in the calldata segment.
+    pub calldata: usize,
+    /// Expected number of bytes in the returndata segment.
+    pub returndata: usize,
+}
+
+impl SegmentCapacities {
+    /// Returns the capacity hint for the given segment, or 0 if there is none.
+    pub(crate) const fn get(&self, segment: Segment) -> usize {
+        match segment {
+            Segment::Stack => self.stack,
+            Segment::MainMemory => self.main_memory,
+            Segment::Calldata => self.calldata,
+            Segment::Returndata => self.returndata,
+            _ => 0,
+        }
+    }
+}
+
+/// Errors that can be detected on the generation inputs before running the
+/// CPU.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerationError {
+    /// The code of a contract invoked by the transaction, identified by its
+    /// code hash, is missing from `contract_code`.
+    MissingContractCode(H256),
+    /// The state trie root computed by the kernel differs from the expected
+    /// one.
+    RootMismatch {
+        computed: H256,
+        expected: H256,
+        /// The first account, in key order, whose state differs between the
+        /// two tries, if it could be identified.
+        first_differing_account: Option<Address>,
+    },
+    /// A storage trie does not hash to the `storage_root` of its account in
+    /// the state trie.
+    StorageRootMismatch {
+        /// The key of the account in the state trie, i.e. the hash of its
+        /// address.
+        state_key: H256,
+        account_storage_root: H256,
+        storage_trie_root: H256,
+    },
+    /// The kernel rejected the transaction, which is therefore not part of a
+    /// valid block.
+    InvalidTxn(InvalidTxnReason),
+    /// Encoded `GenerationInputs` have a schema version other than
+    /// `INPUTS_SCHEMA_VERSION`.
+    VersionMismatch { expected: u32, found: u32 },
+    /// A transaction used more CPU cycles than the given limit.
+    TxnCycleLimitExceeded { limit: usize },
+    /// An initial trie, as loaded in the trie data hashed by the kernel, has a
+    /// different root than the corresponding trie in `TrieInputs`, which is the
+    /// initial root stored in the kernel metadata. This happens when the input
+    /// trie has leaves which the kernel encodes differently, e.g. accounts
+    /// with trailing RLP items.
+    InitialRootMismatch {
+        /// The trie whose roots differ: "state", "transactions" or "receipts".
+        trie: &'static str,
+        loaded: H256,
+        inputs: H256,
+    },
+    /// The kernel to run doesn't define the given label, which is required to
+    /// run it.
+    MissingKernelLabel(&'static str),
+    /// The hash of the kernel differs from the one expected by the caller,
+    /// e.g. the one of the verifier's kernel.
+    KernelMismatch { expected: H256, computed: H256 },
+    /// The run ended before the kernel requested all the replayed prover
+    /// inputs, which were thus recorded on different inputs or by a different
+    /// kernel.
+    UnusedProverInputs { remaining: usize },
+}
+
+/// The validity check of a transaction that failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidTxnReason {
+    /// The nonce of the transaction differs from the nonce of its sender.
+    NonceMismatch,
+    /// The balance of the sender doesn't cover the gas limit at the
+    /// transaction's gas price, plus the transferred value.
+    InsufficientBalance,
+    /// The sender is a contract, i.e. its code is not empty, which EIP-3607
+    /// forbids.
+    SenderHasCode,
+}
+
+impl core::fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GenerationError::MissingContractCode(code_hash) => {
+                write!(f, "missing contract code for code hash {:?}", code_hash)
+            }
+            GenerationError::RootMismatch {
+                computed,
+                expected,
+                first_differing_account,
+            } => {
+                write!(
+                    f,
+                    "state trie root mismatch: computed {:?}, expected {:?}",
+                    computed, expected
+                )?;
+                if let Some(address) = first_differing_account {
+                    write!(f, ", first differing account {:?}", address)?;
+                }
+                Ok(())
+            }
+            GenerationError::StorageRootMismatch {
+                state_key,
+                account_storage_root,
+                storage_trie_root,
+            } => write!(
+                f,
+                "storage trie root mismatch for account with state key {:?}: \
+                 the account has storage root {:?}, the storage trie hashes to {:?}",
+                state_key, account_storage_root, storage_trie_root
+            ),
+            GenerationError::InvalidTxn(reason) => {
+                write!(f, "invalid transaction: {:?}", reason)
+            }
+            GenerationError::VersionMismatch { expected, found } => write!(
+                f,
+                "GenerationInputs schema version mismatch: expected {}, found {}",
+                expected, found
+            ),
+            GenerationError::TxnCycleLimitExceeded { limit } => {
+                write!(f, "transaction used more than {} CPU cycles", limit)
+            }
+            GenerationError::InitialRootMismatch {
+                trie,
+                loaded,
+                inputs,
+            } => write!(
+                f,
+                "initial {} trie root mismatch: loaded trie has {:?}, inputs have {:?}",
+                trie, loaded, inputs
+            ),
+            GenerationError::MissingKernelLabel(label) => {
+                write!(f, "the kernel doesn't define the label {}", label)
+            }
+            GenerationError::KernelMismatch { expected, computed } => write!(
+                f,
+                "kernel code hash mismatch: expected {:?}, computed {:?}",
+                expected, computed
+            ),
+            GenerationError::UnusedProverInputs { remaining } => {
+                write!(f, "{} replayed prover inputs were not used", remaining)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {}
+
+/// Checks that the initial tries, as loaded in the trie data which the kernel
+/// hashes, have the roots of the tries in `tries`, which the kernel asserts
+/// against. Running this before the kernel names the differing trie, instead
+/// of failing on the kernel's assertion.
+fn check_trie_roots_before(
+    memory: &MemoryState,
+    trie_root_ptrs: &TrieRootPtrs,
+    tries: &TrieInputs,
+) -> anyhow::Result<()> {
+    let read_error = |trie, err| anyhow!("Failed to read the initial {} trie: {:?}", trie, err);
+    let loaded_state_trie: HashedPartialTrie =
+        get_state_trie(memory, trie_root_ptrs.state_root_ptr)
+            .map_err(|err| read_error("state", err))?;
+    let loaded_txn_trie: HashedPartialTrie = get_txn_trie(memory, trie_root_ptrs.txn_root_ptr)
+        .map_err(|err| read_error("transactions", err))?;
+    let loaded_receipt_trie: HashedPartialTrie =
+        get_receipt_trie(memory, trie_root_ptrs.receipt_root_ptr)
+            .map_err(|err| read_error("receipts", err))?;
+
+    for (trie, loaded, inputs) in [
+        ("state", loaded_state_trie.hash(), tries.state_trie.hash()),
+        (
+            "transactions",
+            loaded_txn_trie.hash(),
+            tries.transactions_trie.hash(),
+        ),
+        (
+            "receipts",
+            loaded_receipt_trie.hash(),
+            tries.receipts_trie.hash(),
+        ),
+    ] {
+        if loaded != inputs {
+            return Err(GenerationError::InitialRootMismatch {
+                trie,
+                loaded,
+                inputs,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the `to` field of an encoded transaction, or `None` if the
+/// transaction is a contract creation or cannot be decoded. Malformed
+/// transactions are left to the kernel to reject.
+fn txn_destination(signed_txn: &[u8]) -> Option<Address> {
+    let (payload, to_index) = match signed_txn.first()? {
+        // Legacy transaction: `[nonce, gas_price, gas, to, ...]`.
+        0xc0.. => (signed_txn, 3),
+        // EIP-2930 transaction: `[chain_id, nonce, gas_price, gas, to, ...]`.
+        0x01 => (&signed_txn[1..], 4),
+        // EIP-1559 transaction: `[chain_id, nonce, max_priority_fee_per_gas,
+        // max_fee_per_gas, gas, to, ...]`.
+        0x02 => (&signed_txn[1..], 5),
+        _ => return None,
+    };
+
+    let to = ::rlp::Rlp::new(payload).at(to_index).ok()?;
+    let to = to.data().ok()?;
+    (to.len() == 20).then(|| Address::from_slice(to))
+}
+
+/// Checks that the code of the top-level callee of the transaction, if any, is
+/// provided in `contract_code` or by `code_provider`.
+///
+/// Contracts reached through internal calls cannot be determined statically,
+/// and are only checked during execution.
+fn check_top_level_contract_code(
+    inputs: &GenerationInputs,
+    code_provider: Option<&dyn CodeProvider>,
+) -> Result<(), GenerationError> {
+    let Some(to) = inputs.signed_txn.as_deref().and_then(txn_destination) else {
+        return Ok(());
+    };
+
+    let key = Nibbles::from_h256_be(keccak(to));
+    // Accounts absent from the state trie, or hidden behind a hash node, have
+    // no code we could check.
+    let Some(account_rlp) = inputs.tries.state_trie.get(key) else {
+        return Ok(());
+    };
+    let Ok(account) = ::rlp::decode::<AccountRlp>(account_rlp) else {
+        return Ok(());
+    };
+
+    let code_hash = account.code_hash;
+    if code_hash != keccak([])
+        && !inputs.contract_code.contains_key(&code_hash)
+        && !code_provider.is_some_and(|provider| provider.get(code_hash).is_some())
+    {
+        return Err(GenerationError::MissingContractCode(code_hash));
+    }
+
+    Ok(())
+}
+
 fn apply_metadata_and_tries_memops<F: RichField + Extendable<D>, const D: usize>(
     state: &mut GenerationState<F>,
     inputs: &GenerationInputs,
+    kernel: &Kernel,
 ) {
     let metadata = &inputs.block_metadata;
     let tries = &inputs.tries;
@@ -128,6 +538,10 @@ fn apply_metadata_and_tries_memops<F: RichField + Extendable<D>, const D: usize>
             h2u(inputs.block_hashes.cur_hash),
         ),
         (GlobalMetadata::BlockGasUsed, metadata.block_gas_used),
+        (
+            GlobalMetadata::BlockHardfork,
+            metadata.block_hardfork.id().into(),
+        ),
         (GlobalMetadata::BlockGasUsedBefore, inputs.gas_used_before),
         (GlobalMetadata::BlockGasUsedAfter, inputs.gas_used_after),
         (GlobalMetadata::TxnNumberBefore, inputs.txn_number_before),
@@ -159,8 +573,8 @@ fn apply_metadata_and_tries_memops<F: RichField + Extendable<D>, const D: usize>
             GlobalMetadata::ReceiptTrieRootDigestAfter,
             h2u(trie_roots_after.receipts_root),
         ),
-        (GlobalMetadata::KernelHash, h2u(KERNEL.code_hash)),
-        (GlobalMetadata::KernelLen, KERNEL.code.len().into()),
+        (GlobalMetadata::KernelHash, h2u(kernel.code_hash)),
+        (GlobalMetadata::KernelLen, kernel.code.len().into()),
     ];
 
     let channel = MemoryChannel::GeneralPurpose(0);
@@ -222,11 +636,386 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
     config: &StarkConfig,
     timing: &mut TimingTree,
 ) -> anyhow::Result<([Vec<PolynomialValues<F>>; NUM_TABLES], PublicValues)> {
+    let (traces, public_values, _) = simulate_txn(inputs, timing, GenerationOptions::default())?;
+    let tables = timed!(
+        timing,
+        "convert trace data to tables",
+        traces.into_tables(all_stark, config, timing)
+    );
+    Ok((tables, public_values))
+}
+
+/// The options of `generate_traces_with_options`. The default options generate
+/// the same traces as `generate_traces`.
+#[derive(Default)]
+pub struct GenerationOptions<'a> {
+    /// The kernel to run instead of `KERNEL`, e.g. to test a patched kernel.
+    /// The CPU starts at the kernel's `main` label and stops at its `halt`
+    /// label.
+    pub kernel: Option<&'a Kernel>,
+    /// If set, generation first checks that the code hash of the kernel to run
+    /// is this one, and fails with `GenerationError::KernelMismatch`
+    /// otherwise. This lets a prover make sure that its proofs are generated
+    /// with the kernel its verifier expects.
+    pub expected_kernel_hash: Option<H256>,
+    /// The capacities to pre-allocate the memory segments of the kernel and
+    /// top-level transaction contexts with.
+    pub segment_capacities: SegmentCapacities,
+    /// If set, generation fails with `GenerationError::TxnCycleLimitExceeded`
+    /// as soon as a transaction uses more CPU cycles.
+    pub max_txn_cycles: Option<usize>,
+    /// The bound past which memory offsets fault, instead of the default of
+    /// 50M bytes. It can only be raised, up to `u32::MAX / 2`, since the
+    /// default is already out of reach of any realistic gas limit.
+    pub max_memory_bytes: Option<u64>,
+    /// A provider of the codes missing from `inputs.contract_code`, which are
+    /// fetched from it as the kernel loads them.
+    pub code_provider: Option<Rc<dyn CodeProvider>>,
+    /// A source queried for every prover input before computing it from the
+    /// in-memory inputs, e.g. to fetch trie nodes lazily. It can't be used
+    /// along with `replayed_prover_inputs`.
+    pub prover_input_source: Option<Rc<dyn ProverInputSource>>,
+    /// The form in which the tables are output.
+    pub table_output: TableOutput<'a>,
+    /// Whether to record the prover inputs returned to the kernel into
+    /// `GenerationStats::prover_inputs`. Along with the serialized
+    /// `GenerationInputs`, they reproduce a run exactly.
+    pub record_prover_inputs: bool,
+    /// If set, the kernel is given these prover inputs, as recorded by a
+    /// previous run on the same `GenerationInputs`, instead of computing them.
+    /// Generation fails with `GenerationError::UnusedProverInputs` if some of
+    /// them are left over at the end of the run.
+    pub replayed_prover_inputs: Option<Vec<U256>>,
+    /// Whether to record the storage reads and writes of the transaction into
+    /// `GenerationStats::storage_accesses`.
+    pub record_storage_accesses: bool,
+    /// Whether to record the user instructions executed by the CPU into
+    /// `GenerationStats::execution_trace`. Recording has no cost when disabled.
+    pub record_execution_trace: bool,
+    /// Whether to dump the final state trie into
+    /// `GenerationStats::final_accounts`.
+    pub record_final_accounts: bool,
+    /// The accounts whose final storage tries to return in
+    /// `GenerationStats::final_storage_tries`.
+    pub final_storage_tries: Vec<Address>,
+}
+
+/// The form in which `generate_traces_with_options` outputs the tables.
+#[derive(Default)]
+pub enum TableOutput<'a> {
+    /// The columns of each table are returned, as needed for proving.
+    #[default]
+    Columns,
+    /// The rows of each table are returned, without converting them to
+    /// `PolynomialValues`. This is meant for tooling which inspects or exports
+    /// the witness.
+    Rows,
+    /// Each table is written to the given writer with `write_table` as soon as
+    /// it is generated, in the order of `Table::all`, instead of holding all of
+    /// them in memory. The tables can be read back one at a time with
+    /// `read_table`.
+    Writer(&'a mut dyn Write),
+}
+
+/// The tables generated by `generate_traces_with_options`, in the form
+/// requested by its `TableOutput`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum GeneratedTables<F: Field> {
+    /// The columns of each table, for `TableOutput::Columns`.
+    Columns([Vec<PolynomialValues<F>>; NUM_TABLES]),
+    /// The rows of each table, for `TableOutput::Rows`.
+    Rows([Vec<Vec<F>>; NUM_TABLES]),
+    /// The tables were written, for `TableOutput::Writer`.
+    Written,
+}
+
+/// Statistics of a run of `generate_traces_with_options`.
+#[derive(Clone, Debug, Default)]
+pub struct GenerationStats {
+    /// The number of CPU cycles spent processing each transaction, from the
+    /// kernel's `route_txn` to its `txn_after`. Each `GenerationInputs` holds
+    /// at most one transaction, so the cycles of a block are obtained by
+    /// concatenating those of its inputs.
+    pub txn_cycles: Vec<usize>,
+    /// The number of distinct memory addresses holding a value in each segment,
+    /// across all contexts, once all transactions have been processed. This
+    /// shows which segments, e.g. `Segment::MainMemory`, dominate the memory
+    /// table. Segments without any value are absent.
+    pub memory_usage: HashMap<Segment, usize>,
+    /// The hashes of all the codes the kernel loaded from
+    /// `GenerationInputs::contract_code`. The other entries of `contract_code`
+    /// can be dropped from the inputs.
+    pub accessed_code_hashes: HashSet<H256>,
+    /// The prover inputs returned to the kernel, if
+    /// `GenerationOptions::record_prover_inputs` is set. They can be replayed
+    /// with `GenerationOptions::replayed_prover_inputs`.
+    pub prover_inputs: Option<Vec<U256>>,
+    /// The storage accesses of the transaction, in execution order, including
+    /// those of internal calls, if `GenerationOptions::record_storage_accesses`
+    /// is set. Accesses made by calls which are later reverted are kept.
+    pub storage_accesses: Option<Vec<StorageAccess>>,
+    /// The user instructions executed by the CPU, if
+    /// `GenerationOptions::record_execution_trace` is set.
+    pub execution_trace: Option<Vec<TraceStep>>,
+    /// The accounts of the final state trie, with their state keys, in key
+    /// order, if `GenerationOptions::record_final_accounts` is set. This can be
+    /// compared with the state dump of another client. Accounts behind hash
+    /// nodes are unknown, and omitted.
+    pub final_accounts: Option<Vec<(H256, AccountRlp)>>,
+    /// The final storage tries of the accounts of
+    /// `GenerationOptions::final_storage_tries`. Accounts which aren't in the
+    /// final state trie are absent.
+    pub final_storage_tries: HashMap<Address, HashedPartialTrie>,
+}
+
+/// Same as `generate_traces`, but configured by `options`, and also returning
+/// statistics of the run.
+pub fn generate_traces_with_options<F: RichField + Extendable<D>, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    inputs: GenerationInputs,
+    config: &StarkConfig,
+    timing: &mut TimingTree,
+    mut options: GenerationOptions,
+) -> anyhow::Result<(GeneratedTables<F>, PublicValues, GenerationStats)> {
+    let table_output = std::mem::take(&mut options.table_output);
+    let (traces, public_values, stats) = simulate_txn(inputs, timing, options)?;
+    let tables = match table_output {
+        TableOutput::Columns => GeneratedTables::Columns(timed!(
+            timing,
+            "convert trace data to tables",
+            traces.into_tables(all_stark, config, timing)
+        )),
+        TableOutput::Rows => GeneratedTables::Rows(timed!(
+            timing,
+            "convert trace data to raw tables",
+            traces.into_raw_tables(all_stark, config, timing)
+        )),
+        TableOutput::Writer(mut writer) => {
+            timed!(
+                timing,
+                "convert trace data to tables and write them",
+                traces.for_each_table(all_stark, config, timing, |_, table| {
+                    write_table(&table, &mut writer)
+                })
+            )?;
+            GeneratedTables::Written
+        }
+    };
+    Ok((tables, public_values, stats))
+}
+
+/// Writes the columns of a table to `writer`: the number of columns, then for
+/// each column its length followed by its values in canonical form, all as
+/// little-endian `u64`s.
+pub fn write_table<F: PrimeField64>(
+    table: &[PolynomialValues<F>],
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    writer.write_all(&(table.len() as u64).to_le_bytes())?;
+    for column in table {
+        writer.write_all(&(column.values.len() as u64).to_le_bytes())?;
+        for value in &column.values {
+            writer.write_all(&value.to_canonical_u64().to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a table written by `write_table` from `reader`.
+pub fn read_table<F: PrimeField64>(
+    reader: &mut impl Read,
+) -> std::io::Result<Vec<PolynomialValues<F>>> {
+    let mut read_u64 = || -> std::io::Result<u64> {
+        let mut bytes = [0; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    };
+
+    let num_columns = read_u64()?;
+    (0..num_columns)
+        .map(|_| {
+            let len = read_u64()?;
+            let values = (0..len)
+                .map(|_| {
+                    let value = read_u64()?;
+                    if value >= F::ORDER {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "non-canonical field element",
+                        ));
+                    }
+                    Ok(F::from_canonical_u64(value))
+                })
+                .collect::<std::io::Result<_>>()?;
+            Ok(PolynomialValues::new(values))
+        })
+        .collect()
+}
+
+/// An estimate of the size of the traces generated for some inputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CycleEstimate {
+    /// The number of CPU cycles, before padding.
+    pub cpu_cycles: usize,
+    /// The number of rows of the arithmetic table, before padding.
+    pub arithmetic_rows: usize,
+    /// The number of rows of the byte packing table, before padding.
+    pub byte_packing_rows: usize,
+    /// The number of rows of the keccak table, before padding.
+    pub keccak_rows: usize,
+    /// The number of rows of the keccak sponge table, before padding.
+    pub keccak_sponge_rows: usize,
+    /// The number of rows of the logic table, before padding.
+    pub logic_rows: usize,
+    /// A lower bound on the number of rows of the memory table, which also
+    /// contains the initialization of the tries and metadata, as well as
+    /// filler rows.
+    pub memory_rows: usize,
+}
+
+impl CycleEstimate {
+    /// The number of Keccak-f permutations performed, whether by the KECCAK256
+    /// opcode or by the kernel, e.g. when hashing tries or deriving addresses.
+    /// A message of `n` bytes takes `n / 136 + 1` permutations.
+    pub const fn keccak_permutations(&self) -> usize {
+        self.keccak_rows / crate::keccak::keccak_stark::NUM_ROUNDS
+    }
+}
+
+/// Estimates the size of the traces `generate_traces` would produce for the
+/// given inputs, by running them in the interpreter, which doesn't build any
+/// row. The number of CPU cycles is the one used by `generate_traces`.
+pub fn estimate_cycles<F: RichField>(inputs: &GenerationInputs) -> anyhow::Result<CycleEstimate> {
+    interpret::<F>(inputs).map(|(_, estimate)| estimate)
+}
+
+/// Runs the given inputs twice in the interpreter, and checks that both runs
+/// yield the same public values and the same number of rows in each table.
+///
+/// This is a cheap regression check against nondeterministic witness
+/// generation, e.g. from the iteration order of a `HashMap` or from reading
+/// uninitialized memory, meant to be run over many fixtures. Errors of either
+/// run are returned, and so is the first difference between the runs.
+pub fn check_deterministic<F: RichField>(inputs: &GenerationInputs) -> anyhow::Result<()> {
+    let (public_values, estimate) = interpret::<F>(inputs)?;
+    let (public_values_again, estimate_again) = interpret::<F>(inputs)?;
+
+    check_same(
+        "trie roots before",
+        &public_values.trie_roots_before,
+        &public_values_again.trie_roots_before,
+    )?;
+    check_same(
+        "trie roots after",
+        &public_values.trie_roots_after,
+        &public_values_again.trie_roots_after,
+    )?;
+    check_same(
+        "block metadata",
+        &public_values.block_metadata,
+        &public_values_again.block_metadata,
+    )?;
+    check_same(
+        "block hashes",
+        &public_values.block_hashes,
+        &public_values_again.block_hashes,
+    )?;
+    check_same(
+        "extra block data",
+        &public_values.extra_block_data,
+        &public_values_again.extra_block_data,
+    )?;
+    check_same("table sizes", &estimate, &estimate_again)
+}
+
+/// Fails if the values of `what` differ between two runs.
+fn check_same<T: Debug + PartialEq>(what: &str, first: &T, second: &T) -> anyhow::Result<()> {
+    if first != second {
+        bail!("The {what} differ between two runs: {first:?} != {second:?}");
+    }
+    Ok(())
+}
+
+/// Runs the given inputs in the interpreter, and returns the public values of
+/// their proof along with an estimate of the size of their traces.
+fn interpret<F: RichField>(
+    inputs: &GenerationInputs,
+) -> anyhow::Result<(PublicValues, CycleEstimate)> {
+    inputs.tries.validate()?;
+    check_top_level_contract_code(inputs, None)?;
+
+    let mut interpreter = Interpreter::<F>::new_with_generation_inputs(
+        KERNEL.global_labels["main"],
+        vec![],
+        inputs.clone(),
+    );
+    interpreter.run()?;
+
+    let public_values = read_public_values(&interpreter.generation_state.memory, inputs);
+    let estimate = CycleEstimate {
+        cpu_cycles: interpreter.clock,
+        ..interpreter.table_rows
+    };
+    Ok((public_values, estimate))
+}
+
+/// Runs the kernel of `options` on the given inputs, and returns the resulting
+/// traces along with the public values of the proof and statistics of the run.
+fn simulate_txn<F: RichField + Extendable<D>, const D: usize>(
+    inputs: GenerationInputs,
+    timing: &mut TimingTree,
+    options: GenerationOptions,
+) -> anyhow::Result<(Traces<F>, PublicValues, GenerationStats)> {
+    let kernel = options.kernel.unwrap_or(&KERNEL);
+    if let Some(expected) = options.expected_kernel_hash {
+        let computed = kernel.code_hash();
+        if computed != expected {
+            return Err(GenerationError::KernelMismatch { expected, computed }.into());
+        }
+    }
+
     debug_inputs(&inputs);
-    let mut state = GenerationState::<F>::new(inputs.clone(), &KERNEL.code)
-        .map_err(|err| anyhow!("Failed to parse all the initial prover inputs: {:?}", err))?;
+    inputs.tries.validate()?;
+    check_top_level_contract_code(&inputs, options.code_provider.as_deref())?;
 
-    apply_metadata_and_tries_memops(&mut state, &inputs);
+    let mut state =
+        GenerationState::<F>::new_with_kernel(inputs.clone(), kernel, options.segment_capacities)?;
+    state.txn_cycles.max_cycles = options.max_txn_cycles;
+    if let Some(max_memory_bytes) = options.max_memory_bytes {
+        let bounds = MIN_MAX_MEMORY_BYTES.1..=MAX_MAX_MEMORY_BYTES.1;
+        if !bounds.contains(&max_memory_bytes) {
+            bail!("The memory bound {max_memory_bytes} is out of the range {bounds:?}");
+        }
+        state.max_memory_bytes = max_memory_bytes;
+    }
+    if let Some(code_provider) = options.code_provider {
+        state.set_code_provider(code_provider);
+    }
+    let mut replayed_prover_inputs = None;
+    match (options.prover_input_source, options.replayed_prover_inputs) {
+        (Some(_), Some(_)) => {
+            bail!("A prover input source can't be used along with replayed prover inputs")
+        }
+        (Some(source), None) => state.set_prover_input_source(source),
+        (None, Some(prover_inputs)) => {
+            let source = Rc::new(ReplayedProverInputs::new(prover_inputs));
+            state.set_prover_input_source(source.clone());
+            replayed_prover_inputs = Some(source);
+        }
+        (None, None) => {}
+    }
+    if options.record_prover_inputs {
+        state.record_prover_inputs();
+    }
+    if options.record_storage_accesses {
+        state.record_storage_accesses();
+    }
+    if options.record_execution_trace {
+        state.record_execution_trace();
+    }
+
+    apply_metadata_and_tries_memops(&mut state, &inputs, kernel);
+    check_trie_roots_before(&state.memory, &state.trie_root_ptrs, &inputs.tries)?;
 
     let cpu_res = timed!(timing, "simulate CPU", simulate_cpu(&mut state));
     if cpu_res.is_err() {
@@ -234,13 +1023,54 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
 
         cpu_res?;
     }
+    if let Some(replayed_prover_inputs) = replayed_prover_inputs {
+        let remaining = replayed_prover_inputs.remaining();
+        if remaining > 0 {
+            return Err(GenerationError::UnusedProverInputs { remaining }.into());
+        }
+    }
 
     log::info!(
         "Trace lengths (before padding): {:?}",
         state.traces.get_lengths()
     );
+    log::info!("Keccak permutations: {}", state.traces.keccak_inputs.len());
+    time_op_class_cycles(&state, timing);
+
+    let public_values = read_public_values(&state.memory, &inputs);
+
+    let final_accounts = options
+        .record_final_accounts
+        .then(|| state.final_accounts())
+        .transpose()
+        .map_err(|err| anyhow!("Failed to read the final state trie: {:?}", err))?;
+    let mut final_storage_tries = HashMap::new();
+    for address in options.final_storage_tries {
+        let storage_trie = state
+            .final_storage_trie(address)
+            .map_err(|err| anyhow!("Failed to read the final storage trie: {:?}", err))?;
+        if let Some(storage_trie) = storage_trie {
+            final_storage_tries.insert(address, storage_trie);
+        }
+    }
+
+    let stats = GenerationStats {
+        txn_cycles: state.txn_cycles.cycles(),
+        memory_usage: state.memory.usage(),
+        accessed_code_hashes: state.accessed_code_hashes(),
+        prover_inputs: state.take_prover_input_log(),
+        storage_accesses: state.take_storage_access_log().map(|log| log.accesses),
+        execution_trace: state.take_execution_trace(),
+        final_accounts,
+        final_storage_tries,
+    };
+    Ok((state.traces, public_values, stats))
+}
 
-    let read_metadata = |field| state.memory.read_global_metadata(field);
+/// Returns the public values of a proof of the given inputs, from the global
+/// metadata of the memory once the kernel has run.
+fn read_public_values(memory: &MemoryState, inputs: &GenerationInputs) -> PublicValues {
+    let read_metadata = |field| memory.read_global_metadata(field);
     let trie_roots_before = TrieRoots {
         state_root: H256::from_uint(&read_metadata(StateTrieRootDigestBefore)),
         transactions_root: H256::from_uint(&read_metadata(TransactionTrieRootDigestBefore)),
@@ -263,20 +1093,35 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         gas_used_after,
     };
 
-    let public_values = PublicValues {
+    PublicValues {
         trie_roots_before,
         trie_roots_after,
-        block_metadata: inputs.block_metadata,
-        block_hashes: inputs.block_hashes,
+        block_metadata: inputs.block_metadata.clone(),
+        block_hashes: inputs.block_hashes.clone(),
         extra_block_data,
-    };
+    }
+}
 
-    let tables = timed!(
-        timing,
-        "convert trace data to tables",
-        state.traces.into_tables(all_stark, config, timing)
-    );
-    Ok((tables, public_values))
+/// Adds the number of CPU cycles spent in each opcode class to `timing`, as
+/// a scope with one child per class, from the most to the least expensive.
+/// These children take no time: their names carry the cycle counts.
+fn time_op_class_cycles<F: Field>(state: &GenerationState<F>, timing: &mut TimingTree) {
+    let total = state.op_class_cycles.total();
+    let mut cycles = state.op_class_cycles.cycles.iter().collect::<Vec<_>>();
+    cycles.sort_by_key(|&(_, &count)| core::cmp::Reverse(count));
+
+    timing.push("CPU cycles per opcode class", log::Level::Debug);
+    for (class, count) in cycles {
+        let scope = format!(
+            "{} cycles ({:.2}%) in {}",
+            count,
+            100.0 * *count as f64 / total as f64,
+            class
+        );
+        timing.push(&scope, log::Level::Debug);
+        timing.pop();
+    }
+    timing.pop();
 }
 
 fn simulate_cpu<F: Field>(state: &mut GenerationState<F>) -> anyhow::Result<()> {
@@ -295,6 +1140,7 @@ fn simulate_cpu<F: Field>(state: &mut GenerationState<F>) -> anyhow::Result<()>
     loop {
         // Padding to a power of 2.
         state.push_cpu(row);
+        state.op_class_cycles.record_padding();
         row.clock += F::ONE;
         if state.traces.clock().is_power_of_two() {
             break;
@@ -362,3 +1208,953 @@ pub(crate) fn output_debug_tries<F: RichField>(state: &GenerationState<F>) -> an
 
     Ok(())
 }
+
+/// Runs the CPU on the given inputs, and compares the resulting state trie
+/// with `expected_state_trie`, whose hash is the state root expected by the
+/// caller.
+///
+/// This is a debugging helper, which does not generate any trace. On mismatch,
+/// it returns a `GenerationError::RootMismatch` identifying the first account
+/// whose state differs, when its address is known to the prover.
+pub fn check_state_root<F: RichField + Extendable<D>, const D: usize>(
+    inputs: GenerationInputs,
+    expected_state_trie: &HashedPartialTrie,
+) -> anyhow::Result<()> {
+    inputs.tries.validate()?;
+    check_top_level_contract_code(&inputs, None)?;
+
+    let mut state = GenerationState::<F>::new(inputs.clone(), &KERNEL.code)
+        .map_err(|err| anyhow!("Failed to parse all the initial prover inputs: {:?}", err))?;
+
+    apply_metadata_and_tries_memops(&mut state, &inputs, &KERNEL);
+
+    // Run the kernel until it starts checking the final state trie root, at
+    // which point the computed state trie is fully stored in memory.
+    let check_state_trie = KERNEL.global_labels["check_state_trie"];
+    let halt_offsets = state.get_halt_offsets();
+    loop {
+        let registers = state.get_registers();
+        if registers.is_kernel {
+            if registers.program_counter == check_state_trie {
+                break;
+            }
+            if halt_offsets.contains(&registers.program_counter) {
+                return Err(anyhow!("CPU halted before checking the state trie."));
+            }
+        }
+        state.transition()?;
+    }
+
+    let state_trie_ptr = u256_to_usize(
+        state
+            .memory
+            .read_global_metadata(GlobalMetadata::StateTrieRoot),
+    )
+    .map_err(|_| anyhow!("State trie pointer is too large to fit in a usize."))?;
+    let computed_state_trie = get_state_trie::<HashedPartialTrie>(&state.memory, state_trie_ptr)
+        .map_err(|err| anyhow!("Failed to read the computed state trie: {:?}", err))?;
+
+    let computed = computed_state_trie.hash();
+    let expected = expected_state_trie.hash();
+    if computed == expected {
+        return Ok(());
+    }
+
+    let mut known_addresses = state.state_key_to_address.clone();
+    let accessed_addresses = inputs
+        .withdrawals
+        .iter()
+        .map(|(address, _)| *address)
+        .chain(inputs.signed_txn.as_deref().and_then(txn_destination))
+        .chain(core::iter::once(inputs.block_metadata.block_beneficiary));
+    for address in accessed_addresses {
+        known_addresses.insert(keccak(address), address);
+    }
+
+    let first_differing_account = first_differing_key(&computed_state_trie, expected_state_trie)
+        .and_then(|key| known_addresses.get(&key).copied());
+
+    Err(GenerationError::RootMismatch {
+        computed,
+        expected,
+        first_differing_account,
+    }
+    .into())
+}
+
+/// Returns the smallest key whose leaf value differs between the two tries.
+/// Parts of the tries hidden behind hash nodes are ignored.
+fn first_differing_key(a: &HashedPartialTrie, b: &HashedPartialTrie) -> Option<H256> {
+    let leaves = |trie: &HashedPartialTrie| {
+        trie.items()
+            .filter_map(|(key, val)| match val {
+                ValOrHash::Val(val) => Some((H256::from_uint(&key.try_into_u256().ok()?), val)),
+                ValOrHash::Hash(_) => None,
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (a_leaves, b_leaves) = (leaves(a), leaves(b));
+
+    a_leaves
+        .keys()
+        .chain(b_leaves.keys())
+        .filter(|key| a_leaves.get(key) != b_leaves.get(key))
+        .min()
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use mpt_trie::partial_trie::{HashedPartialTrie, PartialTrie};
+    use plonky2::field::goldilocks_field::GoldilocksField as F;
+
+    use super::*;
+    use crate::witness::traces::{arithmetic_op_len, byte_packing_op_len, keccak_sponge_op_len};
+    use crate::Node;
+
+    const D: usize = 2;
+
+    /// Inputs for a block with an empty list of transactions.
+    pub(crate) fn empty_txn_list_inputs() -> GenerationInputs {
+        let state_trie = HashedPartialTrie::from(Node::Empty);
+        let transactions_trie = HashedPartialTrie::from(Node::Empty);
+        let receipts_trie = HashedPartialTrie::from(Node::Empty);
+
+        let mut contract_code = HashMap::new();
+        contract_code.insert(keccak(vec![]), vec![]);
+
+        // No transactions, so no trie roots change.
+        let trie_roots_after = TrieRoots {
+            state_root: state_trie.hash(),
+            transactions_root: transactions_trie.hash(),
+            receipts_root: receipts_trie.hash(),
+        };
+
+        GenerationInputs {
+            tries: TrieInputs {
+                state_trie,
+                transactions_trie,
+                receipts_trie,
+                storage_tries: vec![],
+            },
+            trie_roots_after,
+            contract_code,
+            checkpoint_state_trie_root: HashedPartialTrie::from(Node::Empty).hash(),
+            block_metadata: BlockMetadata {
+                block_number: 1.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_segment_capacities_do_not_change_traces() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let (tables, public_values) = generate_traces(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+        )?;
+
+        let capacities = SegmentCapacities {
+            stack: 1024,
+            main_memory: 1 << 16,
+            calldata: 1 << 12,
+            returndata: 1 << 12,
+        };
+        let (tables_with_capacities, public_values_with_capacities, _) =
+            generate_traces_with_options(
+                &all_stark,
+                inputs,
+                &config,
+                &mut TimingTree::default(),
+                GenerationOptions {
+                    segment_capacities: capacities,
+                    ..Default::default()
+                },
+            )?;
+
+        assert_eq!(GeneratedTables::Columns(tables), tables_with_capacities);
+        assert_eq!(public_values, public_values_with_capacities);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_traces_match_polynomial_values() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let (tables, public_values) = generate_traces(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+        )?;
+        let (raw_tables, raw_public_values, _) = generate_traces_with_options(
+            &all_stark,
+            inputs,
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions {
+                table_output: TableOutput::Rows,
+                ..Default::default()
+            },
+        )?;
+        let GeneratedTables::Rows(raw_tables) = raw_tables else {
+            panic!("Expected the rows of the tables");
+        };
+
+        for (table, raw_table) in tables.into_iter().zip(raw_tables) {
+            let columns: Vec<_> = table.into_iter().map(|column| column.values).collect();
+            assert_eq!(columns, plonky2::util::transpose(&raw_table));
+        }
+        assert_eq!(public_values, raw_public_values);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tables_written_to_writer_round_trip() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let (tables, public_values) = generate_traces(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+        )?;
+        let mut buffer = vec![];
+        let (written_tables, written_public_values, _) = generate_traces_with_options(
+            &all_stark,
+            inputs,
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions {
+                table_output: TableOutput::Writer(&mut buffer),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(written_tables, GeneratedTables::Written);
+        assert_eq!(written_public_values, public_values);
+
+        let mut reader = buffer.as_slice();
+        for table in tables {
+            assert_eq!(read_table::<F>(&mut reader)?, table);
+        }
+        assert!(reader.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_initial_memory_is_read_by_first_transition() -> anyhow::Result<()> {
+        use crate::witness::memory::MemoryOpKind;
+
+        let stack_address = MemoryAddress::new(0, Segment::Stack, 0);
+        // A single `ADD`, with the second stack item seeded in memory.
+        let mut state = GenerationState::<F>::with_initial_memory(
+            GenerationInputs::default(),
+            &[0x01],
+            vec![(stack_address, 7.into())],
+        )
+        .map_err(|err| anyhow!("Failed to initialize the memory: {:?}", err))?;
+        state.registers.program_counter = 0;
+        state.registers.stack_len = 2;
+        state.registers.stack_top = 5.into();
+
+        state.transition()?;
+
+        assert_eq!(state.registers.stack_len, 1);
+        assert_eq!(state.registers.stack_top, 12.into());
+        assert!(state
+            .traces
+            .memory_ops
+            .iter()
+            .any(|op| op.address == stack_address
+                && op.kind == MemoryOpKind::Read
+                && op.value == 7.into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_top_level_contract_code() {
+        use crate::generation::mpt::transaction_testing::{AddressOption, LegacyTransactionRlp};
+
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+
+        let to = Address::from_low_u64_be(0xc0ffee);
+        let code_hash = keccak([0x60, 0x00, 0x60, 0x00, 0xf3]);
+        let account = AccountRlp {
+            nonce: 0.into(),
+            balance: 0.into(),
+            storage_root: HashedPartialTrie::from(Node::Empty).hash(),
+            code_hash,
+        };
+
+        let mut inputs = empty_txn_list_inputs();
+        inputs.tries.state_trie.insert(
+            Nibbles::from_h256_be(keccak(to)),
+            ::rlp::encode(&account).to_vec(),
+        );
+        inputs.signed_txn = Some(
+            ::rlp::encode(&LegacyTransactionRlp {
+                nonce: 0.into(),
+                gas_price: 10.into(),
+                gas: 21_000.into(),
+                to: AddressOption(Some(to)),
+                value: 0.into(),
+                data: vec![].into(),
+                v: 0x1b.into(),
+                r: 1.into(),
+                s: 1.into(),
+            })
+            .to_vec(),
+        );
+
+        let err =
+            generate_traces(&all_stark, inputs, &config, &mut TimingTree::default()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::MissingContractCode(code_hash))
+        );
+    }
+
+    #[test]
+    fn test_validate_storage_roots() {
+        let state_key = keccak(Address::from_low_u64_be(0xc0ffee));
+        let mut storage_trie = HashedPartialTrie::from(Node::Empty);
+        storage_trie.insert(
+            Nibbles::from_h256_be(keccak([0u8; 32])),
+            ::rlp::encode(&U256::from(42)).to_vec(),
+        );
+        let account = AccountRlp {
+            storage_root: storage_trie.hash(),
+            ..AccountRlp::default()
+        };
+
+        let mut tries = empty_txn_list_inputs().tries;
+        tries.state_trie.insert(
+            Nibbles::from_h256_be(state_key),
+            ::rlp::encode(&account).to_vec(),
+        );
+        tries.storage_tries = vec![(state_key, storage_trie.clone())];
+        assert_eq!(tries.validate(), Ok(()));
+
+        // Provide a storage trie with a different value in the slot.
+        let mut wrong_storage_trie = storage_trie.clone();
+        wrong_storage_trie.insert(
+            Nibbles::from_h256_be(keccak([0u8; 32])),
+            ::rlp::encode(&U256::from(43)).to_vec(),
+        );
+        tries.storage_tries = vec![(state_key, wrong_storage_trie.clone())];
+        assert_eq!(
+            tries.validate(),
+            Err(GenerationError::StorageRootMismatch {
+                state_key,
+                account_storage_root: storage_trie.hash(),
+                storage_trie_root: wrong_storage_trie.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_state_root_mismatch_points_at_differing_account() {
+        let withdrawals = vec![
+            (Address::from_low_u64_be(0x1111), U256::from(5)),
+            (Address::from_low_u64_be(0x2222), U256::from(7)),
+        ];
+
+        let mut expected_state_trie = HashedPartialTrie::from(Node::Empty);
+        for (address, amount) in &withdrawals {
+            let account = AccountRlp {
+                balance: *amount,
+                ..AccountRlp::default()
+            };
+            expected_state_trie.insert(
+                Nibbles::from_h256_be(keccak(address)),
+                ::rlp::encode(&account).to_vec(),
+            );
+        }
+
+        let mut inputs = empty_txn_list_inputs();
+        inputs.withdrawals.clone_from(&withdrawals);
+        inputs.trie_roots_after.state_root = expected_state_trie.hash();
+        check_state_root::<F, D>(inputs.clone(), &expected_state_trie).unwrap();
+
+        // Corrupt the balance of the second withdrawal recipient.
+        let corrupted_account = AccountRlp {
+            balance: withdrawals[1].1 + 1,
+            ..AccountRlp::default()
+        };
+        let mut corrupted_state_trie = expected_state_trie.clone();
+        corrupted_state_trie.insert(
+            Nibbles::from_h256_be(keccak(withdrawals[1].0)),
+            ::rlp::encode(&corrupted_account).to_vec(),
+        );
+        inputs.trie_roots_after.state_root = corrupted_state_trie.hash();
+
+        let err = check_state_root::<F, D>(inputs, &corrupted_state_trie).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::RootMismatch {
+                computed: expected_state_trie.hash(),
+                expected: corrupted_state_trie.hash(),
+                first_differing_account: Some(withdrawals[1].0),
+            })
+        );
+    }
+
+    #[test]
+    fn test_op_class_cycles_sum_to_total_cycles() -> anyhow::Result<()> {
+        let inputs = empty_txn_list_inputs();
+        let mut state = GenerationState::<F>::new(inputs.clone(), &KERNEL.code)
+            .map_err(|err| anyhow!("Failed to parse all the initial prover inputs: {:?}", err))?;
+        apply_metadata_and_tries_memops::<F, D>(&mut state, &inputs, &KERNEL);
+
+        simulate_cpu(&mut state)?;
+
+        let op_class_cycles = &state.op_class_cycles;
+        assert_eq!(op_class_cycles.total(), state.traces.clock());
+        assert!(op_class_cycles.cycles["kernel"] > 0);
+        assert!(op_class_cycles.cycles["padding"] > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_cycles() -> anyhow::Result<()> {
+        let mut with_withdrawals = empty_txn_list_inputs();
+        with_withdrawals.withdrawals = vec![
+            (Address::from_low_u64_be(0x1111), U256::from(5)),
+            (Address::from_low_u64_be(0x2222), U256::from(7)),
+            (Address::from_low_u64_be(0x3333), U256::from(9)),
+        ];
+        let mut state_trie = HashedPartialTrie::from(Node::Empty);
+        for (address, amount) in &with_withdrawals.withdrawals {
+            let account = AccountRlp {
+                balance: *amount,
+                ..AccountRlp::default()
+            };
+            state_trie.insert(
+                Nibbles::from_h256_be(keccak(address)),
+                ::rlp::encode(&account).to_vec(),
+            );
+        }
+        with_withdrawals.trie_roots_after.state_root = state_trie.hash();
+
+        for inputs in [empty_txn_list_inputs(), with_withdrawals] {
+            let estimate = estimate_cycles::<F>(&inputs)?;
+
+            let mut state =
+                GenerationState::<F>::new(inputs.clone(), &KERNEL.code).map_err(|err| {
+                    anyhow!("Failed to parse all the initial prover inputs: {:?}", err)
+                })?;
+            apply_metadata_and_tries_memops::<F, D>(&mut state, &inputs, &KERNEL);
+            state.run_cpu()?;
+
+            let traces = &state.traces;
+            assert_eq!(estimate.cpu_cycles, traces.clock());
+            assert_eq!(
+                estimate.arithmetic_rows,
+                traces
+                    .arithmetic_ops
+                    .iter()
+                    .map(arithmetic_op_len)
+                    .sum::<usize>()
+            );
+            assert_eq!(
+                estimate.byte_packing_rows,
+                traces
+                    .byte_packing_ops
+                    .iter()
+                    .map(byte_packing_op_len)
+                    .sum::<usize>()
+            );
+            assert_eq!(
+                estimate.keccak_rows,
+                traces.keccak_inputs.len() * crate::keccak::keccak_stark::NUM_ROUNDS
+            );
+            assert_eq!(estimate.keccak_permutations(), traces.keccak_inputs.len());
+            assert_eq!(
+                estimate.keccak_sponge_rows,
+                traces
+                    .keccak_sponge_ops
+                    .iter()
+                    .map(keccak_sponge_op_len)
+                    .sum::<usize>()
+            );
+            assert_eq!(estimate.logic_rows, traces.logic_ops.len());
+            assert!(estimate.memory_rows <= traces.memory_ops.len());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_same_describes_the_difference() {
+        assert!(check_same("cpu cycles", &1, &1).is_ok());
+        let err = check_same("cpu cycles", &1, &2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The cpu cycles differ between two runs: 1 != 2"
+        );
+    }
+
+    #[test]
+    // The index of the failing CTL depends on the order of the lookups in
+    // `AllStark`, so only the mismatch itself is matched.
+    #[should_panic(expected = "times in the looked table")]
+    fn test_check_ctls_catches_corrupted_memory_value() {
+        use crate::all_stark::Table;
+        use crate::memory::columns::{value_limb, FILTER};
+        use crate::verifier::debug_utils::check_ctls;
+
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let (mut tables, public_values) = generate_traces(
+            &all_stark,
+            empty_txn_list_inputs(),
+            &config,
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+        check_ctls(&all_stark, &tables, &public_values);
+
+        // Corrupt the first limb of the first memory operation.
+        let memory = &mut tables[*Table::Memory];
+        let row = memory[FILTER]
+            .values
+            .iter()
+            .position(|filter| filter.is_one())
+            .unwrap();
+        memory[value_limb(0)].values[row] += F::ONE;
+        check_ctls(&all_stark, &tables, &public_values);
+    }
+
+    #[test]
+    fn test_recordings_are_only_returned_when_requested() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let (_, _, stats) = generate_traces_with_options(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions::default(),
+        )?;
+        assert_eq!(stats.prover_inputs, None);
+        assert_eq!(stats.storage_accesses, None);
+        assert_eq!(stats.execution_trace, None);
+        assert_eq!(stats.final_accounts, None);
+        assert!(stats.final_storage_tries.is_empty());
+
+        let (_, _, stats) = generate_traces_with_options(
+            &all_stark,
+            inputs,
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions {
+                record_storage_accesses: true,
+                record_execution_trace: true,
+                record_final_accounts: true,
+                final_storage_tries: vec![Address::zero()],
+                ..Default::default()
+            },
+        )?;
+        // Without any transaction, nothing is accessed or executed, and the
+        // state trie is empty.
+        assert_eq!(stats.storage_accesses, Some(vec![]));
+        assert_eq!(stats.execution_trace, Some(vec![]));
+        assert_eq!(stats.final_accounts, Some(vec![]));
+        assert!(stats.final_storage_tries.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replayed_prover_inputs() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let run = |replayed_prover_inputs| {
+            generate_traces_with_options(
+                &all_stark,
+                inputs.clone(),
+                &config,
+                &mut TimingTree::default(),
+                GenerationOptions {
+                    record_prover_inputs: true,
+                    replayed_prover_inputs,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let (tables, _, stats) = run(None)?;
+        let prover_inputs = stats.prover_inputs.unwrap();
+        assert!(!prover_inputs.is_empty());
+
+        // The replayed run requests exactly the recorded prover inputs.
+        let (replayed_tables, _, replayed_stats) = run(Some(prover_inputs.clone()))?;
+        assert_eq!(replayed_stats.prover_inputs, Some(prover_inputs.clone()));
+        assert_eq!(replayed_tables, tables);
+
+        // Prover inputs left over after the run are reported.
+        let mut extra_prover_inputs = prover_inputs;
+        extra_prover_inputs.push(U256::one());
+        let err = run(Some(extra_prover_inputs)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::UnusedProverInputs { remaining: 1 })
+        );
+
+        Ok(())
+    }
+
+    /// A `ProverInputSource` counting the prover inputs it is asked for.
+    #[derive(Debug, Default)]
+    struct CountingSource {
+        requests: Cell<usize>,
+    }
+
+    impl ProverInputSource for CountingSource {
+        fn prover_input(&self, _request: &ProverInputRequest) -> Option<U256> {
+            self.requests.set(self.requests.get() + 1);
+            None
+        }
+    }
+
+    #[test]
+    fn test_prover_input_source() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let source = Rc::new(CountingSource::default());
+        let (_, _, stats) = generate_traces_with_options(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions {
+                prover_input_source: Some(source.clone()),
+                record_prover_inputs: true,
+                ..Default::default()
+            },
+        )?;
+        // The source is asked for every prover input, which are then computed
+        // from the in-memory inputs.
+        assert_eq!(source.requests.get(), stats.prover_inputs.unwrap().len());
+
+        assert!(generate_traces_with_options(
+            &all_stark,
+            inputs,
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions {
+                prover_input_source: Some(source),
+                replayed_prover_inputs: Some(vec![]),
+                ..Default::default()
+            },
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_memory_bytes() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let run = |max_memory_bytes, replayed_prover_inputs| {
+            generate_traces_with_options(
+                &all_stark,
+                inputs.clone(),
+                &config,
+                &mut TimingTree::default(),
+                GenerationOptions {
+                    max_memory_bytes,
+                    record_prover_inputs: true,
+                    replayed_prover_inputs,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // The bound is handed to the kernel, which stores it in the global
+        // metadata.
+        let max_memory_bytes = MIN_MAX_MEMORY_BYTES.1 * 2;
+        let (_, _, stats) = run(Some(max_memory_bytes), None)?;
+        let mut prover_inputs = stats.prover_inputs.unwrap();
+        let index = prover_inputs
+            .iter()
+            .position(|&input| input == max_memory_bytes.into())
+            .unwrap();
+
+        // The bound can't be lowered, nor be too large for 32-bit offsets.
+        assert!(run(Some(MIN_MAX_MEMORY_BYTES.1 - 1), None).is_err());
+        assert!(run(Some(MAX_MAX_MEMORY_BYTES.1 + 1), None).is_err());
+
+        // The kernel checks the bound it is given too.
+        prover_inputs[index] = (MIN_MAX_MEMORY_BYTES.1 - 1).into();
+        assert!(run(None, Some(prover_inputs)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_trie_roots_before() {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let mut inputs = empty_txn_list_inputs();
+
+        // An account followed by a fifth item, which decodes fine but which
+        // the kernel drops when hashing the state trie.
+        let key = Nibbles::from_h256_be(keccak(Address::from_low_u64_be(0x1111)));
+        let account = AccountRlp::default();
+        let mut stream = ::rlp::RlpStream::new_list(5);
+        stream
+            .append(&account.nonce)
+            .append(&account.balance)
+            .append(&account.storage_root)
+            .append(&account.code_hash)
+            .append(&0x2au8);
+        inputs.tries.state_trie.insert(key, stream.out().to_vec());
+
+        let mut loaded_state_trie = inputs.tries.state_trie.clone();
+        loaded_state_trie.insert(key, ::rlp::encode(&account).to_vec());
+
+        let err = generate_traces(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::InitialRootMismatch {
+                trie: "state",
+                loaded: loaded_state_trie.hash(),
+                inputs: inputs.tries.state_trie.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_custom_kernel() -> anyhow::Result<()> {
+        let kernel = crate::cpu::kernel::assemble_kernel(&[
+            "global main:\n PUSH halt\n JUMP".to_string(),
+            "global halt:\n PANIC".to_string(),
+        ]);
+        assert_ne!(kernel.global_labels["halt"], KERNEL.global_labels["halt"]);
+
+        let (traces, _, stats) = simulate_txn::<F, D>(
+            empty_txn_list_inputs(),
+            &mut TimingTree::default(),
+            GenerationOptions {
+                kernel: Some(&kernel),
+                ..Default::default()
+            },
+        )?;
+
+        // `PUSH` and `JUMP`, padded to a power of 2.
+        assert_eq!(traces.clock(), 4);
+        assert!(traces.arithmetic_ops.is_empty());
+        assert!(traces.keccak_inputs.is_empty());
+        assert!(stats.txn_cycles.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_kernel_without_halt() {
+        let kernel = crate::cpu::kernel::assemble_kernel(&["global main:\n PANIC".to_string()]);
+
+        let err = simulate_txn::<F, D>(
+            empty_txn_list_inputs(),
+            &mut TimingTree::default(),
+            GenerationOptions {
+                kernel: Some(&kernel),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::MissingKernelLabel("halt"))
+        );
+    }
+
+    #[test]
+    fn test_custom_kernel_hooks() {
+        // The validity checks are resolved from this kernel, not from `KERNEL`. The
+        // transaction has nonce 1, unlike its sender, which is not in the state trie.
+        let kernel = crate::cpu::kernel::assemble_kernel(&[
+            "global main:\n PUSH process_normalized_txn\n JUMP".to_string(),
+            "global process_normalized_txn:\n PUSH @TXN_FIELD_NONCE\n PUSH 1\n MSTORE_GENERAL\n PUSH invalid_txn_1\n JUMP".to_string(),
+            "global buy_gas:\n PANIC".to_string(),
+            "global invalid_txn_1:\n PANIC".to_string(),
+            "global halt:\n PANIC".to_string(),
+        ]);
+        assert_ne!(
+            kernel.global_labels["invalid_txn_1"],
+            KERNEL.global_labels["invalid_txn_1"]
+        );
+
+        let err = simulate_txn::<F, D>(
+            empty_txn_list_inputs(),
+            &mut TimingTree::default(),
+            GenerationOptions {
+                kernel: Some(&kernel),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::InvalidTxn(
+                InvalidTxnReason::NonceMismatch
+            ))
+        );
+    }
+
+    #[test]
+    fn test_expected_kernel_hash() -> anyhow::Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let inputs = empty_txn_list_inputs();
+
+        let wrong_hash = keccak([]);
+        let err = generate_traces_with_options(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions {
+                expected_kernel_hash: Some(wrong_hash),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::KernelMismatch {
+                expected: wrong_hash,
+                computed: KERNEL.code_hash(),
+            })
+        );
+
+        let (tables, public_values, _) = generate_traces_with_options(
+            &all_stark,
+            inputs.clone(),
+            &config,
+            &mut TimingTree::default(),
+            GenerationOptions {
+                expected_kernel_hash: Some(KERNEL.code_hash()),
+                ..Default::default()
+            },
+        )?;
+        let (expected_tables, expected_public_values) =
+            generate_traces(&all_stark, inputs, &config, &mut TimingTree::default())?;
+        assert_eq!(tables, GeneratedTables::Columns(expected_tables));
+        assert_eq!(public_values, expected_public_values);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bincode_bytes_round_trip() -> anyhow::Result<()> {
+        let mut inputs = empty_txn_list_inputs();
+        for i in 0..64u64 {
+            let account = AccountRlp {
+                nonce: i.into(),
+                balance: (i * 1_000_000).into(),
+                code_hash: keccak([i as u8]),
+                ..AccountRlp::default()
+            };
+            inputs.tries.state_trie.insert(
+                Nibbles::from_h256_be(keccak(Address::from_low_u64_be(i))),
+                ::rlp::encode(&account).to_vec(),
+            );
+            inputs
+                .contract_code
+                .insert(keccak([i as u8]), vec![i as u8; 100]);
+        }
+        inputs.withdrawals = vec![(Address::from_low_u64_be(0x1111), U256::from(5))];
+        inputs.signed_txn = Some(vec![0xf8; 120]);
+
+        let bytes = inputs.to_bincode_bytes();
+        assert_eq!(bytes[..4], GENERATION_INPUTS_MAGIC);
+        assert_eq!(bytes[4..8], INPUTS_SCHEMA_VERSION.to_le_bytes());
+
+        let decoded = GenerationInputs::from_bincode_bytes(&bytes)?;
+        assert_eq!(decoded.tries.state_trie, inputs.tries.state_trie);
+        assert_eq!(decoded.contract_code, inputs.contract_code);
+        assert_eq!(decoded.to_bincode_bytes(), bytes);
+
+        assert!(bytes.len() < serde_json::to_vec(&inputs)?.len());
+
+        assert!(GenerationInputs::from_bincode_bytes(&bytes[1..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_contract_code_serialization_is_deterministic() -> anyhow::Result<()> {
+        let codes: Vec<_> = (0..32u8).map(|i| (keccak([i]), vec![i; 10])).collect();
+        let mut forward = empty_txn_list_inputs();
+        forward.contract_code = codes.iter().cloned().collect();
+        let mut backward = empty_txn_list_inputs();
+        backward.contract_code = codes.iter().rev().cloned().collect();
+
+        assert_eq!(
+            serde_json::to_vec(&forward)?,
+            serde_json::to_vec(&backward)?
+        );
+        assert_eq!(
+            bincode::serialize(&forward)?,
+            bincode::serialize(&backward)?
+        );
+        assert_eq!(forward.to_bincode_bytes(), backward.to_bincode_bytes());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bincode_bytes_version_mismatch() -> anyhow::Result<()> {
+        let bytes = empty_txn_list_inputs().to_bincode_bytes();
+        GenerationInputs::from_bincode_bytes(&bytes)?;
+
+        let mut bumped = bytes.clone();
+        bumped[4..8].copy_from_slice(&(INPUTS_SCHEMA_VERSION + 1).to_le_bytes());
+        let err = GenerationInputs::from_bincode_bytes(&bumped).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GenerationError>(),
+            Some(&GenerationError::VersionMismatch {
+                expected: INPUTS_SCHEMA_VERSION,
+                found: INPUTS_SCHEMA_VERSION + 1,
+            })
+        );
+
+        Ok(())
+    }
+}
@@ -1,11 +1,12 @@
 use core::ops::Deref;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use bytes::Bytes;
 use ethereum_types::{Address, BigEndianHash, H256, U256, U512};
 use keccak_hash::keccak;
 use mpt_trie::nibbles::Nibbles;
 use mpt_trie::partial_trie::{HashedPartialTrie, PartialTrie};
+use mpt_trie::trie_ops::ValOrHash;
 use rlp::{Decodable, DecoderError, Encodable, PayloadInfo, Rlp, RlpStream};
 use rlp_derive::{RlpDecodable, RlpEncodable};
 
@@ -15,7 +16,7 @@ use crate::util::h2u;
 use crate::witness::errors::{ProgramError, ProverInputError};
 use crate::Node;
 
-#[derive(RlpEncodable, RlpDecodable, Debug)]
+#[derive(RlpEncodable, RlpDecodable, Debug, Clone, PartialEq, Eq)]
 pub struct AccountRlp {
     pub nonce: U256,
     pub balance: U256,
@@ -35,12 +36,31 @@ impl Default for AccountRlp {
         Self {
             nonce: U256::zero(),
             balance: U256::zero(),
-            storage_root: HashedPartialTrie::from(Node::Empty).hash(),
+            storage_root: empty_trie_hash(),
             code_hash: keccak([]),
         }
     }
 }
 
+/// Returns the root of the empty trie, `keccak(rlp(""))`. This is the storage
+/// root of every account without storage, and it is not `H256::zero()`.
+pub fn empty_trie_hash() -> H256 {
+    HashedPartialTrie::from(Node::Empty).hash()
+}
+
+/// RLP-encodes an account as a state trie leaf, i.e. as the list `[nonce,
+/// balance, storage_root, code_hash]`. A zero nonce or balance is encoded as
+/// the empty string, as in Ethereum.
+pub fn encode_account(account: &AccountRlp) -> Vec<u8> {
+    rlp::encode(account).to_vec()
+}
+
+/// Decodes a state trie leaf encoded by `encode_account`. Non-canonical
+/// encodings, e.g. with leading zeros in the nonce or balance, are rejected.
+pub fn decode_account(bytes: &[u8]) -> Result<AccountRlp, DecoderError> {
+    rlp::decode(bytes)
+}
+
 #[derive(RlpEncodable, RlpDecodable, Debug, Clone)]
 pub struct LogRlp {
     pub address: Address,
@@ -273,12 +293,18 @@ fn load_state_trie(
                 code_hash,
             } = account;
 
-            let storage_hash_only = HashedPartialTrie::new(Node::Hash(storage_root));
+            // A storage trie omitted from `TrieInputs` is only known by its root, unless
+            // that root is the empty trie hash, in which case the whole trie is known.
+            let storage_root_only = if storage_root == empty_trie_hash() {
+                HashedPartialTrie::new(Node::Empty)
+            } else {
+                HashedPartialTrie::new(Node::Hash(storage_root))
+            };
             let merged_key = key.merge_nibbles(nibbles);
             let storage_trie: &HashedPartialTrie = storage_tries_by_state_key
                 .get(&merged_key)
                 .copied()
-                .unwrap_or(&storage_hash_only);
+                .unwrap_or(&storage_root_only);
 
             assert_eq!(storage_trie.hash(), storage_root,
                 "In TrieInputs, an account's storage_root didn't match the associated storage trie hash");
@@ -346,6 +372,272 @@ pub(crate) fn load_all_mpts(
     Ok((trie_root_ptrs, trie_data))
 }
 
+/// The changes of an account between two state tries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountDiff {
+    /// The key of the account in the state trie, i.e. the hash of its address.
+    pub state_key: H256,
+    /// The account before the changes, or `None` if it was created.
+    pub before: Option<AccountRlp>,
+    /// The account after the changes, or `None` if it was deleted.
+    pub after: Option<AccountRlp>,
+    /// The changed storage slots. This is only filled by
+    /// `state_diff_with_storage`, for accounts whose storage tries are given on
+    /// both sides.
+    pub storage: Vec<StorageDiff>,
+}
+
+/// The change of a storage slot between two storage tries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageDiff {
+    /// The key of the slot in the storage trie, i.e. the hash of the slot.
+    pub slot_key: H256,
+    /// The value before the change, or `None` if the slot was empty.
+    pub before: Option<U256>,
+    /// The value after the change, or `None` if the slot was cleared.
+    pub after: Option<U256>,
+}
+
+/// Returns the accounts which differ between the `before` and `after` state
+/// tries, ordered by state key.
+///
+/// Only the leaves present in the tries are compared: accounts hidden behind
+/// `Hash` nodes are ignored.
+pub fn state_diff(
+    before: &HashedPartialTrie,
+    after: &HashedPartialTrie,
+) -> Result<Vec<AccountDiff>, DecoderError> {
+    let decode_account = |rlp: &[u8]| rlp::decode::<AccountRlp>(rlp);
+    Ok(leaf_diffs(before, after, decode_account)?
+        .into_iter()
+        .map(|(state_key, before, after)| AccountDiff {
+            state_key,
+            before,
+            after,
+            storage: vec![],
+        })
+        .collect())
+}
+
+/// Same as `state_diff`, but also reports the storage changes of the accounts
+/// whose storage tries are present in both `storage_tries_before` and
+/// `storage_tries_after`, keyed by state key as in `TrieInputs`.
+pub fn state_diff_with_storage(
+    before: &HashedPartialTrie,
+    after: &HashedPartialTrie,
+    storage_tries_before: &[(H256, HashedPartialTrie)],
+    storage_tries_after: &[(H256, HashedPartialTrie)],
+) -> Result<Vec<AccountDiff>, DecoderError> {
+    let storage_tries_before: HashMap<_, _> = storage_tries_before.iter().cloned().collect();
+    let storage_tries_after: HashMap<_, _> = storage_tries_after.iter().cloned().collect();
+    let decode_value = |rlp: &[u8]| rlp::decode::<U256>(rlp);
+
+    let mut diffs = state_diff(before, after)?;
+    for diff in diffs.iter_mut() {
+        if let (Some(storage_before), Some(storage_after)) = (
+            storage_tries_before.get(&diff.state_key),
+            storage_tries_after.get(&diff.state_key),
+        ) {
+            diff.storage = leaf_diffs(storage_before, storage_after, decode_value)?
+                .into_iter()
+                .map(|(slot_key, before, after)| StorageDiff {
+                    slot_key,
+                    before,
+                    after,
+                })
+                .collect();
+        }
+    }
+    Ok(diffs)
+}
+
+/// How `state_accounts` handles the subtries of a partial trie which are only
+/// known by their hash, and whose accounts therefore cannot be enumerated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashNodePolicy {
+    /// Skip the accounts behind `Hash` nodes.
+    Skip,
+    /// Yield an `AccountsError::HashNode` error for each `Hash` node.
+    Fail,
+}
+
+/// Errors that can occur when enumerating accounts with `state_accounts`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountsError {
+    /// A subtrie is only known by its hash.
+    HashNode(H256),
+    /// A leaf of the state trie is not a valid account encoding.
+    InvalidAccount(DecoderError),
+}
+
+impl core::fmt::Display for AccountsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AccountsError::HashNode(hash) => {
+                write!(f, "the accounts behind hash node {:?} are unknown", hash)
+            }
+            AccountsError::InvalidAccount(err) => write!(f, "invalid account: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AccountsError {}
+
+/// Returns an iterator over the accounts of a state trie, with their state
+/// keys, i.e. the hashes of their addresses, in key order. The accounts behind
+/// `Hash` nodes are skipped or reported as errors, according to `hash_nodes`.
+pub fn state_accounts(
+    trie: &HashedPartialTrie,
+    hash_nodes: HashNodePolicy,
+) -> impl Iterator<Item = Result<(H256, AccountRlp), AccountsError>> + '_ {
+    trie.items().filter_map(move |(key, value)| match value {
+        ValOrHash::Val(encoded) => {
+            let key = key.try_into_u256().expect("Trie keys fit in 256 bits");
+            Some(
+                rlp::decode::<AccountRlp>(&encoded)
+                    .map(|account| (H256::from_uint(&key), account))
+                    .map_err(AccountsError::InvalidAccount),
+            )
+        }
+        ValOrHash::Hash(hash) => match hash_nodes {
+            HashNodePolicy::Skip => None,
+            HashNodePolicy::Fail => Some(Err(AccountsError::HashNode(hash))),
+        },
+    })
+}
+
+/// Returns the decoded leaves which differ between two tries, with their keys.
+#[allow(clippy::type_complexity)]
+fn leaf_diffs<T: PartialEq>(
+    before: &HashedPartialTrie,
+    after: &HashedPartialTrie,
+    decode: impl Fn(&[u8]) -> Result<T, DecoderError>,
+) -> Result<Vec<(H256, Option<T>, Option<T>)>, DecoderError> {
+    let leaves = |trie: &HashedPartialTrie| -> Result<BTreeMap<H256, T>, DecoderError> {
+        trie.items()
+            .filter_map(|(key, value)| match value {
+                ValOrHash::Val(rlp) => Some((key, rlp)),
+                ValOrHash::Hash(_) => None,
+            })
+            .map(|(key, rlp)| {
+                let key = key.try_into_u256().expect("Trie keys fit in 256 bits");
+                Ok((H256::from_uint(&key), decode(&rlp)?))
+            })
+            .collect()
+    };
+    let mut before = leaves(before)?;
+    let mut after = leaves(after)?;
+
+    let keys: BTreeSet<H256> = before.keys().chain(after.keys()).copied().collect();
+    Ok(keys
+        .into_iter()
+        .filter_map(|key| {
+            let value_before = before.remove(&key);
+            let value_after = after.remove(&key);
+            (value_before != value_after).then_some((key, value_before, value_after))
+        })
+        .collect())
+}
+
+/// Errors that can occur when generating a Merkle proof with
+/// `generate_merkle_proof`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// The path to the key goes through a node only known by its hash.
+    HashNode(H256),
+}
+
+impl core::fmt::Display for ProofError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProofError::HashNode(hash) => {
+                write!(f, "the path to the key goes through hash node {:?}", hash)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+/// Returns the Merkle proof of `key` in `trie`, as `eth_getProof` does: the RLP
+/// encodings of the nodes on the path from the root to `key`, omitting the
+/// nodes short enough to be inlined in their parent. If `key` is absent from
+/// the trie, the proof ends at the node where its path leaves the trie, and
+/// proves its exclusion.
+pub fn generate_merkle_proof(
+    trie: &HashedPartialTrie,
+    mut key: Nibbles,
+) -> Result<Vec<Vec<u8>>, ProofError> {
+    let mut proof = vec![];
+    let mut node = trie;
+    loop {
+        if let Node::Hash(hash) = **node {
+            return Err(ProofError::HashNode(hash));
+        }
+        let encoded = encode_trie_node(node);
+        if proof.is_empty() || encoded.len() >= 32 {
+            proof.push(encoded);
+        }
+
+        match &**node {
+            Node::Branch { children, .. } if !key.is_empty() => {
+                node = &**children[key.pop_next_nibble_front() as usize];
+            }
+            Node::Extension { nibbles, child }
+                if key.count >= nibbles.count
+                    && key.nibbles_are_identical_up_to_smallest_count(nibbles) =>
+            {
+                key.truncate_n_nibbles_front_mut(nibbles.count);
+                node = &***child;
+            }
+            _ => return Ok(proof),
+        }
+    }
+}
+
+/// RLP-encodes a trie node, referring to each child by the hash of its
+/// encoding, or by the encoding itself if it is shorter than 32 bytes.
+fn encode_trie_node(node: &HashedPartialTrie) -> Vec<u8> {
+    let append_child = |stream: &mut RlpStream, child: &HashedPartialTrie| match **child {
+        Node::Hash(hash) => {
+            stream.append(&hash);
+        }
+        _ => {
+            let encoded = encode_trie_node(child);
+            if encoded.len() < 32 {
+                stream.append_raw(&encoded, 1);
+            } else {
+                stream.append(&keccak(&encoded));
+            }
+        }
+    };
+
+    match &**node {
+        Node::Empty => rlp::NULL_RLP.to_vec(),
+        Node::Hash(_) => unreachable!("Hash nodes are referred to by their hash"),
+        Node::Branch { children, value } => {
+            let mut stream = RlpStream::new_list(17);
+            for child in children {
+                append_child(&mut stream, child);
+            }
+            stream.append(value);
+            stream.out().to_vec()
+        }
+        Node::Extension { nibbles, child } => {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&nibbles.to_hex_prefix_encoding(false));
+            append_child(&mut stream, child);
+            stream.out().to_vec()
+        }
+        Node::Leaf { nibbles, value } => {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&nibbles.to_hex_prefix_encoding(true));
+            stream.append(value);
+            stream.out().to_vec()
+        }
+    }
+}
+
 pub mod transaction_testing {
     use super::*;
 
@@ -425,3 +717,284 @@ pub mod transaction_testing {
         pub s: U256,
     }
 }
+
+/// Returns the root of the transaction trie of a block with the given signed
+/// transactions, in order. The i-th transaction is keyed by `rlp(i)`, so that
+/// the first one is keyed by `0x80` rather than `0x00`.
+pub fn compute_transactions_root(txns: &[Vec<u8>]) -> H256 {
+    let mut trie = HashedPartialTrie::from(Node::Empty);
+    for (i, txn) in txns.iter().enumerate() {
+        let key = Nibbles::from_bytes_be(&rlp::encode(&i)).expect("An index key is never empty");
+        trie.insert(key, txn.clone());
+    }
+    trie.hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use mpt_trie::trie_subsets::create_trie_subset;
+
+    use super::*;
+
+    #[test]
+    fn test_compute_transactions_root() {
+        assert_eq!(
+            compute_transactions_root(&[]),
+            HashedPartialTrie::from(Node::Empty).hash()
+        );
+
+        // The transactions of the block used in `test_txn_and_receipt_trie_hash`.
+        let txns = [
+            hex!("f8cc8302687f843b9aca008303d090947ef66b77759e12caf3ddb3e4aff524e577c59d8d80b864e9c6c176000000000000000000000000000000000000000000000000000000000000002a0000000000000000000000000000000000000000000000000000000000bd9fe6f7af1cc94b1aef2e0fa15f1b4baefa86eb60e78fa4bd082372a0a446d197fb581ca0d0eeac4841caf7a894dd79e6e633efc2380553cdf8b786d1aa0b8a8dee0266f4a0740710eed9696c663510b7fb71a553112551121595a54ec6d2ec0afcec72a973").to_vec(),
+            hex!("f8cc83026880843b9aca008303d090947ef66b77759e12caf3ddb3e4aff524e577c59d8d80b864e9c6c176000000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000004920eaa814f7df6a2203dc0e472e8828be95957c6b329fee8e2b1bb6f044c1eb4fc2431ba0a3ff39967683fc684dc7b857d6f62723e78804a14b091a058ad95cc1b8a0281fa051b156e05f21f499fa1ae47ebf536b15a237208f1d4a62e33956b6b03cf47742").to_vec(),
+        ];
+        assert_eq!(
+            compute_transactions_root(&txns),
+            hex!("3ab7120d12e1fc07303508542602beb7eecfe8f262b83fd71eefe7d6205242ce").into()
+        );
+    }
+
+    #[test]
+    fn test_account_encoding() {
+        // Every empty account on mainnet has this encoding.
+        let empty = hex!("f8448080a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(encode_account(&AccountRlp::default()), empty);
+        assert_eq!(decode_account(&empty), Ok(AccountRlp::default()));
+
+        let account = AccountRlp {
+            nonce: 1.into(),
+            balance: 0x0de0b6b3a7640000u64.into(),
+            ..AccountRlp::default()
+        };
+        let encoded = hex!("f84c01880de0b6b3a7640000a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(encode_account(&account), encoded);
+        assert_eq!(decode_account(&encoded), Ok(account));
+
+        // A zero nonce encoded as the byte 0x00 rather than the empty string.
+        let mut non_canonical = empty.to_vec();
+        non_canonical[2] = 0x00;
+        assert!(decode_account(&non_canonical).is_err());
+    }
+
+    fn trie_with<V: Encodable>(leaves: &[(H256, V)]) -> HashedPartialTrie {
+        let mut trie = HashedPartialTrie::from(Node::Empty);
+        for (key, value) in leaves {
+            trie.insert(Nibbles::from_h256_be(*key), rlp::encode(value).to_vec());
+        }
+        trie
+    }
+
+    /// Walks a Merkle proof of `key` from `root`, and returns the value of
+    /// `key`, or `None` if the proof shows that it is absent.
+    fn verify_merkle_proof(root: H256, key: H256, proof: &[Vec<u8>]) -> Option<Vec<u8>> {
+        let to_nibbles = |bytes: &[u8]| bytes.iter().flat_map(|b| [b >> 4, b & 0xf]).collect();
+        let mut path: Vec<u8> = to_nibbles(key.as_bytes());
+        let mut nodes = proof.iter();
+        let mut node = nodes.next().unwrap().clone();
+        assert_eq!(keccak(&node), root);
+        loop {
+            let rlp = Rlp::new(&node);
+            let child = if rlp.item_count().unwrap() == 17 {
+                let nibble = path.remove(0);
+                rlp.at(nibble as usize).unwrap()
+            } else {
+                let encoded_path: Vec<u8> = rlp.val_at(0).unwrap();
+                let flag = encoded_path[0] >> 4;
+                let mut node_path = if flag & 1 == 1 {
+                    vec![encoded_path[0] & 0xf]
+                } else {
+                    vec![]
+                };
+                node_path.extend(to_nibbles(&encoded_path[1..]));
+                if !path.starts_with(&node_path) {
+                    return None;
+                }
+                path.drain(..node_path.len());
+                if flag & 2 == 2 {
+                    return Some(rlp.val_at(1).unwrap());
+                }
+                rlp.at(1).unwrap()
+            };
+
+            if child.is_empty() {
+                return None;
+            }
+            node = if child.is_list() {
+                child.as_raw().to_vec()
+            } else {
+                let next = nodes.next().unwrap().clone();
+                assert_eq!(keccak(&next).as_bytes(), child.data().unwrap());
+                next
+            };
+        }
+    }
+
+    #[test]
+    fn test_merkle_proofs() -> Result<(), ProofError> {
+        let leaves: Vec<_> = (0..32u64)
+            .map(|i| (keccak(i.to_be_bytes()), U256::from(i) << 128))
+            .collect();
+        let trie = trie_with(&leaves);
+
+        for (key, value) in &leaves {
+            let proof = generate_merkle_proof(&trie, Nibbles::from_h256_be(*key))?;
+            assert_eq!(
+                verify_merkle_proof(trie.hash(), *key, &proof),
+                Some(rlp::encode(value).to_vec())
+            );
+        }
+
+        let absent = keccak(100u64.to_be_bytes());
+        let proof = generate_merkle_proof(&trie, Nibbles::from_h256_be(absent))?;
+        assert!(!proof.is_empty());
+        assert_eq!(verify_merkle_proof(trie.hash(), absent, &proof), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_diff() -> Result<(), DecoderError> {
+        let [unchanged, funded, created, deleted, stored] =
+            core::array::from_fn(|i| keccak([i as u8]));
+        let account = AccountRlp {
+            balance: 100.into(),
+            ..AccountRlp::default()
+        };
+        let funded_account = AccountRlp {
+            balance: 200.into(),
+            ..AccountRlp::default()
+        };
+
+        let slot = keccak([0u8; 32]);
+        let storage_before = trie_with(&[(slot, U256::from(1))]);
+        let storage_after = trie_with(&[(slot, U256::from(2))]);
+        let stored_account = |storage: &HashedPartialTrie| AccountRlp {
+            storage_root: storage.hash(),
+            ..AccountRlp::default()
+        };
+
+        let state_before = trie_with(&[
+            (unchanged, account.clone()),
+            (funded, account.clone()),
+            (deleted, account.clone()),
+            (stored, stored_account(&storage_before)),
+        ]);
+        let state_after = trie_with(&[
+            (unchanged, account.clone()),
+            (funded, funded_account.clone()),
+            (created, account.clone()),
+            (stored, stored_account(&storage_after)),
+        ]);
+
+        let diffs = state_diff_with_storage(
+            &state_before,
+            &state_after,
+            &[(stored, storage_before.clone())],
+            &[(stored, storage_after.clone())],
+        )?;
+        let mut expected = vec![
+            AccountDiff {
+                state_key: funded,
+                before: Some(account.clone()),
+                after: Some(funded_account),
+                storage: vec![],
+            },
+            AccountDiff {
+                state_key: created,
+                before: None,
+                after: Some(account.clone()),
+                storage: vec![],
+            },
+            AccountDiff {
+                state_key: deleted,
+                before: Some(account),
+                after: None,
+                storage: vec![],
+            },
+            AccountDiff {
+                state_key: stored,
+                before: Some(stored_account(&storage_before)),
+                after: Some(stored_account(&storage_after)),
+                storage: vec![StorageDiff {
+                    slot_key: slot,
+                    before: Some(1.into()),
+                    after: Some(2.into()),
+                }],
+            },
+        ];
+        expected.sort_by_key(|diff| diff.state_key);
+        assert_eq!(diffs, expected);
+
+        // Without the storage tries, only the storage root change is reported.
+        for diff in expected.iter_mut() {
+            diff.storage.clear();
+        }
+        assert_eq!(state_diff(&state_before, &state_after)?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_storage_trie() -> Result<(), ProgramError> {
+        assert_ne!(empty_trie_hash(), H256::zero());
+
+        let state_key = keccak([0u8]);
+        let tries = |storage_tries| TrieInputs {
+            state_trie: trie_with(&[(state_key, AccountRlp::default())]),
+            transactions_trie: Node::Empty.into(),
+            receipts_trie: Node::Empty.into(),
+            storage_tries,
+        };
+        let omitted = tries(vec![]);
+        let explicit = tries(vec![(state_key, Node::Empty.into())]);
+        assert!(omitted.validate().is_ok());
+        assert!(explicit.validate().is_ok());
+
+        // Both are loaded as an empty storage trie, rather than a hash node.
+        let (omitted_ptrs, omitted_data) = load_all_mpts(&omitted)?;
+        let (explicit_ptrs, explicit_data) = load_all_mpts(&explicit)?;
+        assert_eq!(omitted_ptrs.state_root_ptr, explicit_ptrs.state_root_ptr);
+        assert_eq!(omitted_data, explicit_data);
+        assert!(!omitted_data.contains(&h2u(empty_trie_hash())));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_accounts() {
+        let accounts: Vec<_> = (0..10u64)
+            .map(|i| {
+                let account = AccountRlp {
+                    nonce: i.into(),
+                    balance: (i * 1000).into(),
+                    ..AccountRlp::default()
+                };
+                (keccak(i.to_be_bytes()), account)
+            })
+            .collect();
+        let trie = trie_with(&accounts);
+
+        let enumerated: Vec<_> = state_accounts(&trie, HashNodePolicy::Fail)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(enumerated.len(), accounts.len());
+        assert!(enumerated.contains(&accounts[7]));
+        assert!(enumerated.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        let total_supply = enumerated
+            .iter()
+            .fold(U256::zero(), |total, (_, account)| total + account.balance);
+        assert_eq!(total_supply, 45000.into());
+
+        // Only the path to the 8th account is kept, the other subtries are pruned.
+        let pruned = create_trie_subset(&trie, [Nibbles::from_h256_be(accounts[7].0)]).unwrap();
+        let known: Vec<_> = state_accounts(&pruned, HashNodePolicy::Skip)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(known.contains(&accounts[7]));
+        assert!(known.len() < accounts.len());
+        assert!(state_accounts(&pruned, HashNodePolicy::Fail)
+            .any(|item| matches!(item, Err(AccountsError::HashNode(_)))));
+    }
+}
@@ -1,5 +1,7 @@
+use core::fmt::Debug;
 use core::mem::transmute;
-use std::collections::{BTreeSet, HashMap};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::str::FromStr;
 
 use anyhow::{bail, Error};
@@ -39,8 +41,107 @@ impl From<Vec<String>> for ProverInputFn {
     }
 }
 
+/// A request for a prover input, made by the kernel through `PROVER_INPUT`.
+#[derive(Debug, Clone, Copy)]
+pub struct ProverInputRequest<'a> {
+    /// The requested prover input function.
+    pub input_fn: &'a ProverInputFn,
+    /// The context in which the request is made.
+    pub context: usize,
+    /// The kernel program counter of the `PROVER_INPUT` instruction.
+    pub program_counter: usize,
+}
+
+/// A source of prover inputs, queried on demand whenever the kernel requests
+/// a hint. This lets callers which cannot materialize all the hints upfront
+/// provide them lazily, e.g. by fetching trie nodes from a remote store.
+pub trait ProverInputSource: Debug {
+    /// Returns the prover input for `request`, or `None` to compute it from
+    /// the in-memory generation inputs.
+    fn prover_input(&self, _request: &ProverInputRequest) -> Option<U256> {
+        None
+    }
+}
+
+/// A store of contract code, such as a database keyed by code hash. It is
+/// queried on demand for the codes the kernel loads which are missing from
+/// `GenerationInputs::contract_code`, so that unused codes never need to be
+/// loaded.
+pub trait CodeProvider: Debug {
+    /// Returns the code whose hash is `hash`, or `None` if it is unknown.
+    fn get(&self, hash: H256) -> Option<Vec<u8>>;
+}
+
+impl CodeProvider for HashMap<H256, Vec<u8>> {
+    fn get(&self, hash: H256) -> Option<Vec<u8>> {
+        HashMap::get(self, &hash).cloned()
+    }
+}
+
+/// Returns the quotient and remainder of `a` by `b`, as expected by the kernel
+/// and the arithmetic table for `DIV` and `MOD`: they satisfy `a == q * b + r`
+/// with `r < b`, and division by zero yields `q = r = 0`.
+pub fn div_mod_hint(a: U256, b: U256) -> (U256, U256) {
+    if b.is_zero() {
+        (U256::zero(), U256::zero())
+    } else {
+        a.div_mod(b)
+    }
+}
+
+/// The default `ProverInputSource`, which computes every prover input from
+/// the in-memory generation inputs and tries.
+#[derive(Debug, Default)]
+pub(crate) struct InMemoryProverInputs;
+
+impl ProverInputSource for InMemoryProverInputs {}
+
+/// A `ProverInputSource` replaying prover inputs recorded from a previous run,
+/// in order. Once they are exhausted, prover inputs are computed again from
+/// the in-memory generation inputs.
+#[derive(Debug, Default)]
+pub(crate) struct ReplayedProverInputs {
+    inputs: RefCell<VecDeque<U256>>,
+}
+
+impl ReplayedProverInputs {
+    pub(crate) fn new(inputs: Vec<U256>) -> Self {
+        Self {
+            inputs: RefCell::new(inputs.into()),
+        }
+    }
+
+    /// Returns the number of recorded prover inputs not replayed yet.
+    pub(crate) fn remaining(&self) -> usize {
+        self.inputs.borrow().len()
+    }
+}
+
+impl ProverInputSource for ReplayedProverInputs {
+    fn prover_input(&self, _request: &ProverInputRequest) -> Option<U256> {
+        self.inputs.borrow_mut().pop_front()
+    }
+}
+
 impl<F: Field> GenerationState<F> {
     pub(crate) fn prover_input(&mut self, input_fn: &ProverInputFn) -> Result<U256, ProgramError> {
+        let request = ProverInputRequest {
+            input_fn,
+            context: self.registers.context,
+            program_counter: self.registers.program_counter,
+        };
+        let source_input = self.prover_input_source.prover_input(&request);
+        let input = match source_input {
+            Some(input) => input,
+            None => self.compute_prover_input(input_fn)?,
+        };
+        if let Some(log) = self.prover_input_log.as_mut() {
+            log.push(input);
+        }
+        Ok(input)
+    }
+
+    fn compute_prover_input(&mut self, input_fn: &ProverInputFn) -> Result<U256, ProgramError> {
         match input_fn.0[0].as_str() {
             "no_txn" => self.no_txn(),
             "trie_ptr" => self.run_trie_ptr(input_fn),
@@ -55,6 +156,7 @@ impl<F: Field> GenerationState<F> {
             "num_bits" => self.run_num_bits(),
             "jumpdest_table" => self.run_jumpdest_table(input_fn),
             "access_lists" => self.run_access_lists(input_fn),
+            "max_memory_bytes" => Ok(self.max_memory_bytes.into()),
             _ => Err(ProgramError::ProverInputError(InvalidFunction)),
         }
     }
@@ -149,11 +251,23 @@ impl<F: Field> GenerationState<F> {
         let context = stack_peek(self, 1)? >> CONTEXT_SCALING_FACTOR;
         let context = u256_to_usize(context)?;
         let mut address = MemoryAddress::new(context, Segment::Code, 0);
+        let codehash = H256::from_uint(&codehash);
+        if !self.inputs.contract_code.contains_key(&codehash) {
+            // Keep the fetched code, so that it is only fetched once.
+            if let Some(code) = self
+                .code_provider
+                .as_ref()
+                .and_then(|provider| provider.get(codehash))
+            {
+                self.inputs.contract_code.insert(codehash, code);
+            }
+        }
         let code = self
             .inputs
             .contract_code
-            .get(&H256::from_uint(&codehash))
+            .get(&codehash)
             .ok_or(ProgramError::ProverInputError(CodeHashNotFound))?;
+        self.accessed_code_hashes.insert(codehash);
         for &byte in code {
             self.memory.set(address, byte.into());
             address.increment();
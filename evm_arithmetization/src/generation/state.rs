@@ -1,19 +1,34 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem::size_of;
+use std::rc::Rc;
 
 use anyhow::{anyhow, bail};
 use ethereum_types::{Address, BigEndianHash, H160, H256, U256};
 use itertools::Itertools;
 use keccak_hash::keccak;
+use mpt_trie::nibbles::Nibbles;
+use mpt_trie::partial_trie::HashedPartialTrie;
 use plonky2::field::types::Field;
 
-use super::mpt::{load_all_mpts, TrieRootPtrs};
-use super::TrieInputs;
+use super::mpt::{load_all_mpts, state_accounts, AccountRlp, HashNodePolicy, TrieRootPtrs};
+use super::{GenerationError, InvalidTxnReason, SegmentCapacities, TrieInputs};
 use crate::byte_packing::byte_packing_stark::BytePackingOp;
-use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::aggregator::{KERNEL, KERNEL_HOOKS};
+use crate::cpu::kernel::assembler::Kernel;
 use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
+use crate::cpu::kernel::constants::MIN_MAX_MEMORY_BYTES;
+use crate::cpu::kernel::interpreter::get_mnemonic;
 use crate::cpu::stack::MAX_USER_STACK_SIZE;
+use crate::generation::prover_input::{
+    CodeProvider, InMemoryProverInputs, ProverInputSource, ReplayedProverInputs,
+};
 use crate::generation::rlp::all_rlp_prover_inputs_reversed;
+use crate::generation::trie_extractor::{
+    get_state_trie, get_storage_trie, read_state_trie_value, read_trie, read_trie_value,
+    AccountTrieRecord,
+};
 use crate::generation::CpuColumnsView;
 use crate::generation::GenerationInputs;
 use crate::keccak_sponge::columns::KECCAK_WIDTH_BYTES;
@@ -152,11 +167,21 @@ pub(crate) trait State<F: Field> {
         Self: Sized,
     {
         let halt_offsets = self.get_halt_offsets();
+        let hooks = self.get_generation_state().hooks;
 
         loop {
             let registers = self.get_registers();
             let pc = registers.program_counter;
 
+            let clock = self.get_clock();
+            self.get_generation_state().txn_cycles.check_limit(clock)?;
+            let is_route_txn = hooks.route_txn == Some(pc);
+            if registers.is_kernel && (is_route_txn || hooks.txn_after == Some(pc)) {
+                self.get_mut_generation_state()
+                    .txn_cycles
+                    .record_boundary(is_route_txn, clock);
+            }
+
             let halt = registers.is_kernel && halt_offsets.contains(&pc);
 
             // If we've reached the kernel's halt routine, halt.
@@ -173,6 +198,10 @@ pub(crate) trait State<F: Field> {
                 }
             }
 
+            if let Some(reason) = self.get_generation_state().rejected_txn {
+                return Err(GenerationError::InvalidTxn(reason).into());
+            }
+
             self.transition()?;
         }
     }
@@ -197,6 +226,9 @@ pub(crate) trait State<F: Field> {
         let (row, _) = self.base_row();
         generate_exception(exc_code, self, row)
             .map_err(|e| anyhow!("Exception handling failed with error: {:?}", e))?;
+        self.get_mut_generation_state()
+            .op_class_cycles
+            .record_exception();
 
         self.apply_ops(checkpoint);
 
@@ -208,6 +240,10 @@ pub(crate) trait State<F: Field> {
         Self: Transition<F>,
         Self: Sized,
     {
+        if self.get_generation_state().execution_trace.is_some() {
+            self.get_mut_generation_state().trace_instruction();
+        }
+
         let checkpoint = self.checkpoint();
         let result = self.try_perform_instruction();
 
@@ -295,6 +331,155 @@ pub(crate) struct GenerationState<F: Field> {
     /// the code (not necessarily pointing to an opcode) such that for every
     /// j in [i, i+32] it holds that code[j] < 0x7f - j + i.
     pub(crate) jumpdest_table: Option<HashMap<usize, Vec<usize>>>,
+
+    /// Number of CPU cycles spent in each opcode class, for performance
+    /// analysis.
+    pub(crate) op_class_cycles: OpClassCycles,
+
+    /// Number of CPU cycles spent in each transaction, with an optional cap.
+    pub(crate) txn_cycles: TxnCycles,
+
+    /// The store queried for the codes missing from
+    /// `GenerationInputs::contract_code`, if any. It is shared with the states
+    /// cloned from this one.
+    pub(crate) code_provider: Option<Rc<dyn CodeProvider>>,
+
+    /// The hashes of the codes loaded by the kernel from
+    /// `GenerationInputs::contract_code`. States cloned from this one don't
+    /// record the codes they load.
+    pub(crate) accessed_code_hashes: HashSet<H256>,
+
+    /// The bound past which memory offsets fault, loaded by the kernel into
+    /// `GlobalMetadata::MaxMemoryBytes`.
+    pub(crate) max_memory_bytes: u64,
+
+    /// The source queried for prover inputs before computing them from the
+    /// in-memory inputs. It is shared with the states cloned from this one.
+    pub(crate) prover_input_source: Rc<dyn ProverInputSource>,
+
+    /// If set, every prover input returned to the kernel is appended to this
+    /// log, so that the run can be reproduced with `ReplayedProverInputs`.
+    /// States cloned from this one don't record their prover inputs.
+    pub(crate) prover_input_log: Option<Vec<U256>>,
+
+    /// If set, the storage reads and writes of the transaction are appended to
+    /// this log. States cloned from this one don't record their accesses.
+    pub(crate) storage_access_log: Option<StorageAccessLog>,
+
+    /// If set, every user instruction executed is appended to this trace.
+    /// States cloned from this one don't record their instructions.
+    pub(crate) execution_trace: Option<Vec<TraceStep>>,
+
+    /// Set when the validity checks of the kernel reject the transaction for a
+    /// reason with a typed rejection.
+    pub(crate) rejected_txn: Option<InvalidTxnReason>,
+
+    /// The offsets of the kernel's halt routines, at which the CPU stops.
+    pub(crate) halt_offsets: Vec<usize>,
+
+    /// The offsets of the labels of the kernel being run which the CPU
+    /// simulation hooks into.
+    pub(crate) hooks: KernelHooks,
+}
+
+/// The offsets of the kernel labels which the CPU simulation needs, resolved
+/// once from the kernel being run. Labels which a kernel doesn't define are
+/// not hooked into, except for `main` and `halt`, which are required.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct KernelHooks {
+    pub(crate) main: usize,
+    pub(crate) halt: usize,
+    pub(crate) route_txn: Option<usize>,
+    pub(crate) txn_after: Option<usize>,
+    observe_new_address: Option<usize>,
+    observe_new_contract: Option<usize>,
+    insert_accessed_storage_keys: Option<usize>,
+    invalid_txn: Option<usize>,
+    invalid_txn_1: Option<usize>,
+    /// The validity checks of a transaction run from `process_normalized_txn`
+    /// up to `buy_gas`.
+    process_normalized_txn: Option<usize>,
+    buy_gas: Option<usize>,
+}
+
+impl KernelHooks {
+    /// Resolves the labels of the given kernel, failing with
+    /// `GenerationError::MissingKernelLabel` if it lacks `main` or `halt`.
+    pub(crate) fn new(kernel: &Kernel) -> Result<Self, GenerationError> {
+        let label = |name: &str| kernel.global_labels.get(name).copied();
+        let required =
+            |name: &'static str| label(name).ok_or(GenerationError::MissingKernelLabel(name));
+
+        Ok(Self {
+            main: required("main")?,
+            halt: required("halt")?,
+            route_txn: label("route_txn"),
+            txn_after: label("txn_after"),
+            observe_new_address: label("observe_new_address"),
+            observe_new_contract: label("observe_new_contract"),
+            insert_accessed_storage_keys: label("insert_accessed_storage_keys"),
+            invalid_txn: label("invalid_txn"),
+            invalid_txn_1: label("invalid_txn_1"),
+            process_normalized_txn: label("process_normalized_txn"),
+            buy_gas: label("buy_gas"),
+        })
+    }
+
+    /// Whether the given kernel offset is part of the validity checks of a
+    /// transaction.
+    fn is_txn_validity_check(&self, offset: usize) -> bool {
+        match (self.process_normalized_txn, self.buy_gas) {
+            (Some(start), Some(end)) => (start..end).contains(&offset),
+            _ => false,
+        }
+    }
+}
+
+/// A read or write of a storage slot by a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageAccess {
+    /// An `SLOAD` of `value` from `slot` of `address`.
+    Read {
+        address: Address,
+        slot: U256,
+        value: U256,
+    },
+    /// An `SSTORE` of `new_value` to `slot` of `address`, which held
+    /// `old_value`.
+    Write {
+        address: Address,
+        slot: U256,
+        old_value: U256,
+        new_value: U256,
+    },
+}
+
+/// The maximum number of stack items recorded in a `TraceStep`.
+pub const TRACE_STACK_ITEMS: usize = 4;
+
+/// A user instruction, as recorded in the execution trace before executing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    pub pc: usize,
+    pub opcode: u8,
+    /// The gas left in the current context.
+    pub gas_remaining: u64,
+    /// The top `TRACE_STACK_ITEMS` items of the stack, or fewer if the stack
+    /// is shorter, top first.
+    pub stack_top: Vec<U256>,
+    /// The call depth of the current context. As in `debug_traceTransaction`,
+    /// the top-level call of the transaction is at depth 1.
+    pub depth: usize,
+}
+
+/// The storage accesses of a transaction, in execution order, including those
+/// of internal calls. Accesses made by calls which are later reverted are kept.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StorageAccessLog {
+    pub(crate) accesses: Vec<StorageAccess>,
+    /// Set while the kernel executes an `SLOAD` or an `SSTORE`, to the written
+    /// value in the case of an `SSTORE`.
+    pending: Option<Option<U256>>,
 }
 
 impl<F: Field> GenerationState<F> {
@@ -308,6 +493,16 @@ impl<F: Field> GenerationState<F> {
         trie_roots_ptrs
     }
     pub(crate) fn new(inputs: GenerationInputs, kernel_code: &[u8]) -> Result<Self, ProgramError> {
+        Self::new_with_capacities(inputs, kernel_code, SegmentCapacities::default())
+    }
+
+    /// Creates a new `GenerationState`, pre-allocating the memory segments of
+    /// each context according to `segment_capacities`.
+    pub(crate) fn new_with_capacities(
+        inputs: GenerationInputs,
+        kernel_code: &[u8],
+        segment_capacities: SegmentCapacities,
+    ) -> Result<Self, ProgramError> {
         let rlp_prover_inputs =
             all_rlp_prover_inputs_reversed(inputs.clone().signed_txn.as_ref().unwrap_or(&vec![]));
         let withdrawal_prover_inputs = all_withdrawals_prover_inputs_reversed(&inputs.withdrawals);
@@ -316,7 +511,7 @@ impl<F: Field> GenerationState<F> {
         let mut state = Self {
             inputs: inputs.clone(),
             registers: Default::default(),
-            memory: MemoryState::new(kernel_code),
+            memory: MemoryState::new_with_capacities(kernel_code, segment_capacities),
             traces: Traces::default(),
             rlp_prover_inputs,
             withdrawal_prover_inputs,
@@ -328,6 +523,18 @@ impl<F: Field> GenerationState<F> {
                 receipt_root_ptr: 0,
             },
             jumpdest_table: None,
+            op_class_cycles: OpClassCycles::default(),
+            txn_cycles: TxnCycles::default(),
+            code_provider: None,
+            accessed_code_hashes: HashSet::new(),
+            max_memory_bytes: MIN_MAX_MEMORY_BYTES.1,
+            prover_input_source: Rc::new(InMemoryProverInputs),
+            prover_input_log: None,
+            storage_access_log: None,
+            execution_trace: None,
+            rejected_txn: None,
+            halt_offsets: vec![KERNEL_HOOKS.halt],
+            hooks: *KERNEL_HOOKS,
         };
         let trie_root_ptrs = state.preinitialize_mpts(&inputs.tries);
 
@@ -335,24 +542,350 @@ impl<F: Field> GenerationState<F> {
         Ok(state)
     }
 
+    /// Same as `new_with_capacities`, but running the given kernel instead of
+    /// `KERNEL`. The CPU starts at the kernel's `main` label and stops at its
+    /// `halt` label, and fails with `GenerationError::MissingKernelLabel` if
+    /// either is missing.
+    pub(crate) fn new_with_kernel(
+        inputs: GenerationInputs,
+        kernel: &Kernel,
+        segment_capacities: SegmentCapacities,
+    ) -> anyhow::Result<Self> {
+        let hooks = KernelHooks::new(kernel)?;
+        let mut state = Self::new_with_capacities(inputs, &kernel.code, segment_capacities)
+            .map_err(|err| anyhow!("Failed to parse all the initial prover inputs: {:?}", err))?;
+        state.registers.program_counter = hooks.main;
+        state.halt_offsets = vec![hooks.halt];
+        state.hooks = hooks;
+        Ok(state)
+    }
+
+    /// Creates a new `GenerationState` whose memory is seeded with the given
+    /// values before any transition. This is a helper for tests which start
+    /// from a given memory.
+    ///
+    /// The seeded values do not generate any memory operation. The memory
+    /// table only accepts nonzero initial values in the code segment and in
+    /// the trie data of context 0, so a first read of any other seeded address
+    /// can be executed but not proven.
+    pub(crate) fn with_initial_memory(
+        inputs: GenerationInputs,
+        kernel_code: &[u8],
+        mem: Vec<(MemoryAddress, U256)>,
+    ) -> Result<Self, ProgramError> {
+        let mut state = Self::new(inputs, kernel_code)?;
+        for (address, value) in mem {
+            state.memory.set(address, value);
+        }
+        Ok(state)
+    }
+
+    /// Sets the source queried for prover inputs.
+    pub(crate) fn set_prover_input_source(&mut self, source: Rc<dyn ProverInputSource>) {
+        self.prover_input_source = source;
+    }
+
+    /// Sets the store queried for the codes missing from
+    /// `GenerationInputs::contract_code`.
+    pub(crate) fn set_code_provider(&mut self, code_provider: Rc<dyn CodeProvider>) {
+        self.code_provider = Some(code_provider);
+    }
+
+    /// Starts recording the prover inputs returned to the kernel.
+    pub(crate) fn record_prover_inputs(&mut self) {
+        self.prover_input_log = Some(vec![]);
+    }
+
+    /// Returns the prover inputs recorded since `record_prover_inputs` was
+    /// called, and stops recording.
+    pub(crate) fn take_prover_input_log(&mut self) -> Option<Vec<U256>> {
+        self.prover_input_log.take()
+    }
+
+    /// Starts recording the storage reads and writes of the transaction.
+    pub(crate) fn record_storage_accesses(&mut self) {
+        self.storage_access_log = Some(StorageAccessLog::default());
+    }
+
+    /// Returns the storage accesses recorded since `record_storage_accesses`
+    /// was called, and stops recording.
+    pub(crate) fn take_storage_access_log(&mut self) -> Option<StorageAccessLog> {
+        self.storage_access_log.take()
+    }
+
+    /// Starts recording the user instructions executed by the CPU.
+    pub(crate) fn record_execution_trace(&mut self) {
+        self.execution_trace = Some(vec![]);
+    }
+
+    /// Returns the instructions recorded since `record_execution_trace` was
+    /// called, and stops recording.
+    pub(crate) fn take_execution_trace(&mut self) -> Option<Vec<TraceStep>> {
+        self.execution_trace.take()
+    }
+
+    /// Appends the next instruction to the execution trace, if it is a user
+    /// instruction.
+    fn trace_instruction(&mut self) {
+        let registers = self.registers;
+        if registers.is_kernel {
+            return;
+        }
+
+        let opcode = self.memory.get_with_init(MemoryAddress::new(
+            registers.code_context(),
+            Segment::Code,
+            registers.program_counter,
+        ));
+        let gas_limit = self.memory.get_with_init(MemoryAddress::new(
+            registers.context,
+            Segment::ContextMetadata,
+            ContextMetadata::GasLimit.unscale(),
+        ));
+        let stack_top = (0..registers.stack_len.min(TRACE_STACK_ITEMS))
+            .map(|i| stack_peek(self, i).unwrap_or_default())
+            .collect();
+        let depth = self
+            .memory
+            .read_global_metadata(GlobalMetadata::CallStackDepth);
+
+        if let Some(trace) = self.execution_trace.as_mut() {
+            trace.push(TraceStep {
+                pc: registers.program_counter,
+                opcode: opcode.low_u32() as u8,
+                gas_remaining: gas_limit.low_u64().saturating_sub(registers.gas_used),
+                stack_top,
+                depth: depth.low_u64() as usize,
+            });
+        }
+    }
+
+    /// Returns the storage trie of `address` as currently stored in the kernel
+    /// memory, or `None` if the account is not in the state trie.
+    pub(crate) fn final_storage_trie(
+        &self,
+        address: Address,
+    ) -> Result<Option<HashedPartialTrie>, ProgramError> {
+        let state_trie_ptr = u256_to_usize(
+            self.memory
+                .read_global_metadata(GlobalMetadata::StateTrieRoot),
+        )?;
+        let accounts = read_trie(&self.memory, state_trie_ptr, read_state_trie_value)?;
+        accounts
+            .get(&Nibbles::from_h256_be(keccak(address)))
+            .map(|account| get_storage_trie(&self.memory, account.storage_ptr))
+            .transpose()
+    }
+
+    /// Called when the validity checks of the transaction jump to `invalid_txn`
+    /// or `invalid_txn_1`. Returns the reason of the rejection if the failed
+    /// check has a typed rejection, or `None` if the transaction is to be
+    /// processed as a failed one.
+    fn txn_rejection(
+        &self,
+        is_invalid_txn_1: bool,
+    ) -> Result<Option<InvalidTxnReason>, ProgramError> {
+        let sender = self.txn_sender_account()?;
+        if is_invalid_txn_1 {
+            // The nonce check failed if the nonces differ, otherwise the sender code
+            // check, which comes after it, failed.
+            let nonce = self.txn_field(NormalizedTxnField::Nonce);
+            return Ok(Some(if nonce != sender.nonce.into() {
+                InvalidTxnReason::NonceMismatch
+            } else {
+                InvalidTxnReason::SenderHasCode
+            }));
+        }
+
+        // The balance check failed if the gas limit checks, which come before it,
+        // passed. The chain ID check comes after it.
+        let gas_limit = self.txn_field(NormalizedTxnField::GasLimit);
+        let intrinsic_gas = self.txn_field(NormalizedTxnField::IntrinsicGas);
+        let block_gas_limit = self
+            .memory
+            .read_global_metadata(GlobalMetadata::BlockGasLimit);
+        let cost = self
+            .txn_field(NormalizedTxnField::ComputedFeePerGas)
+            .overflowing_mul(gas_limit)
+            .0
+            .overflowing_add(self.txn_field(NormalizedTxnField::Value))
+            .0;
+        let is_balance_check =
+            gas_limit >= intrinsic_gas && block_gas_limit >= gas_limit && sender.balance < cost;
+        Ok(is_balance_check.then_some(InvalidTxnReason::InsufficientBalance))
+    }
+
+    /// Returns the given field of the transaction being processed.
+    fn txn_field(&self, field: NormalizedTxnField) -> U256 {
+        // These fields are already scaled by their respective segment.
+        self.memory
+            .get_with_init(MemoryAddress::new_bundle(U256::from(field as usize)).unwrap())
+    }
+
+    /// Returns the account of the sender of the transaction being processed,
+    /// as currently stored in the kernel memory. A sender which is not in the
+    /// state trie has an empty account.
+    fn txn_sender_account(&self) -> Result<AccountTrieRecord, ProgramError> {
+        let sender = H160::from(H256::from_uint(&self.txn_field(NormalizedTxnField::Origin)));
+        let state_trie_ptr = u256_to_usize(
+            self.memory
+                .read_global_metadata(GlobalMetadata::StateTrieRoot),
+        )?;
+        let account = read_trie_value(
+            &self.memory,
+            state_trie_ptr,
+            Nibbles::from_h256_be(keccak(sender)),
+            4,
+            read_state_trie_value,
+        )?;
+        Ok(account.unwrap_or(AccountTrieRecord {
+            nonce: 0,
+            balance: U256::zero(),
+            storage_ptr: 0,
+            code_hash: keccak([]),
+        }))
+    }
+
+    /// Returns the accounts of the state trie as currently stored in the kernel
+    /// memory, with their state keys, in key order. Once the transactions have
+    /// been processed, this is the final world state, which can be compared
+    /// with the state dump of another client. Accounts behind hash nodes are
+    /// unknown, and omitted.
+    pub(crate) fn final_accounts(&self) -> Result<Vec<(H256, AccountRlp)>, ProgramError> {
+        let state_trie_ptr = u256_to_usize(
+            self.memory
+                .read_global_metadata(GlobalMetadata::StateTrieRoot),
+        )?;
+        let state_trie: HashedPartialTrie = get_state_trie(&self.memory, state_trie_ptr)?;
+        state_accounts(&state_trie, HashNodePolicy::Skip)
+            .collect::<Result<_, _>>()
+            .map_err(|_| ProgramError::InvalidRlp)
+    }
+
+    /// Called when the user code issues the syscall `opcode`. If it is an
+    /// `SLOAD` or an `SSTORE`, the access is logged once the kernel has read
+    /// the current value of the slot.
+    pub(crate) fn observe_storage_syscall(&mut self, opcode: u8) -> Result<(), ProgramError> {
+        if self.storage_access_log.is_none() {
+            return Ok(());
+        }
+
+        let pending = match opcode {
+            0x54 => Some(None),
+            0x55 => Some(Some(stack_peek(self, 1)?)),
+            _ => return Ok(()),
+        };
+        if let Some(log) = self.storage_access_log.as_mut() {
+            log.pending = pending;
+        }
+
+        Ok(())
+    }
+
     /// Updates `program_counter`, and potentially adds some extra handling if
     /// we're jumping to a special location.
     pub(crate) fn jump_to(&mut self, dst: usize) -> Result<(), ProgramError> {
+        let src = self.registers.program_counter;
         self.registers.program_counter = dst;
-        if dst == KERNEL.global_labels["observe_new_address"] {
+        let hooks = self.hooks;
+        let dst = Some(dst);
+        if dst == hooks.observe_new_address {
             let tip_u256 = stack_peek(self, 0)?;
             let tip_h256 = H256::from_uint(&tip_u256);
             let tip_h160 = H160::from(tip_h256);
             self.observe_address(tip_h160);
-        } else if dst == KERNEL.global_labels["observe_new_contract"] {
+        } else if dst == hooks.observe_new_contract {
             let tip_u256 = stack_peek(self, 0)?;
             let tip_h256 = H256::from_uint(&tip_u256);
             self.observe_contract(tip_h256)?;
+        } else if dst == hooks.insert_accessed_storage_keys {
+            // The stack is `addr, slot, current_value, retdest`.
+            if let Some(new_value) = self
+                .storage_access_log
+                .as_mut()
+                .and_then(|log| log.pending.take())
+            {
+                let address = H160::from(H256::from_uint(&stack_peek(self, 0)?));
+                let slot = stack_peek(self, 1)?;
+                let value = stack_peek(self, 2)?;
+                let access = match new_value {
+                    None => StorageAccess::Read {
+                        address,
+                        slot,
+                        value,
+                    },
+                    Some(new_value) => StorageAccess::Write {
+                        address,
+                        slot,
+                        old_value: value,
+                        new_value,
+                    },
+                };
+                if let Some(log) = self.storage_access_log.as_mut() {
+                    log.accesses.push(access);
+                }
+            }
+        } else if (dst == hooks.invalid_txn || dst == hooks.invalid_txn_1)
+            && self.registers.is_kernel
+            && hooks.is_txn_validity_check(src)
+        {
+            self.rejected_txn = self.txn_rejection(dst == hooks.invalid_txn_1)?;
         }
 
         Ok(())
     }
 
+    /// Sets the calldata of the given context in a single pass, along with its
+    /// size in the context metadata.
+    ///
+    /// This does not generate any memory operation, and is only meant to
+    /// initialize a state before execution.
+    #[cfg(test)]
+    pub(crate) fn set_calldata(&mut self, context: usize, calldata: &[u8]) {
+        self.memory.set(
+            MemoryAddress::new(
+                context,
+                Segment::ContextMetadata,
+                ContextMetadata::CalldataSize.unscale(),
+            ),
+            calldata.len().into(),
+        );
+        self.memory.contexts[context].segments[Segment::Calldata.unscale()].content = calldata
+            .iter()
+            .map(|&byte| Some(U256::from(byte)))
+            .collect();
+    }
+
+    /// Returns the hashes of all the codes the kernel loaded from
+    /// `GenerationInputs::contract_code` so far. Once the run is over, the
+    /// other entries of `contract_code` can be dropped from the inputs.
+    pub(crate) fn accessed_code_hashes(&self) -> HashSet<H256> {
+        self.accessed_code_hashes.clone()
+    }
+
+    /// Returns the `len` bytes of memory starting at `address`. These
+    /// crate-internal helpers, used by the witness generation and by tests, are
+    /// not part of the public API. Byte-addressed segments, such as the main
+    /// memory, hold one byte per cell, as written by `MSTORE8`. Unset cells
+    /// read as zero.
+    pub(crate) fn read_bytes(&self, address: MemoryAddress, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| {
+                let cell = MemoryAddress {
+                    virt: address.virt + i,
+                    ..address
+                };
+                self.memory.get_with_init(cell).low_u32() as u8
+            })
+            .collect()
+    }
+
+    /// Returns the word of memory starting at `address`, as read by `MLOAD`,
+    /// i.e. the big-endian value of the 32 bytes starting at `address`.
+    pub(crate) fn read_u256(&self, address: MemoryAddress) -> U256 {
+        U256::from_big_endian(&self.read_bytes(address, 32))
+    }
+
     /// Observe the given address, so that we will be able to recognize the
     /// associated state key. This is just for debugging purposes.
     pub(crate) fn observe_address(&mut self, address: Address) {
@@ -374,11 +907,10 @@ impl<F: Field> GenerationState<F> {
         let returndata_size_addr =
             MemoryAddress::new(ctx, Segment::ContextMetadata, returndata_offset);
         let returndata_size = u256_to_usize(self.memory.get_with_init(returndata_size_addr))?;
-        let code = self.memory.contexts[ctx].segments[Segment::Returndata.unscale()].content
-            [..returndata_size]
-            .iter()
-            .map(|x| x.unwrap_or_default().low_u32() as u8)
-            .collect::<Vec<_>>();
+        let code = self.read_bytes(
+            MemoryAddress::new(ctx, Segment::Returndata, 0),
+            returndata_size,
+        );
         debug_assert_eq!(keccak(&code), codehash);
 
         self.inputs.contract_code.insert(codehash, code);
@@ -415,6 +947,18 @@ impl<F: Field> GenerationState<F> {
                 receipt_root_ptr: 0,
             },
             jumpdest_table: None,
+            op_class_cycles: OpClassCycles::default(),
+            txn_cycles: TxnCycles::default(),
+            code_provider: self.code_provider.clone(),
+            accessed_code_hashes: HashSet::new(),
+            max_memory_bytes: self.max_memory_bytes,
+            prover_input_source: self.prover_input_source.clone(),
+            prover_input_log: None,
+            storage_access_log: None,
+            execution_trace: None,
+            rejected_txn: None,
+            halt_offsets: self.halt_offsets.clone(),
+            hooks: self.hooks,
         }
     }
 }
@@ -493,7 +1037,7 @@ impl<F: Field> State<F> for GenerationState<F> {
     }
 
     fn get_halt_offsets(&self) -> Vec<usize> {
-        vec![KERNEL.global_labels["halt"]]
+        self.halt_offsets.clone()
     }
 
     fn try_perform_instruction(&mut self) -> Result<Operation, ProgramError> {
@@ -530,7 +1074,10 @@ impl<F: Field> State<F> for GenerationState<F> {
             row.general.stack_mut().stack_inv_aux = F::ONE;
         }
 
-        self.perform_state_op(opcode, op, row)
+        let op = self.perform_state_op(opcode, op, row)?;
+        self.op_class_cycles.record(registers.is_kernel, opcode, op);
+
+        Ok(op)
     }
 }
 
@@ -588,6 +1135,108 @@ impl<F: Field> Transition<F> for GenerationState<F> {
     }
 }
 
+/// Counts the CPU cycles spent in each opcode class.
+///
+/// User instructions are attributed to their own class, or to their mnemonic
+/// for syscalls. Kernel instructions are attributed to the syscall or
+/// exception they are handling, if any, so that e.g. all the cycles spent
+/// serving an `SLOAD` are counted together.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OpClassCycles {
+    pub(crate) cycles: BTreeMap<&'static str, usize>,
+    /// The syscall or exception currently being handled by the kernel.
+    pending: Option<&'static str>,
+}
+
+impl OpClassCycles {
+    fn record(&mut self, is_kernel: bool, opcode: u8, op: Operation) {
+        let class = match op {
+            _ if is_kernel => self.pending.unwrap_or("kernel"),
+            Operation::Syscall(..) => {
+                let mnemonic = get_mnemonic(opcode);
+                self.pending = Some(mnemonic);
+                mnemonic
+            }
+            Operation::Iszero
+            | Operation::Not
+            | Operation::Eq
+            | Operation::BinaryArithmetic(_)
+            | Operation::TernaryArithmetic(_) => "arithmetic",
+            Operation::BinaryLogic(_) => "logic",
+            Operation::KeccakGeneral => "keccak",
+            Operation::ProverInput => "prover input",
+            Operation::Pop | Operation::Push(_) | Operation::Dup(_) | Operation::Swap(_) => "stack",
+            Operation::Jump | Operation::Jumpi | Operation::Pc | Operation::Jumpdest => {
+                "control flow"
+            }
+            Operation::GetContext | Operation::SetContext => "context",
+            Operation::Mload32Bytes
+            | Operation::Mstore32Bytes(_)
+            | Operation::MloadGeneral
+            | Operation::MstoreGeneral => "memory",
+            Operation::ExitKernel => "kernel",
+        };
+        *self.cycles.entry(class).or_default() += 1;
+
+        if op == Operation::ExitKernel {
+            self.pending = None;
+        }
+    }
+
+    pub(crate) fn record_exception(&mut self) {
+        *self.cycles.entry("exception").or_default() += 1;
+        self.pending = Some("exception");
+    }
+
+    pub(crate) fn record_padding(&mut self) {
+        *self.cycles.entry("padding").or_default() += 1;
+    }
+
+    /// Returns the total number of recorded cycles.
+    pub(crate) fn total(&self) -> usize {
+        self.cycles.values().sum()
+    }
+}
+
+/// Records the clock when the kernel starts and finishes processing each
+/// transaction, and optionally rejects transactions using too many cycles.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TxnCycles {
+    /// The clock at the start and at the end of each transaction, in order.
+    /// Its length is odd while a transaction is being processed.
+    boundaries: Vec<usize>,
+    /// The maximum number of CPU cycles a single transaction may use.
+    pub(crate) max_cycles: Option<usize>,
+}
+
+impl TxnCycles {
+    fn record_boundary(&mut self, is_start: bool, clock: usize) {
+        // The end of a transaction can be visited again when resuming a run
+        // halted there, so we only record boundaries that alternate.
+        if is_start == (self.boundaries.len() % 2 == 0) {
+            self.boundaries.push(clock);
+        }
+    }
+
+    fn check_limit(&self, clock: usize) -> Result<(), GenerationError> {
+        let in_txn = self.boundaries.len() % 2 == 1;
+        match (self.max_cycles, self.boundaries.last()) {
+            (Some(limit), Some(&start)) if in_txn && clock - start > limit => {
+                Err(GenerationError::TxnCycleLimitExceeded { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of CPU cycles spent in each completed transaction.
+    pub(crate) fn cycles(&self) -> Vec<usize> {
+        self.boundaries
+            .chunks_exact(2)
+            .map(|bounds| bounds[1] - bounds[0])
+            .collect()
+    }
+}
+
 pub(crate) struct GenerationStateCheckpoint {
     pub(crate) registers: RegistersState,
     pub(crate) traces: TraceCheckpoint,
@@ -60,7 +60,7 @@ pub(crate) fn read_trie_helper<V>(
     prefix: Nibbles,
     res: &mut HashMap<Nibbles, V>,
 ) -> Result<(), ProgramError> {
-    let load = |offset| memory.contexts[0].segments[Segment::TrieData as usize].content[offset];
+    let load = |offset| memory.contexts[0].segments[Segment::TrieData.unscale()].content[offset];
     let load_slice_from = |init_offset| {
         &memory.contexts[0].segments[Segment::TrieData.unscale()].content[init_offset..]
     };
@@ -116,6 +116,56 @@ pub(crate) fn read_trie_helper<V>(
     }
 }
 
+/// Returns the value of `key` in the trie at `ptr`, made of the `value_len`
+/// words read by `read_value`, or `None` if the trie doesn't have this key or
+/// if it is behind a hash node. Unlike `read_trie`, this also reads the trie
+/// data which has been preinitialized but not copied into memory yet.
+pub(crate) fn read_trie_value<V>(
+    memory: &MemoryState,
+    mut ptr: usize,
+    mut key: Nibbles,
+    value_len: usize,
+    read_value: fn(&[Option<U256>]) -> Result<V, ProgramError>,
+) -> Result<Option<V>, ProgramError> {
+    let load = |offset| memory.get_with_init(MemoryAddress::new(0, Segment::TrieData, offset));
+
+    let value_ptr = loop {
+        let trie_type = PartialTrieType::all()[u256_to_usize(load(ptr))?];
+        match trie_type {
+            PartialTrieType::Empty | PartialTrieType::Hash => return Ok(None),
+            PartialTrieType::Branch if key.is_empty() => break u256_to_usize(load(ptr + 17))?,
+            PartialTrieType::Branch => {
+                let nibble = key.pop_next_nibble_front();
+                ptr = u256_to_usize(load(ptr + 1 + nibble as usize))?;
+            }
+            PartialTrieType::Extension | PartialTrieType::Leaf => {
+                let nibbles = Nibbles {
+                    count: u256_to_usize(load(ptr + 1))?,
+                    packed: load(ptr + 2).into(),
+                };
+                if key.count < nibbles.count || key.get_next_nibbles(nibbles.count) != nibbles {
+                    return Ok(None);
+                }
+                key = key.truncate_n_nibbles_front(nibbles.count);
+                let next_ptr = u256_to_usize(load(ptr + 3))?;
+                match trie_type {
+                    PartialTrieType::Leaf if key.is_empty() => break next_ptr,
+                    PartialTrieType::Leaf => return Ok(None),
+                    _ => ptr = next_ptr,
+                }
+            }
+        }
+    };
+    if value_ptr == 0 {
+        return Ok(None);
+    }
+
+    let value = (value_ptr..value_ptr + value_len)
+        .map(|offset| Some(load(offset)))
+        .collect::<Vec<_>>();
+    read_value(&value).map(Some)
+}
+
 pub(crate) fn read_receipt_trie_value(
     slice: &[Option<U256>],
 ) -> Result<(Option<u8>, LegacyReceiptRlp), ProgramError> {
@@ -194,9 +244,7 @@ pub(crate) fn read_state_rlp_value(
     slice: &MemoryValues,
 ) -> Result<Vec<u8>, ProgramError> {
     let storage_trie: HashedPartialTrie =
-        get_trie(memory, slice[2].unwrap_or_default().as_usize(), |_, x| {
-            Ok(rlp::encode(&read_storage_trie_value(x)).to_vec())
-        })?;
+        get_storage_trie(memory, slice[2].unwrap_or_default().as_usize())?;
     let account = AccountRlp {
         nonce: slice[0].unwrap_or_default(),
         balance: slice[1].unwrap_or_default(),
@@ -237,6 +285,15 @@ pub(crate) fn get_state_trie<N: PartialTrie>(
     get_trie(memory, ptr, read_state_rlp_value)
 }
 
+pub(crate) fn get_storage_trie<N: PartialTrie>(
+    memory: &MemoryState,
+    ptr: usize,
+) -> Result<N, ProgramError> {
+    get_trie(memory, ptr, |_, x| {
+        Ok(rlp::encode(&read_storage_trie_value(x)).to_vec())
+    })
+}
+
 pub(crate) fn get_txn_trie<N: PartialTrie>(
     memory: &MemoryState,
     ptr: usize,
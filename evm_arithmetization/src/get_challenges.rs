@@ -68,6 +68,7 @@ fn observe_block_metadata<
     for i in 0..8 {
         challenger.observe_elements(&u256_limbs(block_metadata.block_bloom[i]));
     }
+    challenger.observe_element(F::from_canonical_u64(block_metadata.block_hardfork.id()));
 
     Ok(())
 }
@@ -92,6 +93,7 @@ fn observe_block_metadata_target<
     challenger.observe_elements(&block_metadata.block_base_fee);
     challenger.observe_element(block_metadata.block_gas_used);
     challenger.observe_elements(&block_metadata.block_bloom);
+    challenger.observe_element(block_metadata.block_hardfork);
 }
 
 fn observe_extra_block_data<
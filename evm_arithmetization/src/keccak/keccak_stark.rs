@@ -10,6 +10,7 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::plonk_common::reduce_with_powers_ext_circuit;
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
+use plonky2_maybe_rayon::*;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use starky::evaluation_frame::StarkEvaluationFrame;
 use starky::lookup::{Column, Filter};
@@ -67,7 +68,7 @@ impl<F: RichField + Extendable<D>, const D: usize> KeccakStark<F, D> {
     /// Generate the rows of the trace. Note that this does not generate the
     /// permuted columns used in our lookup arguments, as those are computed
     /// after transposing to column-wise form.
-    fn generate_trace_rows(
+    pub(crate) fn generate_trace_rows(
         &self,
         inputs_and_timestamps: Vec<([u64; NUM_INPUTS], usize)>,
         min_rows: usize,
@@ -76,15 +77,15 @@ impl<F: RichField + Extendable<D>, const D: usize> KeccakStark<F, D> {
             .max(min_rows)
             .next_power_of_two();
 
-        let mut rows = Vec::with_capacity(num_rows);
-        for input_and_timestamp in inputs_and_timestamps.iter() {
-            let rows_for_perm = self.generate_trace_rows_for_perm(*input_and_timestamp);
-            rows.extend(rows_for_perm);
-        }
-
-        while rows.len() < num_rows {
-            rows.push([F::ZERO; NUM_COLUMNS]);
-        }
+        // Each permutation is self-contained, so their rows can be generated in
+        // parallel. Padding rows are left as zeros.
+        let mut rows = vec![[F::ZERO; NUM_COLUMNS]; num_rows];
+        rows.par_chunks_mut(NUM_ROUNDS)
+            .zip(inputs_and_timestamps.par_iter())
+            .for_each(|(rows_for_perm, &input_and_timestamp)| {
+                rows_for_perm
+                    .copy_from_slice(&self.generate_trace_rows_for_perm(input_and_timestamp));
+            });
         rows
     }
 
@@ -687,6 +688,52 @@ mod tests {
         Ok(())
     }
 
+    /// Generates the trace rows one permutation after the other, as a
+    /// reference for `generate_trace_rows`.
+    fn generate_trace_rows_sequentially<F: RichField + Extendable<D>, const D: usize>(
+        stark: &KeccakStark<F, D>,
+        inputs_and_timestamps: &[([u64; NUM_INPUTS], usize)],
+        min_rows: usize,
+    ) -> Vec<[F; NUM_COLUMNS]> {
+        let num_rows = (inputs_and_timestamps.len() * NUM_ROUNDS)
+            .max(min_rows)
+            .next_power_of_two();
+        let mut rows = inputs_and_timestamps
+            .iter()
+            .flat_map(|&input_and_timestamp| {
+                stark.generate_trace_rows_for_perm(input_and_timestamp)
+            })
+            .collect::<Vec<_>>();
+        rows.resize(num_rows, [F::ZERO; NUM_COLUMNS]);
+        rows
+    }
+
+    #[test]
+    fn test_trace_rows_match_sequential_generation() {
+        const NUM_PERMS: usize = 1000;
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = KeccakStark<F, D>;
+        let stark = S::default();
+
+        init_logger();
+
+        let inputs: Vec<([u64; NUM_INPUTS], usize)> =
+            (0..NUM_PERMS).map(|i| (rand::random(), i)).collect();
+
+        let mut timing = TimingTree::new("generate trace rows", log::Level::Debug);
+        let expected = timed!(
+            timing,
+            "sequential generation",
+            generate_trace_rows_sequentially(&stark, &inputs, 8)
+        );
+        let rows = timed!(timing, "in parallel", stark.generate_trace_rows(inputs, 8));
+        timing.print();
+
+        assert_eq!(rows, expected);
+    }
+
     #[test]
     fn keccak_benchmark() -> Result<()> {
         const NUM_PERMS: usize = 85;
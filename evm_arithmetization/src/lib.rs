@@ -65,7 +65,7 @@
 //! public inputs, as such:
 //!
 //! ```ignore
-//! let public_values = PublicValues::from_public_inputs(&proof.public_inputs);
+//! let public_values = PublicValues::from_public_inputs(&proof.public_inputs)?;
 //! ```
 //!
 //! ## Aggregation proofs
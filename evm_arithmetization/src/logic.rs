@@ -195,7 +195,7 @@ impl<F: RichField, const D: usize> LogicStark<F, D> {
     /// Generate the `LogicStark` traces based on the provided vector of
     /// operations. The trace is padded to a power of two with all-zero
     /// rows.
-    fn generate_trace_rows(
+    pub(crate) fn generate_trace_rows(
         &self,
         operations: Vec<Operation>,
         min_rows: usize,
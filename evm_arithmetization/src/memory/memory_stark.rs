@@ -139,6 +139,13 @@ impl<F: RichField + Extendable<D>, const D: usize> MemoryStark<F, D> {
     fn generate_trace_row_major(&self, mut memory_ops: Vec<MemoryOp>) -> Vec<[F; NUM_COLUMNS]> {
         // fill_gaps expects an ordered list of operations.
         memory_ops.sort_by_key(MemoryOp::sorting_key);
+        debug_assert!(
+            memory_ops
+                .iter()
+                .tuple_windows()
+                .all(|(op, next_op)| !op.conflicts_with(next_op)),
+            "Conflicting memory operations at the same address and timestamp"
+        );
         Self::fill_gaps(&mut memory_ops);
 
         Self::pad_memory_ops(&mut memory_ops);
@@ -602,6 +609,8 @@ pub(crate) mod tests {
     use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
 
     use crate::memory::memory_stark::MemoryStark;
+    use crate::memory::segments::Segment;
+    use crate::witness::memory::{MemoryAddress, MemoryChannel, MemoryOp, MemoryOpKind};
 
     #[test]
     fn test_stark_degree() -> Result<()> {
@@ -628,4 +637,34 @@ pub(crate) mod tests {
         };
         test_stark_circuit_constraints::<F, C, S, D>(stark)
     }
+
+    #[test]
+    fn test_memory_op_timestamps() {
+        let address = MemoryAddress::new(0, Segment::MainMemory, 42);
+        let write =
+            |channel, clock| MemoryOp::new(channel, clock, address, MemoryOpKind::Write, 1.into());
+
+        // Writes to the same address in a cycle, on different channels, or in
+        // later cycles, have distinct and increasing timestamps.
+        let ops = [
+            write(MemoryChannel::GeneralPurpose(0), 7),
+            write(MemoryChannel::GeneralPurpose(1), 7),
+            write(MemoryChannel::Code, 8),
+        ];
+        for (op, next_op) in ops.iter().zip(&ops[1..]) {
+            assert!(op.timestamp < next_op.timestamp);
+            assert!(!op.conflicts_with(next_op));
+        }
+
+        // Two writes on the same channel in the same cycle conflict.
+        let op = write(MemoryChannel::GeneralPurpose(0), 7);
+        assert!(op.conflicts_with(&ops[0]));
+        // A padding row repeating an operation doesn't.
+        let padding = MemoryOp {
+            filter: false,
+            kind: MemoryOpKind::Read,
+            ..op
+        };
+        assert!(!padding.conflicts_with(&op));
+    }
 }
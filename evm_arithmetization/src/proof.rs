@@ -1,4 +1,4 @@
-use ethereum_types::{Address, H256, U256};
+use ethereum_types::{Address, Bloom, H256, U256};
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::{BoolTarget, Target};
@@ -58,7 +58,11 @@ impl PublicValues {
     /// Extracts public values from the given public inputs of a proof.
     /// Public values are always the first public inputs added to the circuit,
     /// so we can start extracting at index 0.
-    pub fn from_public_inputs<F: RichField>(pis: &[F]) -> Self {
+    ///
+    /// Fails with `PublicValuesError::UnsupportedHardfork` if the hardfork
+    /// identifier isn't one of `Hardfork`. Such public inputs can't come from a
+    /// valid proof, as the kernel rejects unsupported hardforks.
+    pub fn from_public_inputs<F: RichField>(pis: &[F]) -> Result<Self, PublicValuesError> {
         assert!(
             pis.len()
                 > TrieRootsTarget::SIZE * 2
@@ -73,7 +77,7 @@ impl PublicValues {
             TrieRoots::from_public_inputs(&pis[TrieRootsTarget::SIZE..TrieRootsTarget::SIZE * 2]);
         let block_metadata = BlockMetadata::from_public_inputs(
             &pis[TrieRootsTarget::SIZE * 2..TrieRootsTarget::SIZE * 2 + BlockMetadataTarget::SIZE],
-        );
+        )?;
         let block_hashes = BlockHashes::from_public_inputs(
             &pis[TrieRootsTarget::SIZE * 2 + BlockMetadataTarget::SIZE
                 ..TrieRootsTarget::SIZE * 2 + BlockMetadataTarget::SIZE + BlockHashesTarget::SIZE],
@@ -86,16 +90,122 @@ impl PublicValues {
                     + ExtraBlockDataTarget::SIZE],
         );
 
-        Self {
+        Ok(Self {
             trie_roots_before,
             trie_roots_after,
             block_metadata,
             block_hashes,
             extra_block_data,
+        })
+    }
+
+    /// Checks the internal consistency of the public values.
+    ///
+    /// These checks are purely arithmetic, and allow a verifier to cheaply
+    /// reject malformed public inputs before verifying a proof. They do not
+    /// replace proof verification.
+    pub fn sanity_check(&self) -> Result<(), PublicValuesError> {
+        let metadata = &self.block_metadata;
+        let extra = &self.extra_block_data;
+
+        if metadata.block_gas_used > metadata.block_gaslimit {
+            return Err(PublicValuesError::BlockGasUsedAboveLimit {
+                gas_used: metadata.block_gas_used,
+                gas_limit: metadata.block_gaslimit,
+            });
         }
+        if extra.txn_number_after < extra.txn_number_before {
+            return Err(PublicValuesError::TxnNumberDecreased {
+                before: extra.txn_number_before,
+                after: extra.txn_number_after,
+            });
+        }
+        if extra.gas_used_after < extra.gas_used_before {
+            return Err(PublicValuesError::GasUsedDecreased {
+                before: extra.gas_used_before,
+                after: extra.gas_used_after,
+            });
+        }
+        if extra.gas_used_after > metadata.block_gas_used {
+            return Err(PublicValuesError::GasUsedAboveBlockGasUsed {
+                gas_used_after: extra.gas_used_after,
+                block_gas_used: metadata.block_gas_used,
+            });
+        }
+        if extra.txn_number_after == extra.txn_number_before
+            && extra.gas_used_after != extra.gas_used_before
+        {
+            return Err(PublicValuesError::GasUsedWithoutTxn {
+                before: extra.gas_used_before,
+                after: extra.gas_used_after,
+            });
+        }
+
+        Ok(())
     }
 }
 
+/// Inconsistencies detected by `PublicValues::sanity_check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicValuesError {
+    /// The block used more gas than its gas limit.
+    BlockGasUsedAboveLimit { gas_used: U256, gas_limit: U256 },
+    /// The transaction count decreased.
+    TxnNumberDecreased { before: U256, after: U256 },
+    /// The cumulative gas used decreased.
+    GasUsedDecreased { before: U256, after: U256 },
+    /// The cumulative gas used exceeds the gas used by the whole block.
+    GasUsedAboveBlockGasUsed {
+        gas_used_after: U256,
+        block_gas_used: U256,
+    },
+    /// Gas was used although no transaction was executed.
+    GasUsedWithoutTxn { before: U256, after: U256 },
+    /// The hardfork identifier doesn't match any `Hardfork`.
+    UnsupportedHardfork(u64),
+}
+
+impl core::fmt::Display for PublicValuesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PublicValuesError::BlockGasUsedAboveLimit {
+                gas_used,
+                gas_limit,
+            } => write!(
+                f,
+                "block gas used {} exceeds the block gas limit {}",
+                gas_used, gas_limit
+            ),
+            PublicValuesError::TxnNumberDecreased { before, after } => write!(
+                f,
+                "transaction number decreased from {} to {}",
+                before, after
+            ),
+            PublicValuesError::GasUsedDecreased { before, after } => {
+                write!(f, "gas used decreased from {} to {}", before, after)
+            }
+            PublicValuesError::GasUsedAboveBlockGasUsed {
+                gas_used_after,
+                block_gas_used,
+            } => write!(
+                f,
+                "gas used after {} exceeds the block gas used {}",
+                gas_used_after, block_gas_used
+            ),
+            PublicValuesError::GasUsedWithoutTxn { before, after } => write!(
+                f,
+                "gas used changed from {} to {} without any transaction",
+                before, after
+            ),
+            PublicValuesError::UnsupportedHardfork(id) => {
+                write!(f, "unsupported hardfork identifier {}", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PublicValuesError {}
+
 /// Trie hashes.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TrieRoots {
@@ -174,8 +284,12 @@ pub struct BlockMetadata {
     pub block_timestamp: U256,
     /// The index of this block.
     pub block_number: U256,
-    /// The difficulty (before PoS transition) of this block.
+    /// The difficulty (before PoS transition) of this block. It isn't read by
+    /// any opcode.
     pub block_difficulty: U256,
+    /// The `prevRandao` value (after PoS transition) of this block, i.e. the
+    /// `mixHash` field of its header. This is what `PREVRANDAO`, formerly
+    /// `DIFFICULTY`, pushes.
     pub block_random: H256,
     /// The gas limit of this block. It must fit in a `u32`.
     pub block_gaslimit: U256,
@@ -188,10 +302,55 @@ pub struct BlockMetadata {
     /// The block bloom of this block, represented as the consecutive
     /// 32-byte chunks of a block's final bloom filter string.
     pub block_bloom: [U256; 8],
+    /// The hardfork whose gas schedule applies to this block. Block metadata
+    /// serialized before this field existed deserializes to Shanghai.
+    #[serde(default)]
+    pub block_hardfork: Hardfork,
+}
+
+/// The hardforks whose gas schedules are supported by the kernel.
+///
+/// Only the gas schedule depends on the hardfork: the kernel otherwise always
+/// follows Shanghai. In particular, an `Istanbul` block still accepts access
+/// lists and the opcodes introduced since Istanbul, e.g. `BASEFEE` and
+/// `PUSH0`, so it can't be used to prove historical Istanbul blocks making use
+/// of the semantics which changed since.
+///
+/// The discriminant is the value stored in the `BlockHardfork` global
+/// metadata field, and exposed as a public input after the block bloom. The
+/// kernel rejects any other value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Hardfork {
+    /// Shanghai, with the cold/warm access costs of EIP-2929 and the reduced
+    /// refunds of EIP-3529.
+    #[default]
+    Shanghai = 0,
+    /// The gas schedule of Istanbul, with the flat access costs of EIP-1884,
+    /// the `SSTORE` schedule of EIP-2200 and a maximum refund of half the gas
+    /// used.
+    Istanbul = 1,
+}
+
+impl Hardfork {
+    /// Returns the hardfork with the given identifier, if any.
+    pub fn from_id(id: u64) -> Option<Self> {
+        match id {
+            0 => Some(Self::Shanghai),
+            1 => Some(Self::Istanbul),
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier of this hardfork.
+    pub const fn id(self) -> u64 {
+        self as u64
+    }
 }
 
 impl BlockMetadata {
-    pub fn from_public_inputs<F: RichField>(pis: &[F]) -> Self {
+    /// Extracts the block metadata from the given public inputs, which should
+    /// start with them. Fails if the hardfork identifier isn't supported.
+    pub fn from_public_inputs<F: RichField>(pis: &[F]) -> Result<Self, PublicValuesError> {
         assert!(pis.len() == BlockMetadataTarget::SIZE);
 
         let block_beneficiary = get_h160(&pis[0..5]);
@@ -205,8 +364,11 @@ impl BlockMetadata {
             (pis[18].to_canonical_u64() + (pis[19].to_canonical_u64() << 32)).into();
         let block_gas_used = pis[20].to_canonical_u64().into();
         let block_bloom = core::array::from_fn(|i| h2u(get_h256(&pis[21 + 8 * i..29 + 8 * i])));
+        let hardfork_id = pis[85].to_canonical_u64();
+        let block_hardfork = Hardfork::from_id(hardfork_id)
+            .ok_or(PublicValuesError::UnsupportedHardfork(hardfork_id))?;
 
-        Self {
+        Ok(Self {
             block_beneficiary,
             block_timestamp,
             block_number,
@@ -217,10 +379,92 @@ impl BlockMetadata {
             block_base_fee,
             block_gas_used,
             block_bloom,
+            block_hardfork,
+        })
+    }
+
+    /// Builds the metadata of a block from its header, as returned by the
+    /// `eth_getBlockByNumber` JSON-RPC method with hex-encoded fields.
+    ///
+    /// The chain id isn't part of a block header, and must be provided. Blocks
+    /// prior to London have no base fee, which is then set to zero. The
+    /// hardfork is left to its default value.
+    pub fn from_rpc_block(
+        value: &serde_json::Value,
+        chain_id: U256,
+    ) -> Result<Self, BlockMetadataParseError> {
+        let field = |name: &'static str| {
+            value
+                .get(name)
+                .map(|field| {
+                    field
+                        .as_str()
+                        .ok_or(BlockMetadataParseError::InvalidField(name))
+                })
+                .transpose()
+        };
+        let required = |name| field(name)?.ok_or(BlockMetadataParseError::MissingField(name));
+        let parse_u256 = |name, hex: &str| {
+            U256::from_str_radix(hex.strip_prefix("0x").unwrap_or(hex), 16)
+                .map_err(|_| BlockMetadataParseError::InvalidField(name))
+        };
+        let u256 = |name| parse_u256(name, required(name)?);
+        fn parse_hash<T: core::str::FromStr>(
+            name: &'static str,
+            hex: &str,
+        ) -> Result<T, BlockMetadataParseError> {
+            hex.parse()
+                .map_err(|_| BlockMetadataParseError::InvalidField(name))
+        }
+
+        let bloom: Bloom = parse_hash("logsBloom", required("logsBloom")?)?;
+        let block_base_fee = match field("baseFeePerGas")? {
+            Some(hex) => parse_u256("baseFeePerGas", hex)?,
+            None => U256::zero(),
+        };
+
+        Ok(Self {
+            block_beneficiary: parse_hash("miner", required("miner")?)?,
+            block_timestamp: u256("timestamp")?,
+            block_number: u256("number")?,
+            block_difficulty: u256("difficulty")?,
+            block_random: parse_hash("mixHash", required("mixHash")?)?,
+            block_gaslimit: u256("gasLimit")?,
+            block_chain_id: chain_id,
+            block_base_fee,
+            block_gas_used: u256("gasUsed")?,
+            block_bloom: core::array::from_fn(|i| {
+                U256::from_big_endian(&bloom.as_bytes()[32 * i..32 * (i + 1)])
+            }),
+            block_hardfork: Hardfork::default(),
+        })
+    }
+}
+
+/// An error building a `BlockMetadata` from a JSON-RPC block header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockMetadataParseError {
+    /// A required header field is missing.
+    MissingField(&'static str),
+    /// A header field isn't a hex-encoded value of the expected size.
+    InvalidField(&'static str),
+}
+
+impl core::fmt::Display for BlockMetadataParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BlockMetadataParseError::MissingField(name) => {
+                write!(f, "missing block header field {}", name)
+            }
+            BlockMetadataParseError::InvalidField(name) => {
+                write!(f, "invalid block header field {}", name)
+            }
         }
     }
 }
 
+impl std::error::Error for BlockMetadataParseError {}
+
 /// Additional block data that are specific to the local transaction being
 /// proven, unlike `BlockMetadata`.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
@@ -312,6 +556,7 @@ impl PublicValuesTarget {
             block_base_fee,
             block_gas_used,
             block_bloom,
+            block_hardfork,
         } = self.block_metadata;
 
         buffer.write_target_array(&block_beneficiary)?;
@@ -324,6 +569,7 @@ impl PublicValuesTarget {
         buffer.write_target_array(&block_base_fee)?;
         buffer.write_target(block_gas_used)?;
         buffer.write_target_array(&block_bloom)?;
+        buffer.write_target(block_hardfork)?;
 
         let BlockHashesTarget {
             prev_hashes,
@@ -373,6 +619,7 @@ impl PublicValuesTarget {
             block_base_fee: buffer.read_target_array()?,
             block_gas_used: buffer.read_target()?,
             block_bloom: buffer.read_target_array()?,
+            block_hardfork: buffer.read_target()?,
         };
 
         let block_hashes = BlockHashesTarget {
@@ -574,11 +821,15 @@ pub struct BlockMetadataTarget {
     pub(crate) block_gas_used: Target,
     /// `Target`s for the block bloom of this block.
     pub(crate) block_bloom: [Target; 64],
+    /// `Target` for the hardfork identifier of this block.
+    pub(crate) block_hardfork: Target,
 }
 
 impl BlockMetadataTarget {
-    /// Number of `Target`s required for the block metadata.
-    pub(crate) const SIZE: usize = 85;
+    /// Number of `Target`s required for the block metadata. The last one, the
+    /// hardfork identifier, was added after the block bloom, which shifted the
+    /// public inputs following the block metadata by one.
+    pub(crate) const SIZE: usize = 86;
 
     /// Extracts block metadata `Target`s from the provided public input
     /// `Target`s. The provided `pis` should start with the block metadata.
@@ -593,6 +844,7 @@ impl BlockMetadataTarget {
         let block_base_fee = pis[18..20].try_into().unwrap();
         let block_gas_used = pis[20];
         let block_bloom = pis[21..85].try_into().unwrap();
+        let block_hardfork = pis[85];
 
         Self {
             block_beneficiary,
@@ -605,6 +857,7 @@ impl BlockMetadataTarget {
             block_base_fee,
             block_gas_used,
             block_bloom,
+            block_hardfork,
         }
     }
 
@@ -639,6 +892,7 @@ impl BlockMetadataTarget {
             block_bloom: core::array::from_fn(|i| {
                 builder.select(condition, bm0.block_bloom[i], bm1.block_bloom[i])
             }),
+            block_hardfork: builder.select(condition, bm0.block_hardfork, bm1.block_hardfork),
         }
     }
 
@@ -666,6 +920,7 @@ impl BlockMetadataTarget {
         for i in 0..64 {
             builder.connect(bm0.block_bloom[i], bm1.block_bloom[i])
         }
+        builder.connect(bm0.block_hardfork, bm1.block_hardfork);
     }
 }
 
@@ -824,3 +1079,213 @@ impl ExtraBlockDataTarget {
         builder.connect(ed0.gas_used_after, ed1.gas_used_after);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A London-era block header, before the merge.
+    fn london_header() -> serde_json::Value {
+        serde_json::json!({
+            "baseFeePerGas": "0x3b9aca00",
+            "difficulty": "0x1bc7a0c3b6c3d0",
+            "extraData": "0x",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x1b3e79",
+            "hash": "0x9b83c12c69edb74f6c8dd5d052765c1adf940e320bd1291696e6fa07829eee71",
+            "logsBloom": format!("0x{}{}", "00".repeat(255), "80"),
+            "miner": "0x7777788200b672a42421017f65ede4fc759564c8",
+            "mixHash": "0x1a4c4a5c7a8528ef467d48bcdea9a0f4bdad7c2b0c6b1d66a9769f1ef3fd7b2b",
+            "nonce": "0x10bd31d4c1f7a7a4",
+            "number": "0xc5d488",
+            "parentHash": "0x3de6bb3849a138e6ab0b83a3a00dc7433f1e83f7fd488e4bba78f2fe2631a633",
+            "timestamp": "0x610bddf3",
+        })
+    }
+
+    /// A Cancun-era block header, after the merge.
+    fn cancun_header() -> serde_json::Value {
+        serde_json::json!({
+            "baseFeePerGas": "0x6f6a3e8b",
+            "blobGasUsed": "0x40000",
+            "difficulty": "0x0",
+            "excessBlobGas": "0x0",
+            "extraData": "0x6265617665726275696c642e6f7267",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0xe4e1c0",
+            "hash": "0x5c5e7ad8fb0b4a3b70bdc3f9d7e7dd6cd3a2ba4fa06076ad63a97a94411667b2",
+            "logsBloom": format!("0x01{}", "00".repeat(255)),
+            "miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+            "mixHash": "0x2f3b5d44b0b1f02ffa0ed3027bd7d2ac6b3d765c1e4e2f5fd8a0e41ad3e74d2a",
+            "number": "0x12a05f2",
+            "parentBeaconBlockRoot": "0x63dd0e4a6fa5c7a3a3ad6a0aa1b0c5e85c7ff6bd1d493b8e09aac1b37a2ba4ad",
+            "timestamp": "0x65f3d2a3",
+            "withdrawalsRoot": "0x1e7c0fc1e9c1d4cbc512b824cfd8e2dfd9a666b6d0ae6daea9b9e161d4217f25",
+        })
+    }
+
+    #[test]
+    fn test_block_metadata_from_rpc_block() {
+        let mut bloom = [U256::zero(); 8];
+        bloom[7] = 0x80.into();
+        assert_eq!(
+            BlockMetadata::from_rpc_block(&london_header(), 1.into()),
+            Ok(BlockMetadata {
+                block_beneficiary: Address::from(hex_literal::hex!(
+                    "7777788200b672a42421017f65ede4fc759564c8"
+                )),
+                block_timestamp: 0x610bddf3.into(),
+                block_number: 12_965_000.into(),
+                block_difficulty: 0x1bc7a0c3b6c3d0u64.into(),
+                block_random: H256(hex_literal::hex!(
+                    "1a4c4a5c7a8528ef467d48bcdea9a0f4bdad7c2b0c6b1d66a9769f1ef3fd7b2b"
+                )),
+                block_gaslimit: 30_000_000.into(),
+                block_chain_id: 1.into(),
+                block_base_fee: 1_000_000_000.into(),
+                block_gas_used: 0x1b3e79.into(),
+                block_bloom: bloom,
+                block_hardfork: Hardfork::default(),
+            })
+        );
+
+        let metadata = BlockMetadata::from_rpc_block(&cancun_header(), 1.into()).unwrap();
+        assert_eq!(metadata.block_number, 19_531_250.into());
+        assert_eq!(metadata.block_difficulty, U256::zero());
+        assert_eq!(
+            metadata.block_random,
+            H256(hex_literal::hex!(
+                "2f3b5d44b0b1f02ffa0ed3027bd7d2ac6b3d765c1e4e2f5fd8a0e41ad3e74d2a"
+            ))
+        );
+        assert_eq!(metadata.block_base_fee, 0x6f6a3e8b.into());
+        assert_eq!(metadata.block_bloom[0], U256::one() << 248);
+
+        // Blocks prior to London have no base fee.
+        let mut header = london_header();
+        header.as_object_mut().unwrap().remove("baseFeePerGas");
+        let metadata = BlockMetadata::from_rpc_block(&header, 1.into()).unwrap();
+        assert_eq!(metadata.block_base_fee, U256::zero());
+
+        header.as_object_mut().unwrap().remove("miner");
+        assert_eq!(
+            BlockMetadata::from_rpc_block(&header, 1.into()),
+            Err(BlockMetadataParseError::MissingField("miner"))
+        );
+        header["miner"] = "0x1234".into();
+        assert_eq!(
+            BlockMetadata::from_rpc_block(&header, 1.into()),
+            Err(BlockMetadataParseError::InvalidField("miner"))
+        );
+    }
+
+    /// Public values of a block with two transactions, the first of which
+    /// has been executed.
+    fn public_values() -> PublicValues {
+        PublicValues {
+            block_metadata: BlockMetadata {
+                block_gaslimit: 30_000_000.into(),
+                block_gas_used: 50_000.into(),
+                ..Default::default()
+            },
+            extra_block_data: ExtraBlockData {
+                txn_number_before: 0.into(),
+                txn_number_after: 1.into(),
+                gas_used_before: 0.into(),
+                gas_used_after: 21_000.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_block_metadata_from_public_inputs_rejects_unsupported_hardfork() {
+        use plonky2::field::goldilocks_field::GoldilocksField as F;
+        use plonky2::field::types::Field;
+
+        let mut pis = vec![F::ZERO; BlockMetadataTarget::SIZE];
+        pis[85] = F::from_canonical_u64(Hardfork::Istanbul.id());
+        assert_eq!(
+            BlockMetadata::from_public_inputs(&pis).map(|metadata| metadata.block_hardfork),
+            Ok(Hardfork::Istanbul)
+        );
+
+        pis[85] = F::TWO;
+        assert_eq!(
+            BlockMetadata::from_public_inputs(&pis),
+            Err(PublicValuesError::UnsupportedHardfork(2))
+        );
+    }
+
+    #[test]
+    fn test_sanity_check_valid() {
+        assert_eq!(public_values().sanity_check(), Ok(()));
+        assert_eq!(PublicValues::default().sanity_check(), Ok(()));
+    }
+
+    #[test]
+    fn test_sanity_check_block_gas_used_above_limit() {
+        let mut public_values = public_values();
+        public_values.block_metadata.block_gas_used = 30_000_001.into();
+        assert_eq!(
+            public_values.sanity_check(),
+            Err(PublicValuesError::BlockGasUsedAboveLimit {
+                gas_used: 30_000_001.into(),
+                gas_limit: 30_000_000.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_sanity_check_txn_number_decreased() {
+        let mut public_values = public_values();
+        public_values.extra_block_data.txn_number_before = 2.into();
+        assert_eq!(
+            public_values.sanity_check(),
+            Err(PublicValuesError::TxnNumberDecreased {
+                before: 2.into(),
+                after: 1.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_sanity_check_gas_used_decreased() {
+        let mut public_values = public_values();
+        public_values.extra_block_data.gas_used_before = 30_000.into();
+        assert_eq!(
+            public_values.sanity_check(),
+            Err(PublicValuesError::GasUsedDecreased {
+                before: 30_000.into(),
+                after: 21_000.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_sanity_check_gas_used_above_block_gas_used() {
+        let mut public_values = public_values();
+        public_values.extra_block_data.gas_used_after = 60_000.into();
+        assert_eq!(
+            public_values.sanity_check(),
+            Err(PublicValuesError::GasUsedAboveBlockGasUsed {
+                gas_used_after: 60_000.into(),
+                block_gas_used: 50_000.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_sanity_check_gas_used_without_txn() {
+        let mut public_values = public_values();
+        public_values.extra_block_data.txn_number_after = 0.into();
+        assert_eq!(
+            public_values.sanity_check(),
+            Err(PublicValuesError::GasUsedWithoutTxn {
+                before: 0.into(),
+                after: 21_000.into(),
+            })
+        );
+    }
+}
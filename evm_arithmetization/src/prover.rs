@@ -2,7 +2,6 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use hashbrown::HashMap;
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use plonky2::field::extension::Extendable;
@@ -70,6 +69,12 @@ where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
 {
+    // This is an expensive check, hence is only run when `debug_assertions` are
+    // enabled. Running it before any commitment reports witness generation bugs
+    // as the mismatching CTL row, rather than as a failed proof.
+    #[cfg(debug_assertions)]
+    crate::verifier::debug_utils::check_ctls(all_stark, &trace_poly_values, &public_values);
+
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
 
@@ -141,26 +146,6 @@ where
         )?
     );
 
-    // This is an expensive check, hence is only run when `debug_assertions` are
-    // enabled.
-    #[cfg(debug_assertions)]
-    {
-        use starky::cross_table_lookup::debug_utils::check_ctls;
-
-        use crate::verifier::debug_utils::get_memory_extra_looking_values;
-
-        let mut extra_values = HashMap::new();
-        extra_values.insert(
-            *Table::Memory,
-            get_memory_extra_looking_values(&public_values),
-        );
-        check_ctls(
-            &trace_poly_values,
-            &all_stark.cross_table_lookups,
-            &extra_values,
-        );
-    }
-
     Ok(AllProof {
         multi_proof: MultiProof {
             stark_proofs,
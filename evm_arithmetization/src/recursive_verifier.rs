@@ -358,6 +358,10 @@ pub(crate) fn get_memory_extra_looking_sum_circuit<F: RichField + Extendable<D>,
             GlobalMetadata::BlockGasUsed,
             public_values.block_metadata.block_gas_used,
         ),
+        (
+            GlobalMetadata::BlockHardfork,
+            public_values.block_metadata.block_hardfork,
+        ),
         (
             GlobalMetadata::BlockGasUsedBefore,
             public_values.extra_block_data.gas_used_before,
@@ -594,6 +598,7 @@ pub(crate) fn add_virtual_block_metadata<F: RichField + Extendable<D>, const D:
     let block_base_fee = builder.add_virtual_public_input_arr();
     let block_gas_used = builder.add_virtual_public_input();
     let block_bloom = builder.add_virtual_public_input_arr();
+    let block_hardfork = builder.add_virtual_public_input();
     BlockMetadataTarget {
         block_beneficiary,
         block_timestamp,
@@ -605,6 +610,7 @@ pub(crate) fn add_virtual_block_metadata<F: RichField + Extendable<D>, const D:
         block_base_fee,
         block_gas_used,
         block_bloom,
+        block_hardfork,
     }
 }
 
@@ -778,6 +784,10 @@ where
         limbs.copy_from_slice(&u256_limbs(block_metadata.block_bloom[i]));
     }
     witness.set_target_arr(&block_metadata_target.block_bloom, &block_bloom_limbs);
+    witness.set_target(
+        block_metadata_target.block_hardfork,
+        F::from_canonical_u64(block_metadata.block_hardfork.id()),
+    );
 
     Ok(())
 }
@@ -190,6 +190,10 @@ where
             GlobalMetadata::BlockGasUsed,
             public_values.block_metadata.block_gas_used,
         ),
+        (
+            GlobalMetadata::BlockHardfork,
+            public_values.block_metadata.block_hardfork.id().into(),
+        ),
         (
             GlobalMetadata::TxnNumberBefore,
             public_values.extra_block_data.txn_number_before,
@@ -283,8 +287,39 @@ where
 }
 
 pub(crate) mod debug_utils {
+    use hashbrown::HashMap;
+    use plonky2::field::polynomial::PolynomialValues;
+    use starky::cross_table_lookup::debug_utils;
+
     use super::*;
 
+    /// Checks that the given traces satisfy all the cross-table lookups of
+    /// `all_stark`. This is an expensive check, meant to catch witness
+    /// generation bugs before they turn into opaque proving failures.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the first mismatching CTL, with the row at fault and the
+    /// tables and row indices where it appears.
+    pub(crate) fn check_ctls<F, const D: usize>(
+        all_stark: &AllStark<F, D>,
+        trace_poly_values: &[Vec<PolynomialValues<F>>; NUM_TABLES],
+        public_values: &PublicValues,
+    ) where
+        F: RichField + Extendable<D>,
+    {
+        let mut extra_values = HashMap::new();
+        extra_values.insert(
+            *Table::Memory,
+            get_memory_extra_looking_values(public_values),
+        );
+        debug_utils::check_ctls(
+            trace_poly_values,
+            &all_stark.cross_table_lookups,
+            &extra_values,
+        );
+    }
+
     /// Output all the extra memory rows that don't appear in the CPU trace but
     /// are necessary to correctly check the MemoryStark CTL.
     pub(crate) fn get_memory_extra_looking_values<F, const D: usize>(
@@ -335,6 +370,10 @@ pub(crate) mod debug_utils {
                 GlobalMetadata::BlockGasUsed,
                 public_values.block_metadata.block_gas_used,
             ),
+            (
+                GlobalMetadata::BlockHardfork,
+                public_values.block_metadata.block_hardfork.id().into(),
+            ),
             (
                 GlobalMetadata::TxnNumberBefore,
                 public_values.extra_block_data.txn_number_before,
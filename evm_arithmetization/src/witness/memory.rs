@@ -15,6 +15,7 @@ use MemoryChannel::{Code, GeneralPurpose, PartialChannel};
 
 use super::operation::CONTEXT_SCALING_FACTOR;
 use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::generation::SegmentCapacities;
 use crate::memory::segments::{Segment, SEGMENT_SCALING_FACTOR};
 use crate::witness::errors::MemoryError::{ContextTooLarge, SegmentTooLarge, VirtTooLarge};
 use crate::witness::errors::ProgramError;
@@ -119,6 +120,13 @@ pub(crate) static DUMMY_MEMOP: MemoryOp = MemoryOp {
 };
 
 impl MemoryOp {
+    /// Creates a memory operation performed at the given CPU cycle.
+    ///
+    /// Its timestamp is `clock * NUM_CHANNELS + channel`, so timestamps are
+    /// strictly increasing across cycles, and the operations of a single cycle
+    /// on different channels are ordered by channel. Operations sharing a
+    /// channel within a cycle, such as the byte reads of a `MLOAD_32BYTES`,
+    /// must access distinct addresses.
     pub(crate) fn new(
         channel: MemoryChannel,
         clock: usize,
@@ -150,6 +158,17 @@ impl MemoryOp {
         }
     }
 
+    /// Returns whether `self` and `other` are operations at the same address
+    /// and timestamp, one of them being a write. Their order, and thus the
+    /// value read by later operations, would be ambiguous.
+    pub(crate) fn conflicts_with(&self, other: &MemoryOp) -> bool {
+        self.filter
+            && other.filter
+            && self.address == other.address
+            && self.timestamp == other.timestamp
+            && (self.kind == MemoryOpKind::Write || other.kind == MemoryOpKind::Write)
+    }
+
     pub(crate) const fn sorting_key(&self) -> (usize, usize, usize, usize) {
         (
             self.address.context,
@@ -164,12 +183,29 @@ impl MemoryOp {
 pub(crate) struct MemoryState {
     pub(crate) contexts: Vec<MemoryContextState>,
     preinitialized_segments: HashMap<Segment, MemorySegmentState>,
+    /// Allocation hints used when creating the kernel context and the
+    /// top-level context of the transaction.
+    segment_capacities: SegmentCapacities,
 }
 
 impl MemoryState {
     pub(crate) fn new(kernel_code: &[u8]) -> Self {
+        Self::new_with_capacities(kernel_code, SegmentCapacities::default())
+    }
+
+    /// Creates a new `MemoryState` whose kernel context and top-level
+    /// transaction context have their segments pre-allocated according to
+    /// `segment_capacities`.
+    pub(crate) fn new_with_capacities(
+        kernel_code: &[u8],
+        segment_capacities: SegmentCapacities,
+    ) -> Self {
         let code_u256s = kernel_code.iter().map(|&x| Some(x.into())).collect();
-        let mut result = Self::default();
+        let mut result = Self {
+            contexts: vec![MemoryContextState::with_capacities(&segment_capacities)],
+            preinitialized_segments: HashMap::default(),
+            segment_capacities,
+        };
         result.contexts[0].segments[Segment::Code.unscale()].content = code_u256s;
         result
     }
@@ -242,7 +278,15 @@ impl MemoryState {
 
     pub(crate) fn set(&mut self, address: MemoryAddress, val: U256) {
         while address.context >= self.contexts.len() {
-            self.contexts.push(MemoryContextState::default());
+            // Only the kernel context and the top-level context of the
+            // transaction are pre-allocated: the hints are sized for them, and
+            // reserving as much for every sub-call would waste memory.
+            let context = if self.contexts.len() <= 1 {
+                MemoryContextState::with_capacities(&self.segment_capacities)
+            } else {
+                MemoryContextState::default()
+            };
+            self.contexts.push(context);
         }
 
         let segment = Segment::all()[address.segment];
@@ -257,6 +301,33 @@ impl MemoryState {
         self.contexts[address.context].segments[address.segment].set(address.virt, val);
     }
 
+    /// Returns the number of addresses holding a value in each segment, across
+    /// all contexts. Segments without any value are omitted. In interpreter
+    /// mode, the preinitialized values of the kernel context count as well.
+    pub(crate) fn usage(&self) -> HashMap<Segment, usize> {
+        let mut usage = HashMap::new();
+        for (context, context_state) in self.contexts.iter().enumerate() {
+            for (segment, segment_state) in Segment::all().into_iter().zip(&context_state.segments)
+            {
+                let preinitialized = match self.preinitialized_segments.get(&segment) {
+                    Some(preinitialized) if context == 0 => preinitialized.content.as_slice(),
+                    _ => &[],
+                };
+                let is_set = |content: &[Option<U256>], i: usize| {
+                    content.get(i).is_some_and(|value| value.is_some())
+                };
+                let len = segment_state.content.len().max(preinitialized.len());
+                let count = (0..len)
+                    .filter(|&i| is_set(&segment_state.content, i) || is_set(preinitialized, i))
+                    .count();
+                if count > 0 {
+                    *usage.entry(segment).or_default() += count;
+                }
+            }
+        }
+        usage
+    }
+
     // These fields are already scaled by their respective segment.
     pub(crate) fn read_global_metadata(&self, field: GlobalMetadata) -> U256 {
         self.get_with_init(MemoryAddress::new_bundle(U256::from(field as usize)).unwrap())
@@ -296,6 +367,7 @@ impl Default for MemoryState {
             // We start with an initial context for the kernel.
             contexts: vec![MemoryContextState::default()],
             preinitialized_segments: HashMap::default(),
+            segment_capacities: SegmentCapacities::default(),
         }
     }
 }
@@ -314,6 +386,18 @@ impl Default for MemoryContextState {
     }
 }
 
+impl MemoryContextState {
+    /// Creates an empty context, reserving space in each segment according to
+    /// the provided `SegmentCapacities`.
+    pub(crate) fn with_capacities(capacities: &SegmentCapacities) -> Self {
+        Self {
+            segments: std::array::from_fn(|i| MemorySegmentState {
+                content: Vec::with_capacity(capacities.get(Segment::all()[i])),
+            }),
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 pub(crate) struct MemorySegmentState {
     pub(crate) content: Vec<Option<U256>>,
@@ -342,3 +426,74 @@ impl MemorySegmentState {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `len` consecutive bytes to the main memory of context 1, and
+    /// returns the number of times the segment had to be reallocated.
+    fn count_main_memory_reallocations(memory: &mut MemoryState, len: usize) -> usize {
+        let segment_capacity = |memory: &MemoryState| {
+            memory.contexts.get(1).map(|ctx| {
+                ctx.segments[Segment::MainMemory.unscale()]
+                    .content
+                    .capacity()
+            })
+        };
+
+        let mut reallocations = 0;
+        let mut capacity = None;
+        for virt in 0..len {
+            memory.set(
+                MemoryAddress::new(1, Segment::MainMemory, virt),
+                0x42.into(),
+            );
+            let new_capacity = segment_capacity(memory);
+            if capacity.is_some() && new_capacity != capacity {
+                reallocations += 1;
+            }
+            capacity = new_capacity;
+        }
+        reallocations
+    }
+
+    #[test]
+    fn test_segment_capacities_avoid_reallocations() {
+        const LEN: usize = 10_000;
+
+        let mut memory = MemoryState::new(&[]);
+        let reallocations_without_hints = count_main_memory_reallocations(&mut memory, LEN);
+
+        let capacities = SegmentCapacities {
+            main_memory: LEN,
+            ..Default::default()
+        };
+        let mut memory = MemoryState::new_with_capacities(&[], capacities);
+        let reallocations_with_hints = count_main_memory_reallocations(&mut memory, LEN);
+
+        assert!(reallocations_without_hints > 0);
+        assert_eq!(reallocations_with_hints, 0);
+        assert!(
+            memory.contexts[1].segments[Segment::MainMemory.unscale()]
+                .content
+                .capacity()
+                >= LEN
+        );
+        // Segments without hints are not pre-allocated.
+        assert_eq!(
+            memory.contexts[1].segments[Segment::Calldata.unscale()]
+                .content
+                .capacity(),
+            0
+        );
+        // Neither are the contexts of sub-calls.
+        memory.set(MemoryAddress::new(2, Segment::MainMemory, 0), 0x42.into());
+        assert!(
+            memory.contexts[2].segments[Segment::MainMemory.unscale()]
+                .content
+                .capacity()
+                < LEN
+        );
+    }
+}
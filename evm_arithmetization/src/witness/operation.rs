@@ -642,6 +642,7 @@ pub(crate) fn generate_syscall<F: Field, T: Transition<F>>(
     {
         return Err(ProgramError::StackOverflow);
     }
+    generation_state.observe_storage_syscall(opcode)?;
 
     let handler_jumptable_addr = KERNEL.global_labels["syscall_jumptable"];
     let handler_addr_addr =
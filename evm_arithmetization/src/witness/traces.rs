@@ -1,19 +1,44 @@
+use core::convert::Infallible;
+
 use plonky2::field::extension::Extendable;
 use plonky2::field::polynomial::PolynomialValues;
 use plonky2::hash::hash_types::RichField;
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
+use plonky2::util::transpose;
 use starky::config::StarkConfig;
 use starky::util::trace_rows_to_poly_values;
 
-use crate::all_stark::{AllStark, NUM_TABLES};
+use crate::all_stark::{AllStark, Table, NUM_TABLES};
 use crate::arithmetic::{BinaryOperator, Operation};
 use crate::byte_packing::byte_packing_stark::BytePackingOp;
-use crate::cpu::columns::CpuColumnsView;
+use crate::cpu::columns::{CpuColumnsView, NUM_CPU_COLUMNS};
 use crate::keccak_sponge::keccak_sponge_stark::KeccakSpongeOp;
 use crate::witness::memory::MemoryOp;
 use crate::{arithmetic, keccak, keccak_sponge, logic};
 
+/// Returns the number of rows taken by `op` in the arithmetic table.
+pub(crate) fn arithmetic_op_len(op: &arithmetic::Operation) -> usize {
+    match op {
+        Operation::TernaryOperation { .. } => 2,
+        Operation::BinaryOperation { operator, .. } => match operator {
+            BinaryOperator::Div | BinaryOperator::Mod => 2,
+            _ => 1,
+        },
+        Operation::RangeCheckOperation { .. } => 1,
+    }
+}
+
+/// Returns the number of rows taken by `op` in the byte packing table.
+pub(crate) fn byte_packing_op_len(op: &BytePackingOp) -> usize {
+    usize::from(!op.bytes.is_empty())
+}
+
+/// Returns the number of rows taken by `op` in the keccak sponge table.
+pub(crate) fn keccak_sponge_op_len(op: &KeccakSpongeOp) -> usize {
+    op.input.len() / keccak_sponge::columns::KECCAK_RATE_BYTES + 1
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct TraceCheckpoint {
     pub(self) arithmetic_len: usize,
@@ -53,29 +78,14 @@ impl<T: Copy> Traces<T> {
     //  Uses a `TraceCheckPoint` as return object for convenience.
     pub(crate) fn get_lengths(&self) -> TraceCheckpoint {
         TraceCheckpoint {
-            arithmetic_len: self
-                .arithmetic_ops
-                .iter()
-                .map(|op| match op {
-                    Operation::TernaryOperation { .. } => 2,
-                    Operation::BinaryOperation { operator, .. } => match operator {
-                        BinaryOperator::Div | BinaryOperator::Mod => 2,
-                        _ => 1,
-                    },
-                    Operation::RangeCheckOperation { .. } => 1,
-                })
-                .sum(),
-            byte_packing_len: self
-                .byte_packing_ops
-                .iter()
-                .map(|op| usize::from(!op.bytes.is_empty()))
-                .sum(),
+            arithmetic_len: self.arithmetic_ops.iter().map(arithmetic_op_len).sum(),
+            byte_packing_len: self.byte_packing_ops.iter().map(byte_packing_op_len).sum(),
             cpu_len: self.cpu.len(),
             keccak_len: self.keccak_inputs.len() * keccak::keccak_stark::NUM_ROUNDS,
             keccak_sponge_len: self
                 .keccak_sponge_ops
                 .iter()
-                .map(|op| op.input.len() / keccak_sponge::columns::KECCAK_RATE_BYTES + 1)
+                .map(keccak_sponge_op_len)
                 .sum(),
             logic_len: self.logic_ops.len(),
             // This is technically a lower-bound, as we may fill gaps,
@@ -122,6 +132,28 @@ impl<T: Copy> Traces<T> {
         config: &StarkConfig,
         timing: &mut TimingTree,
     ) -> [Vec<PolynomialValues<T>>; NUM_TABLES]
+    where
+        T: RichField + Extendable<D>,
+    {
+        let mut tables: [Vec<PolynomialValues<T>>; NUM_TABLES] = Default::default();
+        self.for_each_table(all_stark, config, timing, |table, trace| {
+            tables[*table] = trace;
+            Ok::<_, Infallible>(())
+        })
+        .unwrap_or_else(|never| match never {});
+        tables
+    }
+
+    /// Same as `into_tables`, but hands each table to `sink` as soon as it is
+    /// generated, in the order of `Table::all`, so that no more than one table
+    /// needs to be held in memory at once. Stops at the first error of `sink`.
+    pub(crate) fn for_each_table<const D: usize, E>(
+        self,
+        all_stark: &AllStark<T, D>,
+        config: &StarkConfig,
+        timing: &mut TimingTree,
+        mut sink: impl FnMut(Table, Vec<PolynomialValues<T>>) -> Result<(), E>,
+    ) -> Result<(), E>
     where
         T: RichField + Extendable<D>,
     {
@@ -141,6 +173,7 @@ impl<T: Copy> Traces<T> {
             "generate arithmetic trace",
             all_stark.arithmetic_stark.generate_trace(arithmetic_ops)
         );
+        sink(Table::Arithmetic, arithmetic_trace)?;
         let byte_packing_trace = timed!(
             timing,
             "generate byte packing trace",
@@ -148,8 +181,10 @@ impl<T: Copy> Traces<T> {
                 .byte_packing_stark
                 .generate_trace(byte_packing_ops, cap_elements, timing)
         );
+        sink(Table::BytePacking, byte_packing_trace)?;
         let cpu_rows = cpu.into_iter().map(|x| x.into()).collect();
         let cpu_trace = trace_rows_to_poly_values(cpu_rows);
+        sink(Table::Cpu, cpu_trace)?;
         let keccak_trace = timed!(
             timing,
             "generate Keccak trace",
@@ -157,6 +192,7 @@ impl<T: Copy> Traces<T> {
                 .keccak_stark
                 .generate_trace(keccak_inputs, cap_elements, timing)
         );
+        sink(Table::Keccak, keccak_trace)?;
         let keccak_sponge_trace = timed!(
             timing,
             "generate Keccak sponge trace",
@@ -164,6 +200,7 @@ impl<T: Copy> Traces<T> {
                 .keccak_sponge_stark
                 .generate_trace(keccak_sponge_ops, cap_elements, timing)
         );
+        sink(Table::KeccakSponge, keccak_sponge_trace)?;
         let logic_trace = timed!(
             timing,
             "generate logic trace",
@@ -171,11 +208,99 @@ impl<T: Copy> Traces<T> {
                 .logic_stark
                 .generate_trace(logic_ops, cap_elements, timing)
         );
+        sink(Table::Logic, logic_trace)?;
         let memory_trace = timed!(
             timing,
             "generate memory trace",
             all_stark.memory_stark.generate_trace(memory_ops, timing)
         );
+        sink(Table::Memory, memory_trace)
+    }
+
+    /// Same as `into_tables`, but returns the rows of each table instead of
+    /// its columns.
+    ///
+    /// The CPU, Keccak and logic tables are generated row-wise, and their rows
+    /// are returned as is. The other tables fill some columns, such as range
+    /// check frequencies, after transposing their rows, so their final columns
+    /// are transposed back.
+    pub(crate) fn into_raw_tables<const D: usize>(
+        self,
+        all_stark: &AllStark<T, D>,
+        config: &StarkConfig,
+        timing: &mut TimingTree,
+    ) -> [Vec<Vec<T>>; NUM_TABLES]
+    where
+        T: RichField + Extendable<D>,
+    {
+        let cap_elements = config.fri_config.num_cap_elements();
+        let Traces {
+            arithmetic_ops,
+            byte_packing_ops,
+            cpu,
+            logic_ops,
+            memory_ops,
+            keccak_inputs,
+            keccak_sponge_ops,
+        } = self;
+
+        let to_rows = |polys: Vec<PolynomialValues<T>>| {
+            let cols: Vec<_> = polys.into_iter().map(|poly| poly.values).collect();
+            transpose(&cols)
+        };
+
+        let arithmetic_trace = timed!(
+            timing,
+            "generate arithmetic trace",
+            to_rows(all_stark.arithmetic_stark.generate_trace(arithmetic_ops))
+        );
+        let byte_packing_trace = timed!(
+            timing,
+            "generate byte packing trace",
+            to_rows(all_stark.byte_packing_stark.generate_trace(
+                byte_packing_ops,
+                cap_elements,
+                timing
+            ))
+        );
+        let cpu_trace = cpu
+            .into_iter()
+            .map(|row| <[T; NUM_CPU_COLUMNS]>::from(row).to_vec())
+            .collect();
+        let keccak_trace = timed!(
+            timing,
+            "generate Keccak trace",
+            all_stark
+                .keccak_stark
+                .generate_trace_rows(keccak_inputs, cap_elements)
+                .into_iter()
+                .map(|row| row.to_vec())
+                .collect()
+        );
+        let keccak_sponge_trace = timed!(
+            timing,
+            "generate Keccak sponge trace",
+            to_rows(all_stark.keccak_sponge_stark.generate_trace(
+                keccak_sponge_ops,
+                cap_elements,
+                timing
+            ))
+        );
+        let logic_trace = timed!(
+            timing,
+            "generate logic trace",
+            all_stark
+                .logic_stark
+                .generate_trace_rows(logic_ops, cap_elements)
+                .into_iter()
+                .map(|row| row.to_vec())
+                .collect()
+        );
+        let memory_trace = timed!(
+            timing,
+            "generate memory trace",
+            to_rows(all_stark.memory_stark.generate_trace(memory_ops, timing))
+        );
 
         [
             arithmetic_trace,
@@ -20,6 +20,9 @@ use crate::witness::state::RegistersState;
 use crate::witness::util::mem_read_code_with_log_and_fill;
 use crate::{arithmetic, logic};
 
+/// Fetches the opcode at the current program counter. Code memory past the
+/// end of the loaded bytecode reads as zero, so running off the end of the
+/// code decodes to `STOP` and halts successfully with empty return data.
 pub(crate) fn read_code_memory<F: Field, T: Transition<F>>(
     state: &mut T,
     row: &mut CpuColumnsView<F>,
@@ -157,6 +160,8 @@ pub(crate) fn decode(registers: RegistersState, opcode: u8) -> Result<Operation,
         (0xfc, true) => Ok(Operation::MstoreGeneral),
         (0xfd, _) => Ok(Operation::Syscall(opcode, 2, false)), // REVERT
         (0xff, _) => Ok(Operation::Syscall(opcode, 1, false)), // SELFDESTRUCT
+        // This includes the designated `INVALID` opcode (0xfe), which is handled
+        // by the kernel like any other undefined opcode.
         _ => {
             log::warn!("Invalid opcode: {}", opcode);
             Err(ProgramError::InvalidOpcode)
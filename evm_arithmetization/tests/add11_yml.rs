@@ -87,6 +87,7 @@ fn add11_yml() -> anyhow::Result<()> {
         block_base_fee: 0xa.into(),
         block_gas_used: 0xa868u64.into(),
         block_bloom: [0.into(); 8],
+        ..Default::default()
     };
 
     let mut contract_code = HashMap::new();
@@ -114,6 +114,7 @@ fn test_basic_smart_contract() -> anyhow::Result<()> {
         block_bloom: [0.into(); 8],
         block_base_fee: 0xa.into(),
         block_random: Default::default(),
+        ..Default::default()
     };
 
     let mut contract_code = HashMap::new();
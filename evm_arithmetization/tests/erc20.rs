@@ -93,6 +93,7 @@ fn test_erc20() -> anyhow::Result<()> {
         block_base_fee: 0xa.into(),
         block_gas_used: gas_used,
         block_bloom: bloom,
+        ..Default::default()
     };
 
     let contract_code = [giver_bytecode(), token_bytecode(), vec![]]
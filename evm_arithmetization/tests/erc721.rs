@@ -157,6 +157,7 @@ fn test_erc721() -> anyhow::Result<()> {
         block_base_fee: 0xa.into(),
         block_gas_used: gas_used,
         block_bloom: bloom.try_into().unwrap(),
+        ..Default::default()
     };
 
     let inputs = GenerationInputs {
@@ -143,6 +143,7 @@ fn test_log_opcodes() -> anyhow::Result<()> {
         block_base_fee: 0xa.into(),
         block_gas_used: 0.into(),
         block_bloom: [0.into(); 8],
+        ..Default::default()
     };
 
     let mut contract_code = HashMap::new();
@@ -363,6 +364,7 @@ fn test_log_with_aggreg() -> anyhow::Result<()> {
             U256::from_dec_str("2722259584404615024560450425766186844160").unwrap(),
         ],
         block_random: Default::default(),
+        ..Default::default()
     };
 
     let beneficiary_account_after = AccountRlp {
@@ -103,6 +103,7 @@ fn self_balance_gas_cost() -> anyhow::Result<()> {
         block_bloom: [0.into(); 8],
         block_base_fee: 0xa.into(),
         block_random: Default::default(),
+        ..Default::default()
     };
 
     let mut contract_code = HashMap::new();
@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     fmt::{self, Display, Formatter},
     iter::{self, empty, once},
+    ops::Range,
 };
 
 use ethereum_types::{Address, H256, U256};
@@ -56,6 +57,10 @@ pub enum TraceParsingError {
     /// Failure due to trying to withdraw from a missing account
     #[error("No account present at {0:x} (hashed: {1:x}) to withdraw {2} Gwei from!")]
     MissingWithdrawalAccount(Address, HashedAccountAddr, U256),
+
+    /// Failure due to requesting a range of txns outside of the block.
+    #[error("Txn range {0:?} is out of bounds for a block with {1} txn proofs")]
+    TxnRangeOutOfBounds(Range<usize>, usize),
 }
 
 /// An enum to cover all Ethereum trie types (see https://ethereum.github.io/yellowpaper/paper.pdf for details).
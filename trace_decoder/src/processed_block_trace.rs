@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::iter::once;
+use std::ops::Range;
 
 use ethereum_types::{Address, H256, U256};
 use evm_arithmetization::generation::mpt::{AccountRlp, LegacyReceiptRlp};
@@ -10,7 +11,7 @@ use mpt_trie::partial_trie::{HashedPartialTrie, PartialTrie};
 use crate::compact::compact_prestate_processing::{
     process_compact_prestate_debug, PartialTriePreImages,
 };
-use crate::decoding::TraceParsingResult;
+use crate::decoding::{TraceParsingError, TraceParsingResult};
 use crate::trace_protocol::{
     BlockTrace, BlockTraceTriePreImages, CombinedPreImages, ContractCodeUsage,
     SeparateStorageTriesPreImage, SeparateTriePreImage, SeparateTriePreImages, TrieCompact,
@@ -52,6 +53,36 @@ impl BlockTrace {
         processed_block_trace.into_txn_proof_gen_ir(other_data)
     }
 
+    /// Processes and returns the [GenerationInputs] for the transactions of the
+    /// block within `txn_range`.
+    ///
+    /// The transactions preceding the range are still processed, so that the
+    /// first returned input starts from the state of the block after
+    /// transaction `txn_range.start - 1`, with its `txn_number_before` and
+    /// `gas_used_before` set accordingly. Indices refer to the inputs returned
+    /// by [BlockTrace::into_txn_proof_gen_ir], which include the dummy
+    /// transactions padding blocks with fewer than two transactions.
+    pub fn into_txn_proof_gen_ir_range<F>(
+        self,
+        p_meta: &ProcessingMeta<F>,
+        other_data: OtherBlockData,
+        txn_range: Range<usize>,
+    ) -> TraceParsingResult<Vec<TxnProofGenIR>>
+    where
+        F: CodeHashResolveFunc,
+    {
+        let mut txn_gen_inputs = self.into_txn_proof_gen_ir(p_meta, other_data)?;
+
+        if txn_range.start > txn_range.end || txn_range.end > txn_gen_inputs.len() {
+            return Err(TraceParsingError::TxnRangeOutOfBounds(
+                txn_range,
+                txn_gen_inputs.len(),
+            ));
+        }
+
+        Ok(txn_gen_inputs.drain(txn_range).collect())
+    }
+
     fn into_processed_block_trace<F>(
         self,
         p_meta: &ProcessingMeta<F>,
@@ -157,9 +188,12 @@ fn process_single_combined_storage_tries(
 }
 
 fn process_multiple_storage_tries(
-    _tries: HashMap<HashedAccountAddr, SeparateTriePreImage>,
+    tries: HashMap<HashedAccountAddr, SeparateTriePreImage>,
 ) -> HashMap<HashedAccountAddr, HashedPartialTrie> {
-    todo!()
+    tries
+        .into_iter()
+        .map(|(h_addr, trie)| (h_addr, process_state_trie(trie)))
+        .collect()
 }
 
 fn process_compact_trie(trie: TrieCompact) -> ProcessedBlockTracePreImages {
@@ -400,3 +434,124 @@ pub(crate) struct TxnMetaState {
     pub(crate) receipt_node_bytes: Vec<u8>,
     pub(crate) gas_used: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use evm_arithmetization::proof::{BlockHashes, BlockMetadata};
+
+    use super::*;
+    use crate::trace_protocol::{TrieDirect, TxnMeta, TxnTrace};
+    use crate::types::BlockLevelData;
+
+    /// A block of `num_txns` transfers of 1 wei from `sender` to `receiver`.
+    fn transfers_block_trace(sender: Address, receiver: Address, num_txns: usize) -> BlockTrace {
+        let mut state_trie = HashedPartialTrie::from(mpt_trie::partial_trie::Node::Empty);
+        for (address, balance) in [(sender, 100), (receiver, 0)] {
+            let account = AccountRlp {
+                balance: balance.into(),
+                ..Default::default()
+            };
+            state_trie.insert(
+                Nibbles::from_h256_be(hash(address.as_bytes())),
+                rlp::encode(&account).to_vec(),
+            );
+        }
+
+        let txn_info = (0..num_txns)
+            .map(|i| {
+                let transfer = |balance: u64, nonce: Option<U256>| TxnTrace {
+                    balance: Some(balance.into()),
+                    nonce,
+                    storage_read: None,
+                    storage_written: None,
+                    code_usage: None,
+                    self_destructed: None,
+                };
+                let traces = HashMap::from([
+                    (sender, transfer(99 - i as u64, Some((i + 1).into()))),
+                    (receiver, transfer(i as u64 + 1, None)),
+                ]);
+
+                TxnInfo {
+                    traces,
+                    meta: TxnMeta {
+                        byte_code: vec![0xf8, i as u8],
+                        new_txn_trie_node_byte: vec![],
+                        new_receipt_trie_node_byte: rlp::encode(&vec![0xaa, i as u8]).to_vec(),
+                        gas_used: 21000,
+                    },
+                }
+            })
+            .collect();
+
+        BlockTrace {
+            trie_pre_images: BlockTraceTriePreImages::Separate(SeparateTriePreImages {
+                state: SeparateTriePreImage::Direct(TrieDirect(state_trie)),
+                storage: SeparateStorageTriesPreImage::MultipleTries(HashMap::new()),
+            }),
+            txn_info,
+        }
+    }
+
+    fn other_block_data() -> OtherBlockData {
+        OtherBlockData {
+            b_data: BlockLevelData {
+                b_meta: BlockMetadata::default(),
+                b_hashes: BlockHashes {
+                    prev_hashes: vec![H256::zero(); 256],
+                    cur_hash: H256::zero(),
+                },
+                withdrawals: vec![],
+            },
+            checkpoint_state_trie_root: EMPTY_TRIE_HASH,
+        }
+    }
+
+    #[test]
+    fn test_txn_range_matches_full_block() -> TraceParsingResult<()> {
+        let sender = Address::from_low_u64_be(1);
+        let receiver = Address::from_low_u64_be(2);
+        let p_meta = ProcessingMeta::new(|_: &CodeHash| vec![]);
+
+        let full = transfers_block_trace(sender, receiver, 3)
+            .into_txn_proof_gen_ir(&p_meta, other_block_data())?;
+        let range = transfers_block_trace(sender, receiver, 3).into_txn_proof_gen_ir_range(
+            &p_meta,
+            other_block_data(),
+            1..2,
+        )?;
+        assert_eq!(range.len(), 1);
+
+        // The proven txn starts from the state after the first txn of the block.
+        let inputs = &range[0];
+        assert_eq!(inputs.txn_number_before, 1.into());
+        assert_eq!(inputs.gas_used_before, 21000.into());
+        assert_eq!(inputs.gas_used_after, 42000.into());
+        assert_eq!(
+            inputs.tries.state_trie.hash(),
+            full[0].trie_roots_after.state_root
+        );
+        assert_eq!(
+            inputs.tries.transactions_trie.hash(),
+            full[0].trie_roots_after.transactions_root
+        );
+        assert_eq!(
+            inputs.tries.receipts_trie.hash(),
+            full[0].trie_roots_after.receipts_root
+        );
+        assert_eq!(inputs.trie_roots_after, full[1].trie_roots_after);
+        assert_eq!(inputs.signed_txn, full[1].signed_txn);
+
+        let out_of_bounds = transfers_block_trace(sender, receiver, 3).into_txn_proof_gen_ir_range(
+            &p_meta,
+            other_block_data(),
+            2..4,
+        );
+        assert!(matches!(
+            out_of_bounds,
+            Err(TraceParsingError::TxnRangeOutOfBounds(_, 3))
+        ));
+
+        Ok(())
+    }
+}